@@ -0,0 +1,188 @@
+//! A tour of the whole crate in an eframe app: persistence, a custom font loader, presets and
+//! previewing an apply
+//!
+//! The configuration is saved in eframe's storage when the user clicks the save button, and
+//! restored on the next start. Run it with `cargo run --example full_demo`.
+
+use {
+    eframe::egui::{self, FontDefinitions, FontFamily},
+    egui_fontcfg::{
+        dry_run_apply, load_fonts_by_priority, new_family, report_family_use, ApplyReport,
+        BundledFont, CustomFontPaths, FamilyTemplate, FontCfgUi, FontCfgWindow, FontConfigSnapshot,
+        FontDefsUiMsg, FontLoadError, FontOrigin, FontPreset,
+    },
+};
+
+/// Font data the application ships with
+const HACK: &[u8] = include_bytes!("../tests/fixtures/Hack-Regular.ttf");
+
+/// Prefix of the paths served from the application's own assets rather than the file system
+const ASSET_PREFIX: &str = "asset:";
+
+/// Key of the saved configuration in eframe's storage
+const STORAGE_KEY: &str = "egui-fontcfg";
+
+/// The named family the application uses for headings
+fn heading() -> FontFamily {
+    FontFamily::Name("Heading".into())
+}
+
+/// Custom paths starting with `asset:` are served from memory, the rest from disk
+fn load_font(path: &str) -> Result<Vec<u8>, FontLoadError> {
+    match path.strip_prefix(ASSET_PREFIX) {
+        Some("Hack") => Ok(HACK.to_vec()),
+        Some(_) => Err(FontLoadError::new(path, "Not among the assets")),
+        None => std::fs::read(path).map_err(|e| FontLoadError::new(path, e)),
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "egui-fontcfg demo",
+        eframe::NativeOptions::default(),
+        Box::new(|cc| Ok(Box::new(App::new(cc)))),
+    )
+}
+
+struct App {
+    font_defs: FontDefinitions,
+    custom: CustomFontPaths,
+    window: FontCfgWindow,
+    /// Why the saved configuration couldn't be restored, if it couldn't
+    restore_error: Option<String>,
+    /// The last preview of applying the fonts
+    report: Option<ApplyReport>,
+}
+
+impl App {
+    fn new(cc: &eframe::CreationContext) -> Self {
+        // The fonts the application registers itself
+        let mut font_defs = FontDefinitions::default();
+        new_family(
+            &mut font_defs,
+            "Heading",
+            &FamilyTemplate::CopyOfProportional,
+        );
+        let mut custom = CustomFontPaths::default();
+        custom.insert("asset-hack".into(), format!("{ASSET_PREFIX}Hack"));
+        font_defs
+            .families
+            .entry(heading())
+            .or_default()
+            .insert(0, "asset-hack".into());
+        let bundled = BundledFont::new("bundled-hack", HACK, "Hack, shipped with the app");
+        let resolutions = load_fonts_by_priority(
+            &FontOrigin::DEFAULT_PRIORITY,
+            Some(&load_font),
+            &custom,
+            std::slice::from_ref(&bundled),
+            &mut font_defs,
+        );
+        let defaults = FontPreset {
+            font_defs: font_defs.clone(),
+            custom: custom.clone(),
+        };
+
+        // Restore what the user saved last time
+        let saved = cc
+            .storage
+            .and_then(|storage| storage.get_string(STORAGE_KEY));
+        let mut family_order = vec![heading()];
+        let mut restore_error = None;
+        if let Some(text) = saved {
+            let restored = FontConfigSnapshot::parse(&text)
+                .map_err(|e| e.to_string())
+                .and_then(|snapshot| {
+                    let (mut font_defs, mut custom) = (font_defs.clone(), custom.clone());
+                    snapshot
+                        .apply_to_defs(Some(&load_font), &mut font_defs, &mut custom)
+                        .map_err(|e| e.to_string())?;
+                    Ok((font_defs, custom, snapshot.family_order()))
+                });
+            match restored {
+                Ok(restored) => (font_defs, custom, family_order) = restored,
+                Err(e) => restore_error = Some(e),
+            }
+        }
+
+        let mut ui = FontCfgUi::default()
+            .font_source(load_font)
+            .bundled_font(bundled)
+            .font_resolutions(resolutions)
+            .family_order(family_order)
+            .max_size([400.0, 600.0]);
+        // Presets the user can switch between, e.g. with hotkeys or in `FontCfgUi::show_kiosk`
+        ui.presets_mut().insert("Default", defaults);
+        ui.presets_mut().insert(
+            "egui defaults",
+            FontPreset {
+                font_defs: FontDefinitions::default(),
+                custom: CustomFontPaths::default(),
+            },
+        );
+        if let Err(e) = egui_fontcfg::apply(&cc.egui_ctx, &font_defs) {
+            restore_error = Some(e.to_string());
+        }
+        Self {
+            font_defs,
+            custom,
+            window: FontCfgWindow::new(ui),
+            restore_error,
+            report: None,
+        }
+    }
+    /// Persist the configuration, in the order the user arranged the families in
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let mut snapshot = FontConfigSnapshot::from_defs(&self.font_defs, &self.custom);
+        let order = self.window.ui_mut().family_display_order(&self.font_defs);
+        snapshot.order_families(&order);
+        storage.set_string(STORAGE_KEY, snapshot.to_text());
+        storage.flush();
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.toggle_value(&mut self.window.open, "Fonts");
+                // What applying the fonts as they are would do, without touching the context.
+                // It lays out text with the fonts, so it's only done on request.
+                if ui.button("Preview apply").clicked() {
+                    self.report = Some(dry_run_apply(&self.font_defs));
+                }
+                if let Some(report) = &self.report {
+                    ui.weak(format!(
+                        "Applying would hold {} KiB of fonts",
+                        report.font_bytes / 1024
+                    ));
+                    if !report.unused.is_empty() {
+                        ui.weak(format!("Unused: {}", report.unused.join(", ")));
+                    }
+                }
+            });
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(e) = &self.restore_error {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+            // Reporting which named families are used lets presets keep their fonts
+            report_family_use(ctx, &heading(), 1);
+            ui.label(egui::RichText::new("Heading").family(heading()).size(28.0));
+            ui.label("Body text, in the proportional family.");
+            ui.code("Code, in the monospace family.");
+        });
+        match self
+            .window
+            .show(ctx, &mut self.font_defs, Some(&mut self.custom))
+        {
+            FontDefsUiMsg::SaveRequest => {
+                if let Some(storage) = frame.storage_mut() {
+                    self.save(storage);
+                }
+            }
+            FontDefsUiMsg::Applied { .. } => self.restore_error = None,
+            _ => {}
+        }
+    }
+}
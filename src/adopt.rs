@@ -0,0 +1,106 @@
+//! Importing fonts from other font definitions
+
+use {
+    egui::FontDefinitions,
+    std::{collections::BTreeSet, sync::Arc},
+};
+
+/// Import the fonts called `names` from `source` into `target`, along with their family entries
+///
+/// Entries are appended to the end of the family in `target`, creating it if necessary.
+/// Fonts that `target` already has a different font of the same name for are skipped.
+/// Returns the names of the skipped fonts.
+pub fn import_fonts(
+    target: &mut FontDefinitions,
+    source: &FontDefinitions,
+    names: &BTreeSet<String>,
+) -> Vec<String> {
+    let mut skipped = Vec::new();
+    for name in names {
+        let Some(data) = source.font_data.get(name) else {
+            continue;
+        };
+        match target.font_data.get(name) {
+            Some(existing) if !Arc::ptr_eq(existing, data) && existing.font != data.font => {
+                skipped.push(name.clone());
+                continue;
+            }
+            Some(_) => {}
+            None => {
+                target.font_data.insert(name.clone(), data.clone());
+            }
+        }
+        for (family, fonts) in &source.families {
+            if !fonts.contains(name) {
+                continue;
+            }
+            let entries = target.families.entry(family.clone()).or_default();
+            if !entries.contains(name) {
+                entries.push(name.clone());
+            }
+        }
+    }
+    skipped
+}
+
+/// Fonts offered for import by [`FontCfgUi::adopt_fonts`](crate::FontCfgUi::adopt_fonts)
+pub(crate) struct AdoptForm {
+    source: FontDefinitions,
+    selected: BTreeSet<String>,
+}
+
+impl AdoptForm {
+    pub(crate) fn new(source: FontDefinitions) -> Self {
+        Self {
+            source,
+            selected: BTreeSet::new(),
+        }
+    }
+    /// Show the list of offered fonts. Returns whether the form is done, and which fonts were
+    /// skipped if any were imported.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+    ) -> (bool, Vec<String>) {
+        for name in self.source.font_data.keys() {
+            ui.horizontal_wrapped(|ui| {
+                let mut checked = self.selected.contains(name);
+                if ui.checkbox(&mut checked, name).changed() {
+                    if checked {
+                        self.selected.insert(name.clone());
+                    } else {
+                        self.selected.remove(name);
+                    }
+                }
+                if font_defs.font_data.contains_key(name) {
+                    ui.weak("(exists)");
+                }
+                let families: Vec<_> = self
+                    .source
+                    .families
+                    .iter()
+                    .filter(|(_, fonts)| fonts.contains(name))
+                    .map(|(family, _)| family.to_string())
+                    .collect();
+                if !families.is_empty() {
+                    ui.weak(families.join(", "));
+                }
+            });
+        }
+        let mut result = (false, Vec::new());
+        ui.horizontal(|ui| {
+            let re = ui.add_enabled(
+                !self.selected.is_empty(),
+                egui::Button::new("Import selected"),
+            );
+            if re.clicked() {
+                result = (true, import_fonts(font_defs, &self.source, &self.selected));
+            }
+            if ui.button("Cancel").clicked() {
+                result.0 = true;
+            }
+        });
+        result
+    }
+}
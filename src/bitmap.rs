@@ -0,0 +1,788 @@
+//! Converting BDF and PCF bitmap fonts into TrueType fonts egui can render
+//!
+//! Every run of set pixels in a row becomes a rectangle in the outline, so the glyphs look
+//! like the original bitmaps when rendered at the font's pixel size or multiples of it.
+
+/// A glyph of a bitmap font
+struct BitmapGlyph {
+    /// Unicode code point
+    code: u32,
+    /// Horizontal advance in pixels
+    advance: i32,
+    /// Offset of the bitmap's left edge from the origin
+    left: i32,
+    /// Offset of the bitmap's bottom row from the baseline, positive upwards
+    bottom: i32,
+    /// Rows of pixels from top to bottom
+    rows: Vec<Vec<bool>>,
+}
+
+/// A bitmap font, with all measurements in pixels
+struct BitmapFont {
+    name: String,
+    ascent: i32,
+    descent: i32,
+    glyphs: Vec<BitmapGlyph>,
+}
+
+/// Convert a BDF or PCF font into a TrueType font
+///
+/// Character codes are taken to be Unicode code points, which is true for fonts with the
+/// ISO10646 and ISO8859-1 encodings.
+pub(crate) fn convert(data: &[u8]) -> Result<Vec<u8>, String> {
+    let font = if data.starts_with(b"\x01fcp") {
+        parse_pcf(data).ok_or("Malformed PCF font")?
+    } else {
+        let text = std::str::from_utf8(data).map_err(|_| "BDF font isn't valid text")?;
+        parse_bdf(text)?
+    };
+    if font.glyphs.is_empty() {
+        return Err("The font has no glyphs".into());
+    }
+    write_ttf(&font)
+}
+
+fn parse_bdf(text: &str) -> Result<BitmapFont, String> {
+    let mut font = BitmapFont {
+        name: String::new(),
+        ascent: 0,
+        descent: 0,
+        glyphs: Vec::new(),
+    };
+    let mut bbox_ascent = None;
+    let mut lines = text.lines().enumerate();
+    let int = |s: Option<&str>, line: usize| {
+        s.and_then(|s| s.parse::<i32>().ok())
+            .ok_or_else(|| format!("Line {}: Expected a number", line + 1))
+    };
+    while let Some((line_no, line)) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FAMILY_NAME") => {
+                font.name = line["FAMILY_NAME".len()..]
+                    .trim()
+                    .trim_matches('"')
+                    .to_owned();
+            }
+            Some("FONT_ASCENT") => font.ascent = int(words.next(), line_no)?,
+            Some("FONT_DESCENT") => font.descent = int(words.next(), line_no)?,
+            Some("FONTBOUNDINGBOX") => {
+                let _width = int(words.next(), line_no)?;
+                let height = int(words.next(), line_no)?;
+                let _x = int(words.next(), line_no)?;
+                let y = int(words.next(), line_no)?;
+                bbox_ascent = Some((height + y, -y));
+            }
+            Some("STARTCHAR") => {
+                let mut code = None;
+                let mut advance = 0;
+                let (mut width, mut height, mut left, mut bottom) = (0, 0, 0, 0);
+                let mut rows = Vec::new();
+                for (line_no, line) in lines.by_ref() {
+                    let mut words = line.split_whitespace();
+                    match words.next() {
+                        Some("ENCODING") => code = Some(int(words.next(), line_no)?),
+                        Some("DWIDTH") => advance = int(words.next(), line_no)?,
+                        Some("BBX") => {
+                            width = int(words.next(), line_no)?;
+                            height = int(words.next(), line_no)?;
+                            left = int(words.next(), line_no)?;
+                            bottom = int(words.next(), line_no)?;
+                        }
+                        Some("BITMAP") => {}
+                        Some("ENDCHAR") => break,
+                        Some(hex) if rows.len() < height as usize => {
+                            rows.push(hex_row(hex, width).ok_or_else(|| {
+                                format!("Line {}: Invalid bitmap row", line_no + 1)
+                            })?);
+                        }
+                        _ => {}
+                    }
+                }
+                // Glyphs without a standard encoding have an ENCODING of -1
+                if let Some(code) = code.and_then(|c| u32::try_from(c).ok()) {
+                    font.glyphs.push(BitmapGlyph {
+                        code,
+                        advance,
+                        left,
+                        bottom,
+                        rows,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    if font.ascent == 0 && font.descent == 0 {
+        (font.ascent, font.descent) =
+            bbox_ascent.ok_or("The font has no ascent or bounding box")?;
+    }
+    Ok(font)
+}
+
+/// Decode a row of a BDF bitmap, which is hex padded to whole bytes
+fn hex_row(hex: &str, width: i32) -> Option<Vec<bool>> {
+    let bytes: Vec<u8> = (0..hex.len() / 2)
+        .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+    Some(
+        (0..width.max(0) as usize)
+            .map(|x| bytes.get(x / 8).is_some_and(|b| b & (0x80 >> (x % 8)) != 0))
+            .collect(),
+    )
+}
+
+const PCF_METRICS: u32 = 1 << 2;
+const PCF_BITMAPS: u32 = 1 << 3;
+const PCF_BDF_ENCODINGS: u32 = 1 << 5;
+const PCF_BDF_ACCELERATORS: u32 = 1 << 8;
+const PCF_ACCELERATORS: u32 = 1 << 1;
+
+/// Reads the values of a PCF table, in the byte order its format specifies
+struct PcfTable<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+    format: u32,
+}
+
+impl<'a> PcfTable<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Option<Self> {
+        // The format is always little endian
+        let format = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?);
+        Some(Self {
+            data,
+            pos: offset + 4,
+            big_endian: format & (1 << 2) != 0,
+            format,
+        })
+    }
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+        Some(bytes)
+    }
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes::<1>()?[0])
+    }
+    fn i16(&mut self) -> Option<i16> {
+        let b = self.bytes()?;
+        Some(if self.big_endian {
+            i16::from_be_bytes(b)
+        } else {
+            i16::from_le_bytes(b)
+        })
+    }
+    fn u16(&mut self) -> Option<u16> {
+        Some(self.i16()? as u16)
+    }
+    fn u32(&mut self) -> Option<u32> {
+        let b = self.bytes()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        })
+    }
+}
+
+/// Metrics of a PCF glyph: left and right bearing, advance, ascent and descent
+type PcfMetrics = (i32, i32, i32, i32, i32);
+
+fn parse_pcf(data: &[u8]) -> Option<BitmapFont> {
+    let le32 = |pos: usize| Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?));
+    let mut tables = std::collections::HashMap::new();
+    for i in 0..le32(4)? as usize {
+        let entry = 8 + i * 16;
+        tables.insert(le32(entry)?, le32(entry + 12)? as usize);
+    }
+    let mut t = PcfTable::new(data, *tables.get(&PCF_METRICS)?)?;
+    let metrics: Vec<PcfMetrics> = if t.format & 0x100 != 0 {
+        // Compressed metrics, stored as bytes offset by 0x80
+        let count = usize::from(t.u16()?);
+        (0..count)
+            .map(|_| {
+                let mut b = || Some(i32::from(t.u8()?) - 0x80);
+                Some((b()?, b()?, b()?, b()?, b()?))
+            })
+            .collect::<Option<_>>()?
+    } else {
+        let count = t.u32()? as usize;
+        (0..count)
+            .map(|_| {
+                let m = (
+                    i32::from(t.i16()?),
+                    i32::from(t.i16()?),
+                    i32::from(t.i16()?),
+                    i32::from(t.i16()?),
+                    i32::from(t.i16()?),
+                );
+                t.i16()?;
+                Some(m)
+            })
+            .collect::<Option<_>>()?
+    };
+    let mut t = PcfTable::new(data, *tables.get(&PCF_BITMAPS)?)?;
+    let format = t.format;
+    let count = t.u32()? as usize;
+    let offsets: Vec<usize> = (0..count)
+        .map(|_| Some(t.u32()? as usize))
+        .collect::<Option<_>>()?;
+    let sizes = [t.u32()?, t.u32()?, t.u32()?, t.u32()?];
+    let bitmap_data = data.get(t.pos..t.pos + sizes[(format & 3) as usize] as usize)?;
+    let pad = 1 << (format & 3);
+    let msb_bit_first = format & (1 << 3) != 0;
+    let msb_byte_first = format & (1 << 2) != 0;
+    let scan_unit = 1 << ((format >> 4) & 3);
+    let mut t = PcfTable::new(data, *tables.get(&PCF_BDF_ENCODINGS)?)?;
+    let (min2, max2, min1, max1) = (t.u16()?, t.u16()?, t.u16()?, t.u16()?);
+    let _default = t.u16()?;
+    let mut glyphs = Vec::new();
+    for byte1 in min1..=max1 {
+        for byte2 in min2..=max2 {
+            let index = t.u16()?;
+            if index == 0xffff {
+                continue;
+            }
+            let (lsb, rsb, advance, ascent, descent) = *metrics.get(usize::from(index))?;
+            let width = (rsb - lsb).max(0) as usize;
+            let height = (ascent + descent).max(0) as usize;
+            let stride = width.div_ceil(8).div_ceil(pad) * pad;
+            let start = *offsets.get(usize::from(index))?;
+            let mut bytes = bitmap_data.get(start..start + stride * height)?.to_vec();
+            if !msb_bit_first {
+                for b in &mut bytes {
+                    *b = b.reverse_bits();
+                }
+            }
+            if msb_byte_first != msb_bit_first && scan_unit > 1 {
+                for unit in bytes.chunks_mut(scan_unit) {
+                    unit.reverse();
+                }
+            }
+            let rows = bytes
+                .chunks(stride.max(1))
+                .take(height)
+                .map(|row| {
+                    (0..width)
+                        .map(|x| row[x / 8] & (0x80 >> (x % 8)) != 0)
+                        .collect()
+                })
+                .collect();
+            glyphs.push(BitmapGlyph {
+                code: u32::from(byte1) << 8 | u32::from(byte2),
+                advance,
+                left: lsb,
+                bottom: -descent,
+                rows,
+            });
+        }
+    }
+    let accelerators = tables
+        .get(&PCF_BDF_ACCELERATORS)
+        .or_else(|| tables.get(&PCF_ACCELERATORS));
+    let (ascent, descent) = match accelerators {
+        Some(&offset) => {
+            let mut t = PcfTable::new(data, offset)?;
+            // Skip the flags, which take 8 bytes before the ascent and descent
+            t.pos += 8;
+            (t.u32()? as i32, t.u32()? as i32)
+        }
+        None => glyphs.iter().fold((0, 0), |(a, d), g| {
+            let top = g.bottom + g.rows.len() as i32;
+            (a.max(top), d.max(-g.bottom))
+        }),
+    };
+    Some(BitmapFont {
+        name: String::new(),
+        ascent,
+        descent,
+        glyphs,
+    })
+}
+
+/// Big endian binary output
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u16(&mut self, v: u16) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn i16(&mut self, v: i16) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn u32(&mut self, v: u32) {
+        self.0.extend(v.to_be_bytes());
+    }
+}
+
+/// Clamp to the range TrueType coordinates can have
+fn coord(v: i32) -> i16 {
+    v.clamp(i16::MIN.into(), i16::MAX.into()) as i16
+}
+
+/// Write a simple glyph whose contours are `rects` (x0, y0, x1, y1)
+fn write_glyph(out: &mut Writer, rects: &[(i32, i32, i32, i32)]) {
+    if rects.is_empty() {
+        return;
+    }
+    let x_min = rects.iter().map(|r| r.0).min().unwrap_or(0);
+    let y_min = rects.iter().map(|r| r.1).min().unwrap_or(0);
+    let x_max = rects.iter().map(|r| r.2).max().unwrap_or(0);
+    let y_max = rects.iter().map(|r| r.3).max().unwrap_or(0);
+    out.i16(coord(rects.len() as i32));
+    for v in [x_min, y_min, x_max, y_max] {
+        out.i16(coord(v));
+    }
+    for i in 0..rects.len() {
+        out.u16((i * 4 + 3) as u16);
+    }
+    // No instructions
+    out.u16(0);
+    // Every point is on the curve, with full 16 bit coordinates
+    out.0.extend(std::iter::repeat_n(1u8, rects.len() * 4));
+    // Clockwise, starting at the top left
+    let points =
+        |&(x0, y0, x1, y1): &(i32, i32, i32, i32)| [(x0, y1), (x1, y1), (x1, y0), (x0, y0)];
+    let mut last = 0;
+    for p in rects.iter().flat_map(points) {
+        out.i16(coord(p.0 - last));
+        last = p.0;
+    }
+    let mut last = 0;
+    for p in rects.iter().flat_map(points) {
+        out.i16(coord(p.1 - last));
+        last = p.1;
+    }
+}
+
+fn write_ttf(font: &BitmapFont) -> Result<Vec<u8>, String> {
+    let mut glyphs: Vec<&BitmapGlyph> = font.glyphs.iter().collect();
+    glyphs.sort_by_key(|g| g.code);
+    glyphs.dedup_by_key(|g| g.code);
+    // Including the .notdef glyph
+    let num_glyphs = u16::try_from(glyphs.len() + 1).map_err(|_| {
+        format!(
+            "The font has {} glyphs, but TrueType fonts can only have {}",
+            glyphs.len(),
+            u16::MAX - 1
+        )
+    })?;
+    let pixel_height = (font.ascent + font.descent).max(1);
+    // Units per pixel, keeping units per em within the allowed range
+    let unit = (16384 / pixel_height).clamp(1, 64);
+    let units_per_em = (pixel_height * unit).max(16);
+    let mut glyf = Writer::default();
+    let mut loca = Writer::default();
+    let mut hmtx = Writer::default();
+    let (mut max_points, mut max_contours, mut max_advance) = (0, 0, 0);
+    let (mut x_min, mut y_min, mut x_max, mut y_max) = (0, 0, 0, 0);
+    // Glyph 0 is the empty .notdef glyph
+    loca.u32(0);
+    hmtx.u16(coord(pixel_height * unit / 2) as u16);
+    hmtx.i16(0);
+    for glyph in &glyphs {
+        loca.u32(glyf.0.len() as u32);
+        let height = glyph.rows.len() as i32;
+        let mut rects = Vec::new();
+        for (y, row) in glyph.rows.iter().enumerate() {
+            let top = (glyph.bottom + height - y as i32) * unit;
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < row.len() && row[x] {
+                    x += 1;
+                }
+                let left = (glyph.left + start as i32) * unit;
+                let right = (glyph.left + x as i32) * unit;
+                rects.push((left, top - unit, right, top));
+            }
+        }
+        write_glyph(&mut glyf, &rects);
+        if glyf.0.len() % 2 == 1 {
+            glyf.0.push(0);
+        }
+        max_points = max_points.max(rects.len() * 4);
+        max_contours = max_contours.max(rects.len());
+        let advance = glyph.advance * unit;
+        max_advance = max_advance.max(advance);
+        let lsb = rects.iter().map(|r| r.0).min().unwrap_or(0);
+        hmtx.u16(coord(advance) as u16);
+        hmtx.i16(coord(lsb));
+        for r in &rects {
+            x_min = x_min.min(r.0);
+            y_min = y_min.min(r.1);
+            x_max = x_max.max(r.2);
+            y_max = y_max.max(r.3);
+        }
+    }
+    loca.u32(glyf.0.len() as u32);
+
+    let mut head = Writer::default();
+    head.u32(0x0001_0000);
+    head.u32(0x0001_0000);
+    // Checksum adjustment, which isn't checked by font parsers
+    head.u32(0);
+    head.u32(0x5F0F_3CF5);
+    // Baseline and left sidebearing at 0, integer scaling
+    head.u16(0b1011);
+    head.u16(units_per_em as u16);
+    // Created and modified dates
+    head.0.extend([0; 16]);
+    for v in [x_min, y_min, x_max, y_max] {
+        head.i16(coord(v));
+    }
+    head.u16(0);
+    // Smallest readable size in pixels
+    head.u16(pixel_height as u16);
+    head.i16(2);
+    // Long offsets in loca
+    head.i16(1);
+    head.i16(0);
+
+    let mut hhea = Writer::default();
+    hhea.u32(0x0001_0000);
+    hhea.i16(coord(font.ascent * unit));
+    hhea.i16(coord(-font.descent * unit));
+    hhea.i16(0);
+    hhea.u16(coord(max_advance) as u16);
+    hhea.i16(0);
+    hhea.i16(0);
+    hhea.i16(coord(x_max));
+    // Caret slope and offset, reserved fields, and metric data format
+    for v in [1, 0, 0, 0, 0, 0, 0, 0] {
+        hhea.i16(v);
+    }
+    hhea.u16(num_glyphs);
+
+    let mut maxp = Writer::default();
+    maxp.u32(0x0001_0000);
+    maxp.u16(num_glyphs);
+    maxp.u16(max_points.min(0xffff) as u16);
+    maxp.u16(max_contours.min(0xffff) as u16);
+    for v in [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0] {
+        maxp.u16(v);
+    }
+
+    // Format 12 cmap, with a group for each run of consecutive code points
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let id = i as u32 + 1;
+        match groups.last_mut() {
+            Some(last) if last.1 + 1 == glyph.code && last.2 + (last.1 - last.0) + 1 == id => {
+                last.1 = glyph.code;
+            }
+            _ => groups.push((glyph.code, glyph.code, id)),
+        }
+    }
+    let mut cmap = Writer::default();
+    cmap.u16(0);
+    cmap.u16(1);
+    // Windows platform, Unicode full repertoire
+    cmap.u16(3);
+    cmap.u16(10);
+    cmap.u32(12);
+    cmap.u16(12);
+    cmap.u16(0);
+    cmap.u32(16 + groups.len() as u32 * 12);
+    cmap.u32(0);
+    cmap.u32(groups.len() as u32);
+    for (start, end, id) in groups {
+        cmap.u32(start);
+        cmap.u32(end);
+        cmap.u32(id);
+    }
+
+    let name_text: Vec<u16> = if font.name.is_empty() {
+        "Bitmap font".encode_utf16().collect()
+    } else {
+        // Keep the string within the 16-bit length of the name record
+        font.name.encode_utf16().take(1024).collect()
+    };
+    let mut name = Writer::default();
+    name.u16(0);
+    // Family and full name records, sharing the same string
+    name.u16(2);
+    name.u16(6 + 2 * 12);
+    for id in [1, 4] {
+        for v in [3, 1, 0x409, id] {
+            name.u16(v);
+        }
+        name.u16(name_text.len() as u16 * 2);
+        name.u16(0);
+    }
+    for c in name_text {
+        name.u16(c);
+    }
+
+    let mut post = Writer::default();
+    post.u32(0x0003_0000);
+    post.u32(0);
+    post.i16(coord(-font.descent * unit));
+    post.i16(coord(unit));
+    post.0.extend([0; 20]);
+
+    let tables = [
+        (b"cmap", cmap.0),
+        (b"glyf", glyf.0),
+        (b"head", head.0),
+        (b"hhea", hhea.0),
+        (b"hmtx", hmtx.0),
+        (b"loca", loca.0),
+        (b"maxp", maxp.0),
+        (b"name", name.0),
+        (b"post", post.0),
+    ];
+    let mut out = Writer::default();
+    out.u32(0x0001_0000);
+    out.u16(tables.len() as u16);
+    // Search range, entry selector and range shift for 9 tables
+    out.u16(128);
+    out.u16(3);
+    out.u16(tables.len() as u16 * 16 - 128);
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in &tables {
+        out.0.extend(*tag);
+        out.u32(checksum(data));
+        out.u32(offset as u32);
+        out.u32(data.len() as u32);
+        offset += data.len().div_ceil(4) * 4;
+    }
+    for (_, data) in &tables {
+        out.0.extend(data);
+        out.0.resize(out.0.len().div_ceil(4) * 4, 0);
+    }
+    Ok(out.0)
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4)
+        .map(|c| {
+            let mut b = [0; 4];
+            b[..c.len()].copy_from_slice(c);
+            u32::from_be_bytes(b)
+        })
+        .fold(0, u32::wrapping_add)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::{
+            convert, write_ttf, BitmapFont, BitmapGlyph, PCF_ACCELERATORS, PCF_BDF_ENCODINGS,
+            PCF_BITMAPS, PCF_METRICS,
+        },
+        ttf_parser::{name_id, Face, GlyphId, OutlineBuilder, Rect},
+    };
+
+    /// A 4x4 "A" with a hollow middle and a solid 2x2 "B", 8 pixels high with 2 below the baseline
+    pub(crate) const BDF: &str = "STARTFONT 2.1
+FONT -test-fixed-medium-r-normal--8-80-75-75-c-40-iso10646-1
+SIZE 8 75 75
+FONTBOUNDINGBOX 4 8 0 -2
+STARTPROPERTIES 3
+FAMILY_NAME \"Test Fixed\"
+FONT_ASCENT 6
+FONT_DESCENT 2
+ENDPROPERTIES
+CHARS 3
+STARTCHAR A
+ENCODING 65
+SWIDTH 500 0
+DWIDTH 5 0
+BBX 4 4 0 0
+BITMAP
+F0
+90
+90
+F0
+ENDCHAR
+STARTCHAR B
+ENCODING 66
+DWIDTH 3 0
+BBX 2 2 1 -2
+BITMAP
+C0
+C0
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 3 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[derive(Default)]
+    struct Contours(usize);
+
+    impl OutlineBuilder for Contours {
+        fn move_to(&mut self, _: f32, _: f32) {
+            self.0 += 1;
+        }
+        fn line_to(&mut self, _: f32, _: f32) {}
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {}
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+        fn close(&mut self) {}
+    }
+
+    /// The number of contours and bounding box of the glyph for `c`, in pixels
+    fn glyph(face: &Face, c: char) -> (usize, Rect) {
+        let id = face.glyph_index(c).unwrap();
+        let mut contours = Contours::default();
+        let rect = face.outline_glyph(id, &mut contours).unwrap();
+        let unit = face.units_per_em() as i16 / 8;
+        let px = |v: i16| v / unit;
+        let rect = Rect {
+            x_min: px(rect.x_min),
+            y_min: px(rect.y_min),
+            x_max: px(rect.x_max),
+            y_max: px(rect.y_max),
+        };
+        (contours.0, rect)
+    }
+
+    /// Check the output of converting the font described by [`BDF`]
+    fn check_converted(ttf: &[u8], name: Option<&str>) {
+        let face = Face::parse(ttf, 0).unwrap();
+        assert_eq!(face.number_of_glyphs(), 3);
+        assert_eq!(face.units_per_em() % 8, 0);
+        let unit = face.units_per_em() as i16 / 8;
+        assert_eq!(face.ascender() / unit, 6);
+        assert_eq!(face.descender() / unit, -2);
+        // The top and bottom rows are one run each, the middle rows two
+        let a = Rect {
+            x_min: 0,
+            y_min: 0,
+            x_max: 4,
+            y_max: 4,
+        };
+        assert_eq!(glyph(&face, 'A'), (6, a));
+        let b = Rect {
+            x_min: 1,
+            y_min: -2,
+            x_max: 3,
+            y_max: 0,
+        };
+        assert_eq!(glyph(&face, 'B'), (2, b));
+        let a_id = face.glyph_index('A').unwrap();
+        assert_eq!(face.glyph_hor_advance(a_id), Some(5 * unit as u16));
+        assert_eq!(face.glyph_index('C'), None);
+        assert_eq!(face.glyph_hor_advance(GlyphId(0)), Some(4 * unit as u16));
+        let family = face
+            .names()
+            .into_iter()
+            .find(|n| n.name_id == name_id::FAMILY)
+            .and_then(|n| n.to_string());
+        assert_eq!(family.as_deref(), Some(name.unwrap_or("Bitmap font")));
+    }
+
+    #[test]
+    fn bdf_round_trip() {
+        let ttf = convert(BDF.as_bytes()).unwrap();
+        check_converted(&ttf, Some("Test Fixed"));
+    }
+
+    /// Write a PCF font with the glyphs of [`BDF`], in big endian order with byte padding
+    fn pcf() -> Vec<u8> {
+        const FORMAT: u32 = 0b1100;
+        fn table(format: u32, fields: &[&[u8]]) -> Vec<u8> {
+            let mut t = format.to_le_bytes().to_vec();
+            for field in fields {
+                t.extend(*field);
+            }
+            t
+        }
+        // Left and right bearing, advance, ascent, descent and attributes, little endian
+        let metrics: Vec<u8> = [[0i16, 4, 5, 4, 0, 0], [1, 3, 3, 0, 2, 0]]
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let bitmaps = [0xF0, 0x90, 0x90, 0xF0, 0xC0, 0xC0];
+        let sizes: Vec<u8> = [6u32; 4].iter().flat_map(|v| v.to_be_bytes()).collect();
+        // Code ranges 0x41..=0x42 and 0..=0, the default glyph, and the glyph of each code
+        let indices: Vec<u8> = [0x41u16, 0x42, 0, 0, 0, 0, 1]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let tables = [
+            (
+                PCF_ACCELERATORS,
+                table(FORMAT, &[&[0; 8], &6u32.to_be_bytes(), &2u32.to_be_bytes()]),
+            ),
+            (PCF_METRICS, table(0, &[&2u32.to_le_bytes(), &metrics])),
+            (
+                PCF_BITMAPS,
+                table(
+                    FORMAT,
+                    &[
+                        &2u32.to_be_bytes(),
+                        &0u32.to_be_bytes(),
+                        &4u32.to_be_bytes(),
+                        &sizes,
+                        &bitmaps,
+                    ],
+                ),
+            ),
+            (PCF_BDF_ENCODINGS, table(FORMAT, &[&indices])),
+        ];
+        let mut out = b"\x01fcp".to_vec();
+        out.extend((tables.len() as u32).to_le_bytes());
+        let mut offset = 8 + tables.len() * 16;
+        for (kind, data) in &tables {
+            for v in [*kind, 0, data.len() as u32, offset as u32] {
+                out.extend(v.to_le_bytes());
+            }
+            offset += data.len();
+        }
+        for (_, data) in tables {
+            out.extend(data);
+        }
+        out
+    }
+
+    #[test]
+    fn pcf_round_trip() {
+        let ttf = convert(&pcf()).unwrap();
+        check_converted(&ttf, None);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(convert(b"\x01fcp\x04\0\0\0").is_err());
+        let mut truncated = pcf();
+        truncated.truncate(truncated.len() - 4);
+        assert!(convert(&truncated).is_err());
+        assert!(convert(b"STARTFONT 2.1\nFONT_ASCENT x\n").is_err());
+        assert!(convert(b"STARTFONT 2.1\nFONT_ASCENT 6\nENDFONT\n").is_err());
+    }
+
+    #[test]
+    fn too_many_glyphs() {
+        let font = |count: u32| BitmapFont {
+            name: String::new(),
+            ascent: 1,
+            descent: 0,
+            glyphs: (0..count)
+                .map(|code| BitmapGlyph {
+                    code,
+                    advance: 1,
+                    left: 0,
+                    bottom: 0,
+                    rows: Vec::new(),
+                })
+                .collect(),
+        };
+        let ttf = write_ttf(&font(0xfffe)).unwrap();
+        assert_eq!(Face::parse(&ttf, 0).unwrap().number_of_glyphs(), 0xffff);
+        assert!(write_ttf(&font(0xffff)).is_err());
+    }
+}
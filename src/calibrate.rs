@@ -0,0 +1,140 @@
+//! Aligning the vertical metrics of two fonts against each other
+
+use {
+    crate::preview::PreviewFonts,
+    egui::{Color32, FontDefinitions, FontFamily, FontId, Galley, Pos2},
+    std::sync::Arc,
+};
+
+const REFERENCE_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+const ADJUSTED_COLOR: Color32 = Color32::from_rgba_premultiplied(200, 110, 0, 200);
+
+/// State of the baseline calibration view
+pub(crate) struct Calibration {
+    reference: String,
+    adjusted: String,
+    text: String,
+    size: f32,
+    preview: PreviewFonts,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            reference: String::new(),
+            adjusted: String::new(),
+            text: "Hxg 漢字 あ".into(),
+            size: 32.0,
+            preview: PreviewFonts::default(),
+        }
+    }
+}
+
+/// The baseline and x-height of a font, measured off the rendered glyph of `x`
+fn x_metrics(galley: &Galley) -> Option<(f32, f32)> {
+    let bounds = galley.rows.first()?.visuals.mesh_bounds;
+    bounds.is_positive().then_some((bounds.max.y, bounds.min.y))
+}
+
+fn font_combo(ui: &mut egui::Ui, label: &str, names: &[&String], selected: &mut String) {
+    egui::ComboBox::from_label(label)
+        .selected_text(selected.as_str())
+        .show_ui(ui, |ui| {
+            for name in names {
+                ui.selectable_value(selected, (*name).clone(), name.as_str());
+            }
+        });
+}
+
+impl Calibration {
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, font_defs: &mut FontDefinitions) {
+        let names: Vec<_> = font_defs.font_data.keys().collect();
+        font_combo(ui, "Reference font", &names, &mut self.reference);
+        font_combo(ui, "Font to adjust", &names, &mut self.adjusted);
+        ui.horizontal(|ui| {
+            let re = ui.text_edit_singleline(&mut self.text);
+            crate::ui::a11y_text_label(&re, "Calibration sample text", &self.text);
+            ui.add(egui::DragValue::new(&mut self.size).range(8.0..=128.0));
+        });
+        let (Some(reference), Some(adjusted)) = (
+            font_defs.font_data.get(&self.reference).cloned(),
+            font_defs.font_data.get_mut(&self.adjusted),
+        ) else {
+            ui.label("Pick the two fonts to compare");
+            return;
+        };
+        let mut tweak = adjusted.tweak;
+        egui::Grid::new("calibration_tweak").show(ui, |ui| {
+            ui.label("Scale");
+            ui.add(egui::Slider::new(&mut tweak.scale, 0.25..=2.0));
+            ui.end_row();
+            ui.label("Y offset factor");
+            ui.add(egui::Slider::new(&mut tweak.y_offset_factor, -1.0..=1.0));
+            ui.end_row();
+            ui.label("Y offset");
+            ui.add(egui::Slider::new(&mut tweak.y_offset, -20.0..=20.0));
+            ui.end_row();
+            ui.label("Baseline offset factor");
+            ui.add(egui::Slider::new(
+                &mut tweak.baseline_offset_factor,
+                -1.0..=1.0,
+            ));
+            ui.end_row();
+        });
+        if tweak != adjusted.tweak {
+            Arc::make_mut(adjusted).tweak = tweak;
+        }
+        let adjusted = adjusted.clone();
+        // A minimal set of definitions containing just the two fonts, to keep rebuilds cheap
+        let ref_family = FontFamily::Name("reference".into());
+        let adj_family = FontFamily::Name("adjusted".into());
+        let mut defs = FontDefinitions::empty();
+        defs.font_data.insert(self.reference.clone(), reference);
+        defs.font_data.insert(self.adjusted.clone(), adjusted);
+        defs.families
+            .insert(ref_family.clone(), vec![self.reference.clone()]);
+        defs.families
+            .insert(adj_family.clone(), vec![self.adjusted.clone()]);
+        if let Err(e) = self.preview.prepare(ui.ctx(), &defs) {
+            ui.label(egui::RichText::new(e).color(Color32::DARK_RED));
+            return;
+        }
+        let lay = |text: &str, family: &FontFamily, color| {
+            self.preview
+                .layout(text, FontId::new(self.size, family.clone()), color)
+        };
+        let (Some(ref_text), Some(adj_text), Some(ref_x), Some(adj_x)) = (
+            lay(&self.text, &ref_family, REFERENCE_COLOR),
+            lay(&self.text, &adj_family, ADJUSTED_COLOR),
+            lay("x", &ref_family, REFERENCE_COLOR),
+            lay("x", &adj_family, ADJUSTED_COLOR),
+        ) else {
+            return;
+        };
+        let height = ref_text.size().y.max(adj_text.size().y) + 8.0;
+        let (rect, _) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), height),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        let pos = Pos2::new(rect.left() + 4.0, rect.top() + 4.0);
+        self.preview.paint(&painter, pos, &ref_text);
+        self.preview.paint(&painter, pos, &adj_text);
+        let line = |y: f32, color: Color32| {
+            painter.hline(rect.x_range(), pos.y + y, egui::Stroke::new(1.0, color));
+        };
+        let metrics = x_metrics(&ref_x).zip(x_metrics(&adj_x));
+        if let Some(((ref_base, ref_xh), (adj_base, adj_xh))) = metrics {
+            line(ref_base, REFERENCE_COLOR);
+            line(ref_xh, REFERENCE_COLOR);
+            line(adj_base, ADJUSTED_COLOR);
+            line(adj_xh, ADJUSTED_COLOR);
+            ui.label(format!(
+                "Baseline Δ {:+.1} pt, x-height Δ {:+.1} pt",
+                adj_base - ref_base,
+                (adj_base - adj_xh) - (ref_base - ref_xh)
+            ));
+        }
+    }
+}
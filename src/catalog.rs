@@ -0,0 +1,148 @@
+//! Fonts compiled into the application, offered for adding without filesystem access
+
+use {
+    crate::{CustomFontPaths, FontLoadError},
+    egui::{FontData, FontDefinitions},
+    std::{collections::BTreeMap, sync::Arc},
+};
+
+/// Start of the paths recorded for bundled fonts, see [`BundledFont::custom_path`]
+const BUNDLED_PREFIX: &str = "bundled:";
+
+/// A font compiled into the application, see [`FontCfgUi::bundled_font`](crate::FontCfgUi::bundled_font)
+#[derive(Debug, Clone)]
+pub struct BundledFont {
+    /// Identifier the font is added under
+    pub name: String,
+    /// Contents of the font file
+    pub data: &'static [u8],
+    /// Description shown to the user, e.g. what the font is good for
+    pub description: String,
+}
+
+impl BundledFont {
+    /// Create a catalog entry
+    pub fn new(
+        name: impl Into<String>,
+        data: &'static [u8],
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            data,
+            description: description.into(),
+        }
+    }
+    /// The path recorded in [`CustomFontPaths`] when the font is added, `bundled:` followed by
+    /// its name
+    ///
+    /// The font can be restored from such paths with [`load_bundled_fonts`].
+    pub fn custom_path(&self) -> String {
+        format!("{BUNDLED_PREFIX}{}", self.name)
+    }
+}
+
+/// The name of the bundled font a path recorded in [`CustomFontPaths`] refers to, if it
+/// refers to one (see [`BundledFont::custom_path`])
+pub(crate) fn bundled_name(path: &str) -> Option<&str> {
+    path.strip_prefix(BUNDLED_PREFIX)
+}
+
+/// Whether a path recorded in [`CustomFontPaths`] refers to a bundled font
+pub(crate) fn is_bundled(path: &str) -> bool {
+    bundled_name(path).is_some()
+}
+
+/// Load the fonts of `catalog` that `custom` records (see [`BundledFont::custom_path`])
+///
+/// These are skipped by [`load_custom_fonts`](crate::load_custom_fonts) and the other
+/// functions loading fonts from files, so call this along with them.
+/// Fails if a recorded font isn't in the catalog, e.g. because a newer version of the
+/// application doesn't ship it anymore.
+pub fn load_bundled_fonts(
+    catalog: &[BundledFont],
+    custom: &CustomFontPaths,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+) -> Result<(), FontLoadError> {
+    for (name, path) in custom {
+        let Some(bundled) = bundled_name(path) else {
+            continue;
+        };
+        let font = catalog
+            .iter()
+            .find(|font| font.name == bundled)
+            .ok_or_else(|| FontLoadError::new(path, "Not among the bundled fonts"))?;
+        font_data.insert(name.clone(), Arc::new(FontData::from_static(font.data)));
+    }
+    Ok(())
+}
+
+/// List the bundled fonts with buttons for adding them. Returns the font to add.
+///
+/// `name_for` gives the identifier a font would be added under, to tell which are added already.
+pub(crate) fn catalog_ui<'a>(
+    ui: &mut egui::Ui,
+    catalog: &'a [BundledFont],
+    font_defs: &FontDefinitions,
+    name_for: impl Fn(&str) -> String,
+) -> Option<&'a BundledFont> {
+    let mut added = None;
+    for font in catalog {
+        ui.horizontal_wrapped(|ui| {
+            let exists = font_defs.font_data.contains_key(&name_for(&font.name));
+            let re = ui
+                .add_enabled(!exists, egui::Button::new("Add"))
+                .on_disabled_hover_text("Already added");
+            if re.clicked() {
+                added = Some(font);
+            }
+            ui.strong(&font.name);
+            ui.weak(&font.description);
+        });
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{load_bundled_fonts, BundledFont},
+        crate::{load_fonts_by_priority, CustomFontPaths, FontOrigin},
+        egui::FontDefinitions,
+        std::collections::BTreeMap,
+    };
+
+    fn catalog() -> Vec<BundledFont> {
+        let hack = FontDefinitions::default().font_data["Hack"].font.to_vec();
+        vec![BundledFont::new("Code", hack.leak(), "For code")]
+    }
+
+    #[test]
+    fn restores_recorded_fonts() {
+        let catalog = catalog();
+        let mut custom = CustomFontPaths::default();
+        custom.insert("Code".into(), catalog[0].custom_path());
+        let mut fonts = BTreeMap::new();
+        #[cfg(feature = "fs")]
+        crate::load_custom_fonts(&custom, &mut fonts).unwrap();
+        assert!(fonts.is_empty());
+        load_bundled_fonts(&catalog, &custom, &mut fonts).unwrap();
+        assert_eq!(*fonts["Code"].font, *catalog[0].data);
+        custom.insert("Gone".into(), "bundled:Gone".into());
+        let e = load_bundled_fonts(&catalog, &custom, &mut fonts).unwrap_err();
+        assert_eq!(e.path, "bundled:Gone");
+    }
+
+    #[test]
+    fn resolves_recorded_fonts() {
+        let catalog = catalog();
+        let mut custom = CustomFontPaths::default();
+        custom.insert("Renamed".into(), catalog[0].custom_path());
+        let mut defs = FontDefinitions::default();
+        let priority = [FontOrigin::CustomPath];
+        let resolutions = load_fonts_by_priority(&priority, None, &custom, &catalog, &mut defs);
+        let winner = Some((FontOrigin::CustomPath, "bundled:Code".to_owned()));
+        assert_eq!(resolutions[0].winner, winner);
+        assert_eq!(*defs.font_data["Renamed"].font, *catalog[0].data);
+    }
+}
@@ -0,0 +1,74 @@
+//! Compressing font data that is kept around but not in use
+
+/// Compress font data, for storing it until it's needed
+///
+/// Fonts typically shrink by a quarter to a third. The result is an LZ4 block prefixed with the
+/// uncompressed size as a little endian `u32`. Restore it with [`decompress_font_data`].
+pub fn compress_font_data(data: &[u8]) -> Vec<u8> {
+    lz4_flex::compress_prepend_size(data)
+}
+
+/// Restore font data compressed with [`compress_font_data`]
+pub fn decompress_font_data(data: &[u8]) -> Result<Vec<u8>, String> {
+    let corrupt = || "The compressed font data is corrupt".to_owned();
+    let (size, block) = data.split_first_chunk::<4>().ok_or_else(corrupt)?;
+    let size = u32::from_le_bytes(*size) as usize;
+    // The size may be corrupt too, so don't allocate more than the data can expand to
+    if size > block.len().saturating_mul(255) {
+        return Err(corrupt());
+    }
+    let out = lz4_flex::decompress(block, size).map_err(|_| corrupt())?;
+    if out.len() != size {
+        return Err(corrupt());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_font_data, decompress_font_data};
+
+    fn round_trip(data: &[u8]) {
+        let compressed = compress_font_data(data);
+        assert_eq!(decompress_font_data(&compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn empty() {
+        round_trip(&[]);
+    }
+
+    #[test]
+    fn lengths_around_match_limit() {
+        for len in 0..=40 {
+            let data: Vec<u8> = (0..len).map(|i| (i % 3) as u8).collect();
+            round_trip(&data);
+        }
+    }
+
+    #[test]
+    fn repetitive() {
+        let data = b"egui-fontcfg ".repeat(10_000);
+        let compressed = compress_font_data(&data);
+        assert!(compressed.len() < data.len() / 10);
+        round_trip(&data);
+    }
+
+    #[test]
+    fn real_font() {
+        let defs = egui::FontDefinitions::default();
+        round_trip(&defs.font_data["Hack"].font);
+    }
+
+    #[test]
+    fn corrupt_input() {
+        assert!(decompress_font_data(&[]).is_err());
+        let mut compressed = compress_font_data(&b"abcd".repeat(100));
+        compressed.truncate(compressed.len() - 3);
+        assert!(decompress_font_data(&compressed).is_err());
+        // A size that doesn't match the data
+        let mut compressed = compress_font_data(b"abcd");
+        compressed[0] += 1;
+        assert!(decompress_font_data(&compressed).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! Storing only what the user changed relative to the application's default fonts
+
+use {
+    crate::{
+        source,
+        textfmt::{self, ParseError},
+        CustomFontPaths, FamilyId, FamilySpec, FontEntry, FontLoadError, FontSource,
+    },
+    egui::{FontData, FontDefinitions},
+    std::{collections::BTreeSet, sync::Arc},
+};
+
+const FORMAT: textfmt::Format = textfmt::Format {
+    header: "egui-fontcfg diff 1",
+    what: "diff",
+};
+
+/// The changes the user made to a baseline configuration (e.g. the defaults the app ships)
+///
+/// Storing this instead of the whole configuration lets changes to the baseline in newer
+/// versions of the app take effect for things the user didn't touch.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontConfigDiff {
+    /// Fonts loaded from files, which replace baseline fonts of the same name
+    ///
+    /// Entries without a path can't be loaded, and are skipped by [`Self::apply`].
+    pub added_fonts: Vec<FontEntry>,
+    /// Baseline fonts that were removed
+    pub removed_fonts: BTreeSet<String>,
+    /// Baseline fonts whose face index or tweak changed
+    pub changed_fonts: Vec<FontEntry>,
+    /// Families that were added or changed, with their complete list of fonts
+    pub families: Vec<FamilySpec>,
+    /// Baseline families that were removed
+    pub removed_families: BTreeSet<FamilyId>,
+}
+
+impl FontConfigDiff {
+    /// Compute the changes that turn `baseline` into `current`
+    ///
+    /// Fonts that aren't in the baseline can only be stored if they have a path in `custom`.
+    /// Fonts without a path are left out.
+    pub fn new(
+        baseline: &FontDefinitions,
+        current: &FontDefinitions,
+        custom: &CustomFontPaths,
+    ) -> Self {
+        let mut diff = Self::default();
+        for (name, font) in &current.font_data {
+            let base = baseline.font_data.get(name);
+            if let Some(base) = base {
+                if Arc::ptr_eq(base, font) {
+                    continue;
+                }
+            }
+            let replaced = base.is_none_or(|base| base.font != font.font);
+            match custom.get(name) {
+                Some(path) if replaced => {
+                    diff.added_fonts
+                        .push(FontEntry::new(name, font, Some(path.clone())));
+                }
+                _ => {
+                    if base.is_some_and(|base| base.index != font.index || base.tweak != font.tweak)
+                    {
+                        diff.changed_fonts.push(FontEntry::new(name, font, None));
+                    }
+                }
+            }
+        }
+        for name in baseline.font_data.keys() {
+            if !current.font_data.contains_key(name) {
+                diff.removed_fonts.insert(name.clone());
+            }
+        }
+        for (family, fonts) in &current.families {
+            if baseline.families.get(family) != Some(fonts) {
+                diff.families.push(FamilySpec {
+                    id: family.into(),
+                    fonts: fonts.clone(),
+                });
+            }
+        }
+        for family in baseline.families.keys() {
+            if !current.families.contains_key(family) {
+                diff.removed_families.insert(family.into());
+            }
+        }
+        diff
+    }
+    /// Whether there are no changes
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    /// Apply the changes to `baseline`, loading the added fonts from `source`, or from their
+    /// files if it's `None`
+    ///
+    /// The paths of added fonts are inserted into `custom`. Added bundled fonts have to be in
+    /// `baseline` already, see [`load_bundled_fonts`](crate::load_bundled_fonts). Family entries that refer to fonts
+    /// which don't exist (e.g. because the baseline dropped them) are removed.
+    pub fn apply(
+        &self,
+        source: Option<&dyn FontSource>,
+        baseline: &FontDefinitions,
+        custom: &mut CustomFontPaths,
+    ) -> Result<FontDefinitions, FontLoadError> {
+        let mut defs = baseline.clone();
+        for name in &self.removed_fonts {
+            defs.font_data.remove(name);
+        }
+        for entry in &self.changed_fonts {
+            if let Some(font) = defs.font_data.get_mut(&entry.name) {
+                entry.apply_to(font);
+            }
+        }
+        for entry in &self.added_fonts {
+            let Some(path) = &entry.path else {
+                continue;
+            };
+            if crate::catalog::is_bundled(path) {
+                custom.insert(entry.name.clone(), path.clone());
+                continue;
+            }
+            let font = FontData {
+                index: entry.index,
+                tweak: entry.tweaks.into(),
+                ..source::load_custom_font(source, path)?
+            };
+            defs.font_data.insert(entry.name.clone(), Arc::new(font));
+            custom.insert(entry.name.clone(), path.clone());
+        }
+        for family in &self.removed_families {
+            defs.families.remove(&family.into());
+        }
+        for spec in &self.families {
+            defs.families.insert((&spec.id).into(), spec.fonts.clone());
+        }
+        for fonts in defs.families.values_mut() {
+            fonts.retain(|name| defs.font_data.contains_key(name));
+        }
+        Ok(defs)
+    }
+    /// Write the changes in a line based text format, readable by [`Self::parse`]
+    pub fn to_text(&self) -> String {
+        let mut out = FORMAT.start();
+        for added in &self.added_fonts {
+            let Some(path) = &added.path else {
+                continue;
+            };
+            let index = added.index.to_string();
+            let tweak = textfmt::tweak_to_fields(&added.tweaks);
+            let fields = [added.name.as_str(), path.as_str(), index.as_str()];
+            textfmt::push_line(
+                &mut out,
+                "add-font",
+                fields.into_iter().chain(tweak.iter().map(String::as_str)),
+            );
+        }
+        for name in &self.removed_fonts {
+            textfmt::push_line(&mut out, "remove-font", [name.as_str()]);
+        }
+        for changed in &self.changed_fonts {
+            let index = changed.index.to_string();
+            let tweak = textfmt::tweak_to_fields(&changed.tweaks);
+            textfmt::push_line(
+                &mut out,
+                "font-properties",
+                [changed.name.as_str(), index.as_str()]
+                    .into_iter()
+                    .chain(tweak.iter().map(String::as_str)),
+            );
+        }
+        for spec in &self.families {
+            let family = textfmt::family_to_field(&spec.id);
+            textfmt::push_line(
+                &mut out,
+                "family",
+                std::iter::once(family.as_str()).chain(spec.fonts.iter().map(String::as_str)),
+            );
+        }
+        for family in &self.removed_families {
+            let family = textfmt::family_to_field(family);
+            textfmt::push_line(&mut out, "remove-family", [family.as_str()]);
+        }
+        out
+    }
+    /// Read changes written by [`Self::to_text`]
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut diff = Self::default();
+        for line in FORMAT.body(text)? {
+            let (line_no, keyword, fields) = line?;
+            let err = |message: String| ParseError {
+                line: line_no,
+                message,
+            };
+            match (keyword.as_str(), fields.as_slice()) {
+                ("add-font", [name, path, index, tweak @ ..]) => {
+                    diff.added_fonts.push(FontEntry {
+                        name: name.clone(),
+                        path: Some(path.clone()),
+                        index: textfmt::index_from_field(index).map_err(err)?,
+                        tweaks: textfmt::tweak_from_fields(tweak).map_err(err)?,
+                    });
+                }
+                ("remove-font", [name]) => {
+                    diff.removed_fonts.insert(name.clone());
+                }
+                ("font-properties", [name, index, tweak @ ..]) => {
+                    diff.changed_fonts.push(FontEntry {
+                        name: name.clone(),
+                        path: None,
+                        index: textfmt::index_from_field(index).map_err(err)?,
+                        tweaks: textfmt::tweak_from_fields(tweak).map_err(err)?,
+                    });
+                }
+                ("family", [family, fonts @ ..]) => {
+                    let family = textfmt::family_from_field(family).map_err(err)?;
+                    diff.families.push(FamilySpec {
+                        id: family,
+                        fonts: fonts.to_vec(),
+                    });
+                }
+                ("remove-family", [family]) => {
+                    let family = textfmt::family_from_field(family).map_err(err)?;
+                    diff.removed_families.insert(family);
+                }
+                _ => return Err(err(format!("Unexpected line starting with {keyword:?}"))),
+            }
+        }
+        Ok(diff)
+    }
+}
+
+/// Form for exporting and importing the changes relative to the baseline
+#[derive(Default)]
+pub(crate) struct DiffForm {
+    import_text: String,
+}
+
+impl DiffForm {
+    /// Show the form. Returns the error message if importing failed.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        source: Option<&dyn FontSource>,
+        baseline: &FontDefinitions,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> Option<String> {
+        let no_paths = CustomFontPaths::default();
+        let diff = FontConfigDiff::new(baseline, font_defs, custom.as_deref().unwrap_or(&no_paths));
+        ui.label(format!(
+            "{} fonts added, {} removed, {} changed, {} families changed, {} removed",
+            diff.added_fonts.len(),
+            diff.removed_fonts.len(),
+            diff.changed_fonts.len(),
+            diff.families.len(),
+            diff.removed_families.len(),
+        ));
+        if ui
+            .add_enabled(!diff.is_empty(), egui::Button::new("📋 Copy changes"))
+            .on_hover_text("Copy the changes from the defaults as text")
+            .clicked()
+        {
+            ui.ctx().copy_text(diff.to_text());
+        }
+        let re = ui.add(
+            egui::TextEdit::multiline(&mut self.import_text)
+                .hint_text("Paste changes to import")
+                .desired_rows(2),
+        );
+        crate::ui::a11y_text_label(&re, "Changes to import", &self.import_text);
+        let re = ui
+            .add_enabled(
+                !self.import_text.trim().is_empty(),
+                egui::Button::new("Import changes"),
+            )
+            .on_hover_text("Replace the configuration with the defaults plus these changes");
+        if !re.clicked() {
+            return None;
+        }
+        let diff = match FontConfigDiff::parse(&self.import_text) {
+            Ok(diff) => diff,
+            Err(e) => return Some(e.to_string()),
+        };
+        let mut paths = custom.as_deref().cloned().unwrap_or_default();
+        match diff.apply(source, baseline, &mut paths) {
+            Ok(defs) => {
+                *font_defs = defs;
+                if let Some(custom) = custom {
+                    *custom = paths;
+                }
+                self.import_text.clear();
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::FontConfigDiff,
+        crate::{textfmt::ParseError, CustomFontPaths},
+        egui::{FontDefinitions, FontFamily},
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn round_trip() {
+        let baseline = FontDefinitions::default();
+        let mut current = baseline.clone();
+        let mut custom = CustomFontPaths::default();
+        let hack = current.font_data["Hack"].clone();
+        current.font_data.insert("New\tfont".into(), hack);
+        custom.insert("New\tfont".into(), "/fonts/new font.ttf".into());
+        current.font_data.remove("emoji-icon-font");
+        Arc::make_mut(current.font_data.get_mut("Ubuntu-Light").unwrap())
+            .tweak
+            .y_offset_factor = 0.125;
+        current
+            .families
+            .insert(FontFamily::Name("Code".into()), vec!["New\tfont".into()]);
+        current.families.remove(&FontFamily::Monospace);
+        let diff = FontConfigDiff::new(&baseline, &current, &custom);
+        assert!(!diff.is_empty());
+        assert_eq!(FontConfigDiff::parse(&diff.to_text()), Ok(diff));
+    }
+
+    #[test]
+    fn error_lines() {
+        let error = |text: &str| FontConfigDiff::parse(text).unwrap_err();
+        assert_eq!(
+            error("# exported\negui-fontcfg snapshot 1\n"),
+            ParseError {
+                line: 2,
+                message: "Not an egui-fontcfg diff".into(),
+            }
+        );
+        let text = "egui-fontcfg diff 1\nremove-font\tHack\nremove-family\tname:\n";
+        assert_eq!(error(text).line, 3);
+        let text = "egui-fontcfg diff 1\nremove-font\tHack\textra\n";
+        assert_eq!(
+            error(text),
+            ParseError {
+                line: 2,
+                message: "Unexpected line starting with \"remove-font\"".into(),
+            }
+        );
+    }
+}
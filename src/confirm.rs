@@ -0,0 +1,109 @@
+//! Asking the user to confirm destructive actions
+
+use {
+    crate::ui::{a11y_label, status_label, Severity},
+    egui::FontFamily,
+};
+
+/// Which destructive actions [`FontCfgUi`](crate::FontCfgUi) asks the user to confirm
+///
+/// The default is [`Self::NONE`], performing all actions right away.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmationPolicy {
+    /// Removing a font
+    pub remove_font: bool,
+    /// Removing a family
+    pub remove_family: bool,
+    /// Adding a font with the identifier of an existing one, replacing it
+    pub overwrite_duplicate: bool,
+    /// Reverting to the fonts the dialog was opened with, or resetting to egui's defaults
+    pub reset: bool,
+}
+
+impl ConfirmationPolicy {
+    /// Don't ask for confirmation
+    pub const NONE: Self = Self {
+        remove_font: false,
+        remove_family: false,
+        overwrite_duplicate: false,
+        reset: false,
+    };
+    /// Ask for confirmation before every destructive action
+    pub const ALL: Self = Self {
+        remove_font: true,
+        remove_family: true,
+        overwrite_duplicate: true,
+        reset: true,
+    };
+}
+
+/// An action waiting for the user's confirmation
+pub(crate) enum PendingAction {
+    RemoveFont(String),
+    RemoveFamily(FontFamily),
+    /// Add the font from the add new font form, replacing the existing font of this name
+    OverwriteFont(String),
+    Restore(Restore),
+    /// Close the [`FontCfgWindow`](crate::FontCfgWindow) with unsaved changes
+    CloseWindow,
+}
+
+/// Discarding all edits, see [`ConfirmationPolicy::reset`]
+#[derive(Clone, Copy)]
+pub(crate) enum Restore {
+    /// Go back to the fonts the dialog was opened with
+    Revert,
+    /// Go back to [`egui::FontDefinitions::default`]
+    Reset,
+}
+
+impl PendingAction {
+    fn question(&self) -> (String, &'static str) {
+        match self {
+            Self::RemoveFont(name) => (format!("Remove font {name}?"), "Remove"),
+            Self::RemoveFamily(family) => (format!("Remove {family} family?"), "Remove"),
+            Self::OverwriteFont(name) => (
+                format!("A font called {name} already exists. Replace it?"),
+                "Replace",
+            ),
+            Self::Restore(Restore::Revert) => (
+                "Discard all changes since the dialog was opened?".into(),
+                "Revert",
+            ),
+            Self::Restore(Restore::Reset) => (
+                "Discard all fonts and restore egui's defaults?".into(),
+                "Reset",
+            ),
+            Self::CloseWindow => (
+                "There are changes that weren't applied or saved. Close anyway?".into(),
+                "Close",
+            ),
+        }
+    }
+}
+
+/// Ask the user to confirm `action`. Returns whether they confirmed or cancelled it, if they did.
+pub(crate) fn confirm_ui(
+    ui: &mut egui::Ui,
+    high_contrast: bool,
+    action: &PendingAction,
+) -> Option<bool> {
+    let (question, verb) = action.question();
+    let mut answer = None;
+    ui.horizontal_wrapped(|ui| {
+        status_label(ui, high_contrast, Severity::Warning, &question);
+        let re = ui.button(verb);
+        a11y_label(
+            &re,
+            egui::WidgetType::Button,
+            &format!("Confirm: {question}"),
+        );
+        if re.clicked() {
+            answer = Some(true);
+        }
+        if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            answer = Some(false);
+        }
+    });
+    answer
+}
@@ -0,0 +1,290 @@
+//! How much of common Unicode blocks the fonts of a family cover
+
+use {
+    egui::FontData,
+    std::{
+        collections::{BTreeMap, HashMap},
+        ops::RangeInclusive,
+        sync::{Arc, Weak},
+    },
+};
+
+/// The blocks shown in the coverage bar, with the assigned code points that are checked
+const BLOCKS: &[(&str, &[RangeInclusive<u32>])] = &[
+    ("Basic Latin", &[0x20..=0x7E]),
+    ("Latin Extended", &[0xC0..=0x24F]),
+    ("Greek", &[0x391..=0x3A1, 0x3A3..=0x3A9, 0x3B1..=0x3C9]),
+    ("Cyrillic", &[0x400..=0x4FF]),
+    ("CJK", &[0x4E00..=0x9FFF]),
+    ("Emoji", &[0x1F600..=0x1F64F]),
+];
+
+/// Which of the checked code points a font has glyphs for, in [`BLOCKS`] order
+fn font_coverage(font: &FontData) -> Vec<Vec<bool>> {
+    let face = ttf_parser::Face::parse(&font.font, font.index).ok();
+    BLOCKS
+        .iter()
+        .map(|(_, ranges)| {
+            ranges
+                .iter()
+                .flat_map(|r| r.clone().filter_map(char::from_u32))
+                .map(|c| face.as_ref().is_some_and(|f| f.glyph_index(c).is_some()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Per font coverage, which is only recomputed when the font data changes
+#[derive(Default)]
+pub(crate) struct CoverageCache {
+    fonts: HashMap<String, (Weak<FontData>, Vec<Vec<bool>>)>,
+}
+
+impl CoverageCache {
+    /// Forget fonts that no longer exist
+    pub(crate) fn prune(&mut self, font_data: &BTreeMap<String, Arc<FontData>>) {
+        self.fonts.retain(|name, _| font_data.contains_key(name));
+    }
+    fn font(&mut self, name: &str, font: &Arc<FontData>) -> &[Vec<bool>] {
+        let entry = self
+            .fonts
+            .entry(name.to_owned())
+            .or_insert_with(|| (Weak::new(), Vec::new()));
+        if !entry.0.upgrade().is_some_and(|e| Arc::ptr_eq(&e, font)) {
+            *entry = (Arc::downgrade(font), font_coverage(font));
+        }
+        &entry.1
+    }
+    /// Fraction of each block covered by the union of `fonts`
+    fn family(
+        &mut self,
+        font_data: &BTreeMap<String, Arc<FontData>>,
+        fonts: &[String],
+    ) -> Vec<f32> {
+        let mut union = no_coverage();
+        for name in fonts {
+            let Some(font) = font_data.get(name) else {
+                continue;
+            };
+            for (acc, block) in union.iter_mut().zip(self.font(name, font)) {
+                for (a, &b) in acc.iter_mut().zip(block) {
+                    *a |= b;
+                }
+            }
+        }
+        union
+            .iter()
+            .map(|block| block.iter().filter(|&&b| b).count() as f32 / block.len() as f32)
+            .collect()
+    }
+}
+
+/// Coverage of a font without any of the checked glyphs
+fn no_coverage() -> Vec<Vec<bool>> {
+    BLOCKS
+        .iter()
+        .map(|(_, ranges)| vec![false; ranges.iter().map(|r| r.clone().count()).sum()])
+        .collect()
+}
+
+/// Show a compact bar with one segment per block, filled by how much the family covers
+pub(crate) fn family_coverage_ui(
+    ui: &mut egui::Ui,
+    cache: &mut CoverageCache,
+    font_data: &BTreeMap<String, Arc<FontData>>,
+    fonts: &[String],
+) {
+    let coverage = cache.family(font_data, fonts);
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        let visuals = ui.visuals().clone();
+        for ((block, _), fraction) in BLOCKS.iter().zip(coverage) {
+            let (rect, re) = ui.allocate_exact_size(egui::vec2(24.0, 8.0), egui::Sense::hover());
+            let painter = ui.painter();
+            painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+            let mut filled = rect;
+            filled.set_width(rect.width() * fraction);
+            painter.rect_filled(filled, 2.0, visuals.selection.bg_fill);
+            painter.rect_stroke(rect, 2.0, visuals.widgets.noninteractive.bg_stroke);
+            re.on_hover_text(format!("{block}: {:.0}%", fraction * 100.0));
+        }
+    });
+}
+
+/// Unicode blocks listed in the coverage section of a font's details
+///
+/// Control characters are left out, as fonts don't need glyphs for them.
+const UNICODE_BLOCKS: &[(&str, RangeInclusive<u32>)] = &[
+    ("Basic Latin", 0x0020..=0x007E),
+    ("Latin-1 Supplement", 0x00A0..=0x00FF),
+    ("Latin Extended-A", 0x0100..=0x017F),
+    ("Latin Extended-B", 0x0180..=0x024F),
+    ("IPA Extensions", 0x0250..=0x02AF),
+    ("Spacing Modifier Letters", 0x02B0..=0x02FF),
+    ("Combining Diacritical Marks", 0x0300..=0x036F),
+    ("Greek and Coptic", 0x0370..=0x03FF),
+    ("Cyrillic", 0x0400..=0x04FF),
+    ("Cyrillic Supplement", 0x0500..=0x052F),
+    ("Armenian", 0x0530..=0x058F),
+    ("Hebrew", 0x0590..=0x05FF),
+    ("Arabic", 0x0600..=0x06FF),
+    ("Syriac", 0x0700..=0x074F),
+    ("Thaana", 0x0780..=0x07BF),
+    ("Devanagari", 0x0900..=0x097F),
+    ("Bengali", 0x0980..=0x09FF),
+    ("Gurmukhi", 0x0A00..=0x0A7F),
+    ("Gujarati", 0x0A80..=0x0AFF),
+    ("Tamil", 0x0B80..=0x0BFF),
+    ("Telugu", 0x0C00..=0x0C7F),
+    ("Kannada", 0x0C80..=0x0CFF),
+    ("Malayalam", 0x0D00..=0x0D7F),
+    ("Thai", 0x0E00..=0x0E7F),
+    ("Lao", 0x0E80..=0x0EFF),
+    ("Tibetan", 0x0F00..=0x0FFF),
+    ("Georgian", 0x10A0..=0x10FF),
+    ("Hangul Jamo", 0x1100..=0x11FF),
+    ("Ethiopic", 0x1200..=0x137F),
+    ("Cherokee", 0x13A0..=0x13FF),
+    ("Khmer", 0x1780..=0x17FF),
+    ("Mongolian", 0x1800..=0x18AF),
+    ("Latin Extended Additional", 0x1E00..=0x1EFF),
+    ("Greek Extended", 0x1F00..=0x1FFF),
+    ("General Punctuation", 0x2000..=0x206F),
+    ("Superscripts and Subscripts", 0x2070..=0x209F),
+    ("Currency Symbols", 0x20A0..=0x20CF),
+    ("Letterlike Symbols", 0x2100..=0x214F),
+    ("Number Forms", 0x2150..=0x218F),
+    ("Arrows", 0x2190..=0x21FF),
+    ("Mathematical Operators", 0x2200..=0x22FF),
+    ("Miscellaneous Technical", 0x2300..=0x23FF),
+    ("Box Drawing", 0x2500..=0x257F),
+    ("Block Elements", 0x2580..=0x259F),
+    ("Geometric Shapes", 0x25A0..=0x25FF),
+    ("Miscellaneous Symbols", 0x2600..=0x26FF),
+    ("Dingbats", 0x2700..=0x27BF),
+    ("Braille Patterns", 0x2800..=0x28FF),
+    ("CJK Symbols and Punctuation", 0x3000..=0x303F),
+    ("Hiragana", 0x3040..=0x309F),
+    ("Katakana", 0x30A0..=0x30FF),
+    ("Bopomofo", 0x3100..=0x312F),
+    ("CJK Unified Ideographs Extension A", 0x3400..=0x4DBF),
+    ("CJK Unified Ideographs", 0x4E00..=0x9FFF),
+    ("Hangul Syllables", 0xAC00..=0xD7AF),
+    ("Private Use Area", 0xE000..=0xF8FF),
+    ("CJK Compatibility Ideographs", 0xF900..=0xFAFF),
+    ("Alphabetic Presentation Forms", 0xFB00..=0xFB4F),
+    ("Arabic Presentation Forms-A", 0xFB50..=0xFDFF),
+    ("Halfwidth and Fullwidth Forms", 0xFF00..=0xFFEF),
+    ("Mathematical Alphanumeric Symbols", 0x1D400..=0x1D7FF),
+    ("Miscellaneous Symbols and Pictographs", 0x1F300..=0x1F5FF),
+    ("Emoticons", 0x1F600..=0x1F64F),
+    ("Transport and Map Symbols", 0x1F680..=0x1F6FF),
+    ("Supplemental Symbols and Pictographs", 0x1F900..=0x1F9FF),
+];
+
+/// How many code points of a Unicode block a font maps to glyphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BlockCoverage {
+    pub(crate) block: &'static str,
+    pub(crate) covered: usize,
+    pub(crate) size: usize,
+}
+
+impl BlockCoverage {
+    pub(crate) fn fraction(&self) -> f32 {
+        self.covered as f32 / self.size as f32
+    }
+}
+
+/// The code points the Unicode subtables of the cmap map to real glyphs, sorted
+fn mapped_codepoints(font: &FontData) -> Vec<u32> {
+    let Ok(face) = ttf_parser::Face::parse(&font.font, font.index) else {
+        return Vec::new();
+    };
+    let mut codepoints = Vec::new();
+    for subtable in face
+        .tables()
+        .cmap
+        .into_iter()
+        .flat_map(|cmap| cmap.subtables)
+        .filter(|subtable| subtable.is_unicode())
+    {
+        subtable.codepoints(|cp| {
+            if subtable.glyph_index(cp).is_some_and(|id| id.0 != 0) {
+                codepoints.push(cp);
+            }
+        });
+    }
+    codepoints.sort_unstable();
+    codepoints.dedup();
+    codepoints
+}
+
+/// The blocks `codepoints` (sorted) cover at least partially, and how many code points are
+/// outside of the listed blocks
+fn block_coverage(codepoints: &[u32]) -> (Vec<BlockCoverage>, usize) {
+    let count_in = |range: &RangeInclusive<u32>| {
+        codepoints.partition_point(|cp| cp <= range.end())
+            - codepoints.partition_point(|cp| cp < range.start())
+    };
+    let blocks: Vec<_> = UNICODE_BLOCKS
+        .iter()
+        .map(|(block, range)| BlockCoverage {
+            block,
+            covered: count_in(range),
+            size: range.clone().count(),
+        })
+        .filter(|coverage| coverage.covered > 0)
+        .collect();
+    let other = codepoints.len() - blocks.iter().map(|b| b.covered).sum::<usize>();
+    (blocks, other)
+}
+
+/// Per font block coverage, which is only recomputed when the font data changes
+#[derive(Default)]
+pub(crate) struct BlockCoverageCache {
+    fonts: HashMap<String, (Weak<FontData>, Vec<BlockCoverage>, usize, usize)>,
+}
+
+impl BlockCoverageCache {
+    pub(crate) fn remove(&mut self, name: &str) {
+        self.fonts.remove(name);
+    }
+    /// Show the blocks `font` covers, with the fraction of each
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, name: &str, font: &Arc<FontData>) {
+        let entry = self
+            .fonts
+            .entry(name.to_owned())
+            .or_insert_with(|| (Weak::new(), Vec::new(), 0, 0));
+        if !entry.0.upgrade().is_some_and(|e| Arc::ptr_eq(&e, font)) {
+            let codepoints = mapped_codepoints(font);
+            let (blocks, other) = block_coverage(&codepoints);
+            *entry = (Arc::downgrade(font), blocks, other, codepoints.len());
+        }
+        let (_, blocks, other, total) = entry;
+        if *total == 0 {
+            ui.weak("The font doesn't map any Unicode characters");
+            return;
+        }
+        ui.weak(format!("{total} characters in total"));
+        egui::Grid::new(("font_block_coverage", name))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for block in blocks.iter() {
+                    ui.label(block.block);
+                    ui.add(
+                        egui::ProgressBar::new(block.fraction())
+                            .desired_width(120.0)
+                            .text(format!("{:.0}%", block.fraction() * 100.0)),
+                    )
+                    .on_hover_text(format!("{} of {} code points", block.covered, block.size));
+                    ui.end_row();
+                }
+                if *other > 0 {
+                    ui.label("Other");
+                    ui.weak(format!("{other} characters"));
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,355 @@
+//! The expandable details view of a single font
+
+use {
+    crate::{
+        coverage::BlockCoverageCache,
+        exclusions::{format_codepoint_ranges, parse_codepoint_ranges, FallbackExclusions},
+        info::FontInfo,
+        preview::PreviewFonts,
+        rename::take_renamed,
+    },
+    egui::{FontData, FontDefinitions, FontFamily, FontId, FontTweak},
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::Arc,
+    },
+};
+
+/// Which fonts have their details expanded
+#[derive(Default)]
+pub(crate) struct DetailsState {
+    expanded: HashSet<String>,
+    /// Previews for the language samples of expanded fonts
+    samples: HashMap<String, PreviewFonts>,
+    /// Excluded ranges being typed, for fonts where they don't parse yet
+    exclusion_texts: HashMap<String, String>,
+    /// Expanded fonts with their tweaks from before they were edited, for comparison
+    tweaks_before: HashMap<String, Arc<FontData>>,
+    tweak_previews: HashMap<String, PreviewFonts>,
+    /// Tweaks and face indices being dragged, see [`on_release`]
+    dragged_tweaks: HashMap<String, FontTweak>,
+    dragged_indices: HashMap<String, u32>,
+    block_coverage: BlockCoverageCache,
+}
+
+impl DetailsState {
+    pub(crate) fn is_expanded(&self, name: &str) -> bool {
+        self.expanded.contains(name)
+    }
+    pub(crate) fn toggle(&mut self, name: &str) {
+        if self.expanded.remove(name) {
+            self.samples.remove(name);
+            self.exclusion_texts.remove(name);
+            self.tweaks_before.remove(name);
+            self.tweak_previews.remove(name);
+            self.dragged_tweaks.remove(name);
+            self.dragged_indices.remove(name);
+            self.block_coverage.remove(name);
+        } else {
+            self.expanded.insert(name.to_owned());
+        }
+    }
+    /// Keep the state of renamed fonts, see [`crate::rename_fonts`]
+    pub(crate) fn rename(&mut self, renames: &BTreeMap<String, String>) {
+        let taken = take_renamed(renames, |old| {
+            // The caches are rebuilt for the new identifiers
+            self.samples.remove(old);
+            self.tweak_previews.remove(old);
+            self.dragged_tweaks.remove(old);
+            self.dragged_indices.remove(old);
+            self.block_coverage.remove(old);
+            let text = self.exclusion_texts.remove(old);
+            let before = self.tweaks_before.remove(old);
+            self.expanded.remove(old).then_some((text, before))
+        });
+        for (new, (text, before)) in taken {
+            self.expanded.insert(new.clone());
+            if let Some(text) = text {
+                self.exclusion_texts.insert(new.clone(), text);
+            }
+            if let Some(before) = before {
+                self.tweaks_before.insert(new.clone(), before);
+            }
+        }
+    }
+}
+
+/// What to change a property of the font `name` to, holding changes back while they're dragged
+///
+/// Changing a font that the undo history shares copies all of its data, so a drag is
+/// committed once when it's released rather than every frame.
+fn on_release<T: Copy + PartialEq>(
+    dragged: &mut HashMap<String, T>,
+    name: &str,
+    value: T,
+    current: T,
+    dragging: bool,
+) -> Option<T> {
+    if dragging {
+        dragged.insert(name.to_owned(), value);
+        return None;
+    }
+    dragged.remove(name);
+    (value != current).then_some(value)
+}
+
+/// Let the user edit the ranges `name` is excluded from being used for
+fn exclusions_ui(
+    ui: &mut egui::Ui,
+    name: &str,
+    texts: &mut HashMap<String, String>,
+    exclusions: &mut FallbackExclusions,
+) {
+    ui.label("Excluded ranges")
+        .on_hover_text("Code points this font won't be used for, e.g. U+0400-04FF, U+00A0");
+    let text = texts.entry(name.to_owned()).or_insert_with(|| {
+        exclusions
+            .get(name)
+            .map(|ranges| format_codepoint_ranges(ranges))
+            .unwrap_or_default()
+    });
+    ui.vertical(|ui| {
+        let re = ui.add(egui::TextEdit::singleline(text).hint_text("U+0400-04FF, …"));
+        let label = format!("Fallback exclusions of {name}");
+        crate::ui::a11y_text_label(&re, &label, text);
+        match parse_codepoint_ranges(text) {
+            Ok(ranges) if re.changed() => {
+                if ranges.is_empty() {
+                    exclusions.remove(name);
+                } else {
+                    exclusions.insert(name.to_owned(), ranges);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                ui.weak(e);
+            }
+        }
+    });
+}
+
+/// Let the user edit the tweak of `font`, comparing it to the tweak from before the edits
+fn tweak_ui(
+    ui: &mut egui::Ui,
+    name: &str,
+    font: &mut Arc<FontData>,
+    before: &mut Arc<FontData>,
+    dragged: &mut HashMap<String, FontTweak>,
+    preview: &mut PreviewFonts,
+) {
+    // Comparing the data itself would be slow for large fonts
+    if before.index != font.index || before.font.len() != font.font.len() {
+        // The font was replaced, so only keep the tweak
+        *before = Arc::new(FontData {
+            tweak: before.tweak,
+            ..(**font).clone()
+        });
+    }
+    let before_font = before.clone();
+    let before = before.tweak;
+    let mut tweak = dragged.get(name).copied().unwrap_or(font.tweak);
+    let dragging = egui::Grid::new(("font_tweak", name))
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Scale")
+                .on_hover_text("Size of the glyphs compared to other fonts");
+            let mut re = ui.add(egui::Slider::new(&mut tweak.scale, 0.25..=2.0));
+            ui.end_row();
+            ui.label("Y offset factor")
+                .on_hover_text("Vertical shift, as a fraction of the font size");
+            re |= ui.add(egui::Slider::new(&mut tweak.y_offset_factor, -1.0..=1.0));
+            ui.end_row();
+            ui.label("Y offset")
+                .on_hover_text("Vertical shift in points");
+            re |= ui.add(egui::Slider::new(&mut tweak.y_offset, -20.0..=20.0));
+            ui.end_row();
+            ui.label("Baseline offset factor")
+                .on_hover_text("Shift of the baseline, as a fraction of the font size");
+            re |= ui.add(egui::Slider::new(
+                &mut tweak.baseline_offset_factor,
+                -1.0..=1.0,
+            ));
+            ui.end_row();
+            re.dragged()
+        })
+        .inner;
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(tweak != before, egui::Button::new("Undo changes"))
+            .clicked()
+        {
+            tweak = before;
+        }
+        if ui
+            .add_enabled(tweak != FontTweak::default(), egui::Button::new("Reset"))
+            .on_hover_text("Remove the tweak")
+            .clicked()
+        {
+            tweak = FontTweak::default();
+        }
+    });
+    if let Some(tweak) = on_release(dragged, name, tweak, font.tweak, dragging) {
+        Arc::make_mut(font).tweak = tweak;
+    }
+    // Both versions of the font side by side, so their baselines can be compared
+    let before_family = FontFamily::Name("before".into());
+    let after_family = FontFamily::Name("after".into());
+    let mut defs = FontDefinitions::empty();
+    defs.font_data.insert("before".into(), before_font);
+    defs.font_data.insert("after".into(), font.clone());
+    defs.families
+        .insert(before_family.clone(), vec!["before".into()]);
+    defs.families
+        .insert(after_family.clone(), vec!["after".into()]);
+    if let Err(e) = preview.prepare(ui.ctx(), &defs) {
+        ui.weak(e);
+        return;
+    }
+    let size = egui::TextStyle::Body.resolve(ui.style()).size * 2.0;
+    let color = ui.visuals().text_color();
+    let (Some(before), Some(after)) = (
+        preview.layout("Before Hxg", FontId::new(size, before_family), color),
+        preview.layout("After Hxg", FontId::new(size, after_family), color),
+    ) else {
+        return;
+    };
+    let height = before.size().y.max(after.size().y);
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(before.size().x + after.size().x + 16.0, height),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    preview.paint(&painter, rect.min, &before);
+    preview.paint(
+        &painter,
+        rect.min + egui::vec2(before.size().x + 16.0, 0.0),
+        &after,
+    );
+}
+
+/// Language samples, along with characters that a font must have to be considered covering
+/// the script. Only scripts that egui can render without complex shaping are included.
+pub(crate) const LANGUAGE_SAMPLES: &[(&str, &str, &[char])] = &[
+    ("Latin", "The quick brown fox jumps", &['a', 'Z']),
+    ("Greek", "Καλημέρα κόσμε", &['α', 'Ω']),
+    ("Cyrillic", "Съешь же ещё этих булок", &['ж', 'Я']),
+    ("Thai", "สวัสดีชาวโลก", &['ก', 'ส']),
+    ("Kana", "いろはにほへと カタカナ", &['あ', 'ア']),
+    ("Han", "漢字 中文 汉字", &['漢', '中']),
+    ("Hangul", "안녕하세요 세계", &['안', '한']),
+    ("Emoji", "😀 🎉 👍 🦀", &['😀', '👍']),
+];
+
+/// Show samples of the scripts `font` covers, rendered with only that font
+fn language_samples_ui(
+    ui: &mut egui::Ui,
+    name: &str,
+    font: &Arc<FontData>,
+    preview: &mut PreviewFonts,
+) {
+    let Ok(face) = ttf_parser::Face::parse(&font.font, font.index) else {
+        ui.weak("The font can't be parsed");
+        return;
+    };
+    let covered: Vec<_> = LANGUAGE_SAMPLES
+        .iter()
+        .filter(|(_, _, probes)| probes.iter().all(|&c| face.glyph_index(c).is_some()))
+        .collect();
+    if covered.is_empty() {
+        ui.weak("No known scripts covered");
+        return;
+    }
+    let family = FontFamily::Name(name.into());
+    let mut defs = FontDefinitions::empty();
+    defs.font_data.insert(name.to_owned(), font.clone());
+    defs.families.insert(family.clone(), vec![name.to_owned()]);
+    if let Err(e) = preview.prepare(ui.ctx(), &defs) {
+        ui.weak(e);
+        return;
+    }
+    let size = egui::TextStyle::Body.resolve(ui.style()).size;
+    let color = ui.visuals().text_color();
+    for (script, sample, _) in covered {
+        ui.label(*script);
+        if let Some(galley) = preview.layout(sample, FontId::new(size, family.clone()), color) {
+            let (rect, _) = ui.allocate_exact_size(galley.size(), egui::Sense::hover());
+            preview.paint(ui.painter(), rect.min, &galley);
+        }
+        ui.end_row();
+    }
+}
+
+/// Show the details of the font called `name`, allowing some of them to be edited
+pub(crate) fn font_details_ui(
+    ui: &mut egui::Ui,
+    name: &str,
+    font: &mut Arc<FontData>,
+    state: &mut DetailsState,
+    exclusions: &mut FallbackExclusions,
+) {
+    egui::Grid::new(("font_details", name))
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Size");
+            ui.label(format!("{:.1} KiB", font.font.len() as f64 / 1024.0));
+            ui.end_row();
+            if let Some(info) = FontInfo::of(font) {
+                info.grid_rows_ui(ui);
+            }
+            // Fonts that aren't collections have a single face at index 0
+            let faces = ttf_parser::fonts_in_collection(&font.font).unwrap_or(1);
+            ui.label("Face index");
+            let mut index = state
+                .dragged_indices
+                .get(name)
+                .copied()
+                .unwrap_or(font.index);
+            let re = ui
+                .add_enabled(
+                    faces > 1,
+                    egui::DragValue::new(&mut index).range(0..=faces.saturating_sub(1)),
+                )
+                .on_hover_text(format!("Which of the {faces} faces in the file to use"))
+                .on_disabled_hover_text("The file only contains one face");
+            let dragged = &mut state.dragged_indices;
+            if let Some(index) = on_release(dragged, name, index, font.index, re.dragged()) {
+                Arc::make_mut(font).index = index;
+            }
+            ui.end_row();
+            exclusions_ui(ui, name, &mut state.exclusion_texts, exclusions);
+            ui.end_row();
+            let sample_preview = state.samples.entry(name.to_owned()).or_default();
+            language_samples_ui(ui, name, font, sample_preview);
+        });
+    egui::CollapsingHeader::new("Tweak")
+        .id_salt(("font_tweak_section", name))
+        .show(ui, |ui| {
+            let before = state
+                .tweaks_before
+                .entry(name.to_owned())
+                .or_insert_with(|| font.clone());
+            let preview = state.tweak_previews.entry(name.to_owned()).or_default();
+            let dragged = &mut state.dragged_tweaks;
+            tweak_ui(ui, name, font, before, dragged, preview);
+        });
+    egui::CollapsingHeader::new("Coverage")
+        .id_salt(("font_coverage_section", name))
+        .show(ui, |ui| state.block_coverage.ui(ui, name, font));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drags_are_committed_on_release() {
+        let mut dragged = HashMap::new();
+        assert_eq!(on_release(&mut dragged, "font", 1, 0, true), None);
+        assert_eq!(on_release(&mut dragged, "font", 2, 0, true), None);
+        assert_eq!(dragged.get("font"), Some(&2));
+        assert_eq!(on_release(&mut dragged, "font", 2, 0, false), Some(2));
+        assert!(dragged.is_empty());
+        assert_eq!(on_release(&mut dragged, "font", 2, 2, false), None);
+    }
+}
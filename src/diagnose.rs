@@ -0,0 +1,263 @@
+//! Finding out why characters show up as boxes
+
+use {
+    egui::{FontDefinitions, FontFamily},
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+/// A character that a built-in family has no glyph for, see [`diagnose_missing_glyphs`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingGlyph {
+    /// The character
+    pub ch: char,
+    /// The families none of whose fonts cover the character
+    pub missing_in: Vec<FontFamily>,
+    /// Loaded fonts that do cover it, and could be added to those families
+    pub covered_by: Vec<String>,
+}
+
+/// Check which characters of `text` the [`FontFamily::Proportional`] and
+/// [`FontFamily::Monospace`] families can't show
+///
+/// Each character is looked up in the character maps of the fonts of each family, in fallback
+/// order, like egui does. Whitespace and control characters are skipped.
+pub fn diagnose_missing_glyphs(font_defs: &FontDefinitions, text: &str) -> Vec<MissingGlyph> {
+    let faces: BTreeMap<&str, ttf_parser::Face> = font_defs
+        .font_data
+        .iter()
+        .filter_map(|(name, font)| {
+            let face = ttf_parser::Face::parse(&font.font, font.index).ok()?;
+            Some((name.as_str(), face))
+        })
+        .collect();
+    let covers = |name: &str, ch: char| {
+        faces
+            .get(name)
+            .is_some_and(|face| face.glyph_index(ch).is_some_and(|id| id.0 != 0))
+    };
+    let chars: BTreeSet<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    chars
+        .into_iter()
+        .filter_map(|ch| {
+            let missing_in: Vec<_> = [FontFamily::Proportional, FontFamily::Monospace]
+                .into_iter()
+                .filter(|family| {
+                    !font_defs
+                        .families
+                        .get(family)
+                        .is_some_and(|fonts| fonts.iter().any(|f| covers(f, ch)))
+                })
+                .collect();
+            if missing_in.is_empty() {
+                return None;
+            }
+            let covered_by = faces
+                .keys()
+                .filter(|name| covers(name, ch))
+                .map(|name| name.to_string())
+                .collect();
+            Some(MissingGlyph {
+                ch,
+                missing_in,
+                covered_by,
+            })
+        })
+        .collect()
+}
+
+/// What the user asked for in the missing glyph tool
+pub(crate) enum DiagnoseAction {
+    /// Add the loaded font to the end of the families
+    AddToFamilies(String, BTreeSet<FontFamily>),
+    /// Add the installed font
+    #[cfg(feature = "system-fonts")]
+    AddSystemFont(crate::SystemFont),
+    /// Look for installed fonts covering the characters, see [`DiagnoseForm::search_system_fonts`]
+    #[cfg(feature = "system-fonts")]
+    SearchSystemFonts(Vec<MissingGlyph>),
+}
+
+/// The "Why is this character a box?" tool
+#[derive(Default)]
+pub(crate) struct DiagnoseForm {
+    text: String,
+    /// The missing glyphs of the text, and the definitions they were found for
+    missing: Option<(crate::DefsFingerprint, Vec<MissingGlyph>)>,
+    /// Installed fonts covering each missing character, once searched for
+    #[cfg(feature = "system-fonts")]
+    system_matches: Option<BTreeMap<char, Vec<crate::SystemFont>>>,
+    /// The search for installed fonts running in the background
+    #[cfg(feature = "system-fonts")]
+    searching: Option<std::sync::mpsc::Receiver<BTreeMap<char, Vec<crate::SystemFont>>>>,
+}
+
+/// Installed fonts suggested for each missing character, at most
+#[cfg(feature = "system-fonts")]
+const MAX_SYSTEM_MATCHES: usize = 3;
+
+impl DiagnoseForm {
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &FontDefinitions,
+    ) -> Option<DiagnoseAction> {
+        let re = ui.add(
+            egui::TextEdit::multiline(&mut self.text)
+                .hint_text("Paste text that shows boxes")
+                .desired_rows(2),
+        );
+        crate::ui::a11y_text_label(&re, "Text to check for missing glyphs", &self.text);
+        if re.changed() {
+            self.missing = None;
+            #[cfg(feature = "system-fonts")]
+            {
+                self.system_matches = None;
+                self.searching = None;
+            }
+        }
+        #[cfg(feature = "system-fonts")]
+        if let Some(searching) = &self.searching {
+            match searching.try_recv() {
+                Ok(matches) => self.system_matches = Some(matches),
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_millis(100));
+                }
+                // The search panicked
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.system_matches = Some(BTreeMap::new());
+                }
+            }
+            if self.system_matches.is_some() {
+                self.searching = None;
+            }
+        }
+        if self.text.trim().is_empty() {
+            return None;
+        }
+        // Looking the characters up parses every font, so only do it when something changed
+        if !self
+            .missing
+            .as_ref()
+            .is_some_and(|(fingerprint, _)| fingerprint.matches(font_defs))
+        {
+            let missing = diagnose_missing_glyphs(font_defs, &self.text);
+            self.missing = Some((crate::DefsFingerprint::new(font_defs), missing));
+        }
+        let Some((_, missing)) = &self.missing else {
+            return None;
+        };
+        if missing.is_empty() {
+            ui.weak("Every character is covered in Proportional and Monospace");
+            return None;
+        }
+        let mut action = None;
+        egui::Grid::new("missing_glyphs")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for glyph in missing {
+                    ui.label(format!("{} U+{:04X}", glyph.ch, u32::from(glyph.ch)));
+                    ui.vertical(|ui| {
+                        let families: Vec<_> =
+                            glyph.missing_in.iter().map(ToString::to_string).collect();
+                        ui.label(format!("Missing in {}", families.join(", ")));
+                        if glyph.covered_by.is_empty() {
+                            ui.weak("No loaded font covers it");
+                        }
+                        for name in &glyph.covered_by {
+                            if ui
+                                .button(format!("Add {name}"))
+                                .on_hover_text("Add it as the last fallback of these families")
+                                .clicked()
+                            {
+                                let families = glyph.missing_in.iter().cloned().collect();
+                                action =
+                                    Some(DiagnoseAction::AddToFamilies(name.clone(), families));
+                            }
+                        }
+                        #[cfg(feature = "system-fonts")]
+                        if let Some(fonts) = self
+                            .system_matches
+                            .as_ref()
+                            .and_then(|matches| matches.get(&glyph.ch))
+                        {
+                            for font in fonts {
+                                if ui
+                                    .button(format!("Add installed {}", font.display_name()))
+                                    .on_hover_text(font.path.display().to_string())
+                                    .clicked()
+                                {
+                                    action = Some(DiagnoseAction::AddSystemFont(font.clone()));
+                                }
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        #[cfg(feature = "system-fonts")]
+        if self.searching.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Searching installed fonts…");
+            });
+        } else if self.system_matches.is_none()
+            && ui
+                .button("Search installed fonts")
+                .on_hover_text("Look for installed fonts covering the missing characters. This reads every installed font, so it can take a while.")
+                .clicked()
+        {
+            action = Some(DiagnoseAction::SearchSystemFonts(missing.clone()));
+        }
+        action
+    }
+    /// Start looking for installed fonts covering the `missing` characters on `spawner`
+    #[cfg(feature = "system-fonts")]
+    pub(crate) fn search_system_fonts(
+        &mut self,
+        missing: Vec<MissingGlyph>,
+        spawner: &crate::Spawner,
+    ) {
+        self.system_matches = None;
+        self.searching = Some(crate::loading::spawn(spawner, move || {
+            search_system_fonts(&missing)
+        }));
+    }
+}
+
+/// Find installed fonts covering each of the `missing` characters
+#[cfg(feature = "system-fonts")]
+fn search_system_fonts(missing: &[MissingGlyph]) -> BTreeMap<char, Vec<crate::SystemFont>> {
+    let mut matches: BTreeMap<char, Vec<crate::SystemFont>> = BTreeMap::new();
+    for font in crate::system_fonts() {
+        let all_found = missing.iter().all(|glyph| {
+            matches
+                .get(&glyph.ch)
+                .is_some_and(|found| found.len() >= MAX_SYSTEM_MATCHES)
+        });
+        if all_found {
+            break;
+        }
+        let Ok(data) = std::fs::read(&font.path) else {
+            continue;
+        };
+        if crate::format::check_font_data(&data, font.index).is_err() {
+            continue;
+        }
+        let Ok(face) = ttf_parser::Face::parse(&data, font.index) else {
+            continue;
+        };
+        for glyph in missing {
+            let found = matches.entry(glyph.ch).or_default();
+            if found.len() < MAX_SYSTEM_MATCHES
+                && face.glyph_index(glyph.ch).is_some_and(|id| id.0 != 0)
+            {
+                found.push(font.clone());
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,203 @@
+//! Keeping fonts from being used for some code points
+//!
+//! egui picks the first font of a family that has a glyph for a character, so the only way to
+//! make it skip a font for a range is to remove the range from the font's character map.
+
+use {
+    egui::{FontData, FontDefinitions},
+    std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc},
+};
+
+/// Code point ranges each font (by identifier) must not be used for
+///
+/// The exclusions apply in every family the font is a member of.
+/// They are enforced when applying, by [`apply_fallback_exclusions`].
+pub type FallbackExclusions = BTreeMap<String, Vec<RangeInclusive<u32>>>;
+
+/// Parse ranges written like `U+0400-04FF, 2000-206F, U+00A0`
+pub fn parse_codepoint_ranges(text: &str) -> Result<Vec<RangeInclusive<u32>>, String> {
+    let parse = |s: &str| {
+        let s = s.trim();
+        let hex = s
+            .strip_prefix("U+")
+            .or_else(|| s.strip_prefix("u+"))
+            .unwrap_or(s);
+        u32::from_str_radix(hex, 16).map_err(|_| format!("{s:?} is not a hexadecimal code point"))
+    };
+    text.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => {
+                    let cp = parse(part)?;
+                    (cp, cp)
+                }
+            };
+            if start > end {
+                return Err(format!("Range {part:?} ends before it starts"));
+            }
+            Ok(start..=end)
+        })
+        .collect()
+}
+
+/// Write ranges in the format [`parse_codepoint_ranges`] accepts
+pub fn format_codepoint_ranges(ranges: &[RangeInclusive<u32>]) -> String {
+    ranges
+        .iter()
+        .map(|r| {
+            if r.start() == r.end() {
+                format!("U+{:04X}", r.start())
+            } else {
+                format!("U+{:04X}-{:04X}", r.start(), r.end())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// A format 12 `cmap` table with a single Windows Unicode full repertoire subtable
+fn build_cmap(mapping: &BTreeMap<u32, u16>) -> Vec<u8> {
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (&cp, &gid) in mapping {
+        match groups.last_mut() {
+            Some((start, end, start_gid))
+                if cp == *end + 1 && gid as u32 == *start_gid + cp - *start =>
+            {
+                *end = cp;
+            }
+            _ => groups.push((cp, cp, gid.into())),
+        }
+    }
+    let subtable_len = 16 + 12 * groups.len() as u32;
+    let mut out = Vec::with_capacity(12 + subtable_len as usize);
+    // Header: version, number of encoding records
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&1u16.to_be_bytes());
+    // Encoding record: Windows platform, Unicode full repertoire, subtable offset
+    out.extend_from_slice(&3u16.to_be_bytes());
+    out.extend_from_slice(&10u16.to_be_bytes());
+    out.extend_from_slice(&12u32.to_be_bytes());
+    // Subtable: format, reserved, length, language, number of groups
+    out.extend_from_slice(&12u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&subtable_len.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+    out.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+    for (start, end, gid) in groups {
+        out.extend_from_slice(&start.to_be_bytes());
+        out.extend_from_slice(&end.to_be_bytes());
+        out.extend_from_slice(&gid.to_be_bytes());
+    }
+    out
+}
+
+fn table_checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Copy of `font` without glyph mappings for the code points in `ranges`
+///
+/// For font collections, only the used face is kept. Returns `None` if the font can't be parsed.
+pub fn exclude_codepoints(font: &FontData, ranges: &[RangeInclusive<u32>]) -> Option<FontData> {
+    let data: &[u8] = &font.font;
+    let face = ttf_parser::Face::parse(data, font.index).ok()?;
+    let mut mapping = BTreeMap::new();
+    for subtable in face.tables().cmap?.subtables {
+        if !subtable.is_unicode() {
+            continue;
+        }
+        subtable.codepoints(|cp| {
+            if ranges.iter().any(|r| r.contains(&cp)) {
+                return;
+            }
+            if let Some(gid) = subtable.glyph_index(cp) {
+                mapping.entry(cp).or_insert(gid.0);
+            }
+        });
+    }
+    // Offset of the table directory of the face
+    let dir = if data.get(..4)? == b"ttcf" {
+        read_u32(data, 12 + 4 * font.index as usize)? as usize
+    } else {
+        0
+    };
+    let num_tables = read_u16(data, dir + 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    let cmap = build_cmap(&mapping);
+    for i in 0..num_tables {
+        let rec = dir + 12 + 16 * i;
+        let tag: [u8; 4] = data.get(rec..rec + 4)?.try_into().ok()?;
+        let table = if &tag == b"cmap" {
+            &cmap[..]
+        } else {
+            let offset = read_u32(data, rec + 8)? as usize;
+            let len = read_u32(data, rec + 12)? as usize;
+            data.get(offset..offset.checked_add(len)?)?
+        };
+        tables.push((tag, table));
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(data.get(dir..dir + 4)?);
+    let entry_selector = (num_tables as u16).checked_ilog2().unwrap_or(0) as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    for field in [
+        num_tables as u16,
+        search_range,
+        entry_selector,
+        (num_tables as u16 * 16).saturating_sub(search_range),
+    ] {
+        out.extend_from_slice(&field.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * num_tables;
+    for (tag, table) in &tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&table_checksum(table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        out.extend_from_slice(table);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    Some(FontData {
+        font: out.into(),
+        index: 0,
+        tweak: font.tweak,
+    })
+}
+
+/// Copy of `font_defs` with the fonts that have exclusions replaced by copies without them
+///
+/// Fonts that can't be parsed are kept as they are.
+pub fn apply_fallback_exclusions(
+    font_defs: &FontDefinitions,
+    exclusions: &FallbackExclusions,
+) -> FontDefinitions {
+    let mut defs = font_defs.clone();
+    for (name, ranges) in exclusions {
+        if ranges.is_empty() {
+            continue;
+        }
+        if let Some(font) = defs.font_data.get_mut(name) {
+            if let Some(excluded) = exclude_codepoints(font, ranges) {
+                *font = Arc::new(excluded);
+            }
+        }
+    }
+    defs
+}
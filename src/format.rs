@@ -0,0 +1,201 @@
+//! Recognizing font file formats egui can't load, to explain the problem instead of failing
+//! at rasterization time
+
+/// The format of font file data, see [`detect_font_format`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontFormat {
+    /// TrueType or OpenType font with outlines, which egui can load
+    Sfnt,
+    /// TrueType or OpenType collection of several fonts, which egui can load
+    Collection,
+    /// TrueType or OpenType font with only embedded bitmaps (e.g. color emoji)
+    BitmapOnly,
+    /// Web Open Font Format 1.0
+    Woff,
+    /// Web Open Font Format 2.0
+    Woff2,
+    /// PostScript Type 1 font (`.pfb`, `.pfa`, or wrapped in a TrueType container)
+    Type1,
+    /// Mac OS resource fork font suitcase (`.dfont`)
+    Dfont,
+    /// Glyph Bitmap Distribution Format (`.bdf`)
+    Bdf,
+    /// Portable Compiled Format bitmap font (`.pcf`)
+    Pcf,
+    /// Not recognized as a font
+    Unknown,
+}
+
+impl FontFormat {
+    /// Whether egui can render fonts of this format
+    pub fn is_supported(self) -> bool {
+        matches!(self, Self::Sfnt | Self::Collection)
+    }
+    /// Human readable name of the format
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sfnt => "TrueType/OpenType font",
+            Self::Collection => "TrueType/OpenType collection",
+            Self::BitmapOnly => "Bitmap-only font",
+            Self::Woff => "WOFF web font",
+            Self::Woff2 => "WOFF2 web font",
+            Self::Type1 => "PostScript Type 1 font",
+            Self::Dfont => "Mac OS font suitcase",
+            Self::Bdf => "BDF bitmap font",
+            Self::Pcf => "PCF bitmap font",
+            Self::Unknown => "Unknown format",
+        }
+    }
+    /// What the user can do to get a usable font, for unsupported formats
+    pub fn hint(self) -> Option<&'static str> {
+        Some(match self {
+            Self::Sfnt | Self::Collection => return None,
+            Self::BitmapOnly => {
+                "egui only renders outlines. For emoji, use a monochrome outline font \
+                 like Noto Emoji instead of a color one."
+            }
+            Self::Woff | Self::Woff2 => {
+                "Web fonts are compressed. Download the TTF or OTF version of the font, \
+                 or decompress it with a tool like fonttools."
+            }
+            Self::Type1 => {
+                "Type 1 fonts are obsolete. Look for an OpenType version of the font, \
+                 or convert it with FontForge."
+            }
+            Self::Dfont => {
+                "Extract the TrueType font from the suitcase, e.g. with fondu or FontForge."
+            }
+            Self::Bdf | Self::Pcf => {
+                "Bitmap fonts need to be converted to an outline font first, e.g. with \
+                 FontForge or bitsnpicas."
+            }
+            Self::Unknown => "The file doesn't seem to be a font.",
+        })
+    }
+}
+
+/// Determine the format of font file data
+pub fn detect_font_format(data: &[u8]) -> FontFormat {
+    match data.get(..4) {
+        Some(b"wOFF") => return FontFormat::Woff,
+        Some(b"wOF2") => return FontFormat::Woff2,
+        Some(b"ttcf") => return FontFormat::Collection,
+        Some(b"typ1") => return FontFormat::Type1,
+        Some(b"\x01fcp") => return FontFormat::Pcf,
+        _ => {}
+    }
+    if data.starts_with(b"\x80\x01") || data.starts_with(b"%!") {
+        return FontFormat::Type1;
+    }
+    if data.starts_with(b"STARTFONT") {
+        return FontFormat::Bdf;
+    }
+    if let Ok(face) = ttf_parser::Face::parse(data, 0) {
+        let tables = face.tables();
+        let outlines = tables.glyf.is_some() || tables.cff.is_some() || tables.cff2.is_some();
+        let bitmaps = tables.cbdt.is_some()
+            || tables.ebdt.is_some()
+            || tables.bdat.is_some()
+            || tables.sbix.is_some();
+        return if !outlines && bitmaps {
+            FontFormat::BitmapOnly
+        } else {
+            FontFormat::Sfnt
+        };
+    }
+    if suitcase_fonts(data).is_some_and(|fonts| !fonts.is_empty()) {
+        return FontFormat::Dfont;
+    }
+    FontFormat::Unknown
+}
+
+/// The `sfnt` resources of a Mac OS resource fork, which are complete TrueType fonts
+fn suitcase_fonts(data: &[u8]) -> Option<Vec<&[u8]>> {
+    let u16_at = |pos: usize| Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?));
+    let u32_at = |pos: usize| Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?));
+    let data_start = u32_at(0)? as usize;
+    let map_start = u32_at(4)? as usize;
+    let type_list = map_start + usize::from(u16_at(map_start + 24)?);
+    let types = usize::from(u16_at(type_list)?) + 1;
+    let mut fonts = Vec::new();
+    for i in 0..types {
+        let entry = type_list + 2 + i * 8;
+        if data.get(entry..entry + 4)? != b"sfnt" {
+            continue;
+        }
+        let count = usize::from(u16_at(entry + 4)?) + 1;
+        let refs = type_list + usize::from(u16_at(entry + 6)?);
+        for j in 0..count {
+            // The data offset is 3 bytes long, following a byte of attributes
+            let offset = u32_at(refs + j * 12 + 4)? as usize & 0x00ff_ffff;
+            let pos = data_start.checked_add(offset)?;
+            let len = u32_at(pos)? as usize;
+            fonts.push(data.get(pos + 4..(pos + 4).checked_add(len)?)?);
+        }
+    }
+    Some(fonts)
+}
+
+/// Convert font data of a format egui can't load into one it can, if possible
+///
+/// With the `font-conversion` feature, this extracts the first font of Mac OS font
+/// suitcases. With the `bitmap-fonts` feature, BDF and PCF fonts are converted to TrueType
+/// fonts that look like the bitmaps at multiples of their pixel size.
+#[cfg(any(feature = "font-conversion", feature = "bitmap-fonts"))]
+pub fn convert_font(data: &[u8]) -> Result<Vec<u8>, String> {
+    let format = detect_font_format(data);
+    match format {
+        #[cfg(feature = "font-conversion")]
+        FontFormat::Dfont => suitcase_fonts(data)
+            .and_then(|fonts| Some(fonts.first()?.to_vec()))
+            .ok_or_else(|| "The suitcase contains no fonts".into()),
+        #[cfg(feature = "bitmap-fonts")]
+        FontFormat::Bdf | FontFormat::Pcf => crate::bitmap::convert(data),
+        _ => Err(format!("{} can't be converted", format.label())),
+    }
+}
+
+/// Make sure face `index` of `data` is in a format egui can load, converting it if possible
+///
+/// The error explains what is wrong and what to do about it.
+pub(crate) fn ensure_loadable(data: Vec<u8>, index: u32) -> Result<Vec<u8>, String> {
+    let data = convert_if_needed(data)?;
+    check_font_data(&data, index)?;
+    Ok(data)
+}
+
+fn convert_if_needed(data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let format = detect_font_format(&data);
+    if format.is_supported() {
+        return Ok(data);
+    }
+    #[cfg(any(feature = "font-conversion", feature = "bitmap-fonts"))]
+    match format {
+        #[cfg(feature = "font-conversion")]
+        FontFormat::Dfont => return convert_font(&data),
+        #[cfg(feature = "bitmap-fonts")]
+        FontFormat::Bdf | FontFormat::Pcf => return convert_font(&data),
+        _ => {}
+    }
+    Err(format!(
+        "{} can't be used: {}",
+        format.label(),
+        format.hint().unwrap_or_default()
+    ))
+}
+
+/// Make sure face `index` of `data` can be parsed, so egui doesn't choke on it later
+pub(crate) fn check_font_data(data: &[u8], index: u32) -> Result<(), String> {
+    // ttf-parser asserts instead of failing on collections with absurd face counts
+    if data.starts_with(b"ttcf") {
+        let faces = data.get(8..12).map_or(0, |n| {
+            u32::from_be_bytes(n.try_into().unwrap_or_default()) as usize
+        });
+        if faces.saturating_mul(4).saturating_add(12) > data.len() {
+            return Err("Not a valid TTF/OTF font: the collection header is truncated".into());
+        }
+    }
+    ttf_parser::Face::parse(data, index)
+        .map(drop)
+        .map_err(|e| format!("Not a valid TTF/OTF font: {e}"))
+}
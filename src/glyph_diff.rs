@@ -0,0 +1,300 @@
+//! Which glyphs of a font look different in the pending definitions than in the applied ones
+
+use {
+    crate::{
+        details::LANGUAGE_SAMPLES, exclusions::format_codepoint_ranges, preview::PreviewFonts,
+        preview::SAMPLE_PRESETS,
+    },
+    egui::{FontData, FontDefinitions, FontFamily, FontId},
+    std::{
+        collections::{BTreeSet, HashMap},
+        hash::{Hash, Hasher},
+        ops::RangeInclusive,
+        sync::{Arc, Weak},
+    },
+};
+
+/// How the glyphs of a font changed
+struct GlyphDiff {
+    /// The tweak or face index changed, which affects every glyph
+    everything: bool,
+    /// Code points that only the new font has glyphs for
+    added: BTreeSet<u32>,
+    /// Code points that only the old font has glyphs for
+    removed: BTreeSet<u32>,
+    /// Code points whose outline or advance changed
+    changed: BTreeSet<u32>,
+}
+
+impl GlyphDiff {
+    fn is_empty(&self) -> bool {
+        !self.everything
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+    fn affects(&self, c: char) -> bool {
+        let c = c as u32;
+        self.everything
+            || self.added.contains(&c)
+            || self.removed.contains(&c)
+            || self.changed.contains(&c)
+    }
+}
+
+/// Hashes an outline with coordinates relative to the em size, ignoring rounding noise
+struct OutlineHasher {
+    hasher: std::collections::hash_map::DefaultHasher,
+    units_per_em: f32,
+}
+
+impl OutlineHasher {
+    fn point(&mut self, x: f32, y: f32) {
+        for v in [x, y] {
+            ((v / self.units_per_em * 4096.0).round() as i32).hash(&mut self.hasher);
+        }
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineHasher {
+    fn move_to(&mut self, x: f32, y: f32) {
+        0u8.hash(&mut self.hasher);
+        self.point(x, y);
+    }
+    fn line_to(&mut self, x: f32, y: f32) {
+        1u8.hash(&mut self.hasher);
+        self.point(x, y);
+    }
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        2u8.hash(&mut self.hasher);
+        self.point(x1, y1);
+        self.point(x, y);
+    }
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        3u8.hash(&mut self.hasher);
+        self.point(x1, y1);
+        self.point(x2, y2);
+        self.point(x, y);
+    }
+    fn close(&mut self) {
+        4u8.hash(&mut self.hasher);
+    }
+}
+
+/// Glyph appearance of every mapped code point, as a hash of its advance and outline
+fn glyph_signatures(font: &FontData) -> HashMap<u32, u64> {
+    let mut out = HashMap::new();
+    let Ok(face) = ttf_parser::Face::parse(&font.font, font.index) else {
+        return out;
+    };
+    let units_per_em = f32::from(face.units_per_em());
+    let mut codepoints = BTreeSet::new();
+    if let Some(cmap) = face.tables().cmap {
+        for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+            subtable.codepoints(|cp| {
+                codepoints.insert(cp);
+            });
+        }
+    }
+    for cp in codepoints {
+        let Some(id) = char::from_u32(cp).and_then(|c| face.glyph_index(c)) else {
+            continue;
+        };
+        let mut hasher = OutlineHasher {
+            hasher: Default::default(),
+            units_per_em,
+        };
+        hasher.point(f32::from(face.glyph_hor_advance(id).unwrap_or(0)), 0.0);
+        face.outline_glyph(id, &mut hasher);
+        out.insert(cp, hasher.hasher.finish());
+    }
+    out
+}
+
+fn glyph_diff(old: &FontData, new: &FontData) -> GlyphDiff {
+    let everything = old.index != new.index || old.tweak != new.tweak;
+    if old.font == new.font {
+        return GlyphDiff {
+            everything,
+            added: BTreeSet::new(),
+            removed: BTreeSet::new(),
+            changed: BTreeSet::new(),
+        };
+    }
+    let old = glyph_signatures(old);
+    let new = glyph_signatures(new);
+    let mut diff = GlyphDiff {
+        everything,
+        added: new
+            .keys()
+            .filter(|cp| !old.contains_key(cp))
+            .copied()
+            .collect(),
+        removed: old
+            .keys()
+            .filter(|cp| !new.contains_key(cp))
+            .copied()
+            .collect(),
+        changed: BTreeSet::new(),
+    };
+    diff.changed = new
+        .iter()
+        .filter(|(cp, sig)| old.get(cp).is_some_and(|old| old != *sig))
+        .map(|(cp, _)| *cp)
+        .collect();
+    diff
+}
+
+/// Collapse sorted code points into ranges of consecutive ones
+fn to_ranges(codepoints: &BTreeSet<u32>) -> Vec<RangeInclusive<u32>> {
+    let mut ranges: Vec<RangeInclusive<u32>> = Vec::new();
+    for &cp in codepoints {
+        match ranges.last_mut() {
+            Some(last) if *last.end() + 1 == cp => *last = *last.start()..=cp,
+            _ => ranges.push(cp..=cp),
+        }
+    }
+    ranges
+}
+
+/// A computed diff along with previews of the font before and after
+struct Entry {
+    old: Weak<FontData>,
+    new: Weak<FontData>,
+    diff: GlyphDiff,
+    before: PreviewFonts,
+    after: PreviewFonts,
+}
+
+/// Per font glyph diffs, which are only recomputed when either side changes
+#[derive(Default)]
+pub(crate) struct GlyphDiffCache {
+    fonts: HashMap<String, Entry>,
+}
+
+impl GlyphDiffCache {
+    fn entry(&mut self, name: &str, old: &Arc<FontData>, new: &Arc<FontData>) -> &mut Entry {
+        let fresh = |entry: &Entry| {
+            entry.old.upgrade().is_some_and(|e| Arc::ptr_eq(&e, old))
+                && entry.new.upgrade().is_some_and(|e| Arc::ptr_eq(&e, new))
+        };
+        if !self.fonts.get(name).is_some_and(fresh) {
+            self.fonts.insert(
+                name.to_owned(),
+                Entry {
+                    old: Arc::downgrade(old),
+                    new: Arc::downgrade(new),
+                    diff: glyph_diff(old, new),
+                    before: PreviewFonts::default(),
+                    after: PreviewFonts::default(),
+                },
+            );
+        }
+        self.fonts.get_mut(name).expect("Just inserted")
+    }
+}
+
+/// Render `text` with only `font` into `preview`
+fn sample_ui(ui: &mut egui::Ui, preview: &mut PreviewFonts, font: &Arc<FontData>, text: &str) {
+    let family = FontFamily::Name("glyph_diff".into());
+    let mut defs = FontDefinitions::empty();
+    defs.font_data.insert("font".into(), font.clone());
+    defs.families.insert(family.clone(), vec!["font".into()]);
+    if let Err(e) = preview.prepare(ui.ctx(), &defs) {
+        ui.weak(e);
+        return;
+    }
+    let size = egui::TextStyle::Body.resolve(ui.style()).size;
+    let color = ui.visuals().text_color();
+    if let Some(galley) = preview.layout(text, FontId::new(size, family), color) {
+        let size = preview.display_size(ui.ctx(), &galley);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        preview.paint(ui.painter(), rect.min, &galley);
+    }
+}
+
+/// How many ranges of affected code points are listed before the rest are summarized
+const MAX_RANGES: usize = 24;
+
+fn ranges_row(ui: &mut egui::Ui, label: &str, codepoints: &BTreeSet<u32>) {
+    if codepoints.is_empty() {
+        return;
+    }
+    let ranges = to_ranges(codepoints);
+    let mut text = format_codepoint_ranges(&ranges[..ranges.len().min(MAX_RANGES)]);
+    if ranges.len() > MAX_RANGES {
+        text.push_str(&format!(" and {} more ranges", ranges.len() - MAX_RANGES));
+    }
+    ui.label(format!("{label} ({})", codepoints.len()));
+    ui.label(text);
+    ui.end_row();
+}
+
+/// For each font that differs from the applied one of the same name, show the affected
+/// glyph ranges and sample lines as they look before and after applying
+pub(crate) fn glyph_diff_ui(
+    ui: &mut egui::Ui,
+    cache: &mut GlyphDiffCache,
+    font_defs: &FontDefinitions,
+) {
+    let applied = ui.fonts(|f| f.lock().fonts.definitions().font_data.clone());
+    cache
+        .fonts
+        .retain(|name, _| font_defs.font_data.contains_key(name));
+    let mut any = false;
+    for (name, new) in &font_defs.font_data {
+        let Some(old) = applied.get(name) else {
+            continue;
+        };
+        if Arc::ptr_eq(old, new) {
+            continue;
+        }
+        let entry = cache.entry(name, old, new);
+        if entry.diff.is_empty() {
+            continue;
+        }
+        any = true;
+        ui.strong(name);
+        egui::Grid::new(("glyph_diff", name))
+            .num_columns(2)
+            .show(ui, |ui| {
+                if entry.diff.everything {
+                    ui.label("All glyphs");
+                    ui.label("The tweak or face index changed");
+                    ui.end_row();
+                }
+                ranges_row(ui, "Added", &entry.diff.added);
+                ranges_row(ui, "Removed", &entry.diff.removed);
+                ranges_row(ui, "Changed", &entry.diff.changed);
+            });
+        let lines: Vec<&str> = SAMPLE_PRESETS
+            .iter()
+            .map(|(_, text)| *text)
+            .chain(LANGUAGE_SAMPLES.iter().map(|(_, text, _)| *text))
+            .filter(|text| {
+                text.chars()
+                    .any(|c| !c.is_whitespace() && entry.diff.affects(c))
+            })
+            .collect();
+        if lines.is_empty() {
+            ui.weak("None of the sample lines are affected");
+            continue;
+        }
+        egui::Grid::new(("glyph_diff_samples", name))
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for text in lines {
+                    ui.weak("Before");
+                    sample_ui(ui, &mut entry.before, old, text);
+                    ui.end_row();
+                    ui.weak("After");
+                    sample_ui(ui, &mut entry.after, new, text);
+                    ui.end_row();
+                }
+            });
+    }
+    if !any {
+        ui.label("No applied font changes its glyphs");
+    }
+}
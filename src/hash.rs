@@ -0,0 +1,82 @@
+//! Content hashes of font files
+
+use {egui::FontDefinitions, sha2::Digest};
+
+/// SHA-256 hash of the contents of a font file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub [u8; 32]);
+
+impl ContentHash {
+    /// Hash `data`
+    pub fn of(data: &[u8]) -> Self {
+        Self(sha2::Sha256::digest(data).into())
+    }
+    /// Parse a hash from the hexadecimal form produced by [`Display`](std::fmt::Display)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+        let mut out = [0; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Self(out))
+    }
+}
+
+impl std::fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+/// Find a loaded font whose file has the same contents as `data`, e.g. to use it instead of
+/// loading the same file again under another identifier
+///
+/// Only fonts of the same size are hashed, so this is cheap even with many fonts loaded.
+/// Faces other than the first of a collection don't count as the same font.
+pub fn find_duplicate_font<'a>(font_defs: &'a FontDefinitions, data: &[u8]) -> Option<&'a str> {
+    let mut hash = None;
+    font_defs
+        .font_data
+        .iter()
+        .find(|(_, font)| {
+            font.index == 0
+                && font.font.len() == data.len()
+                && ContentHash::of(&font.font) == *hash.get_or_insert_with(|| ContentHash::of(data))
+        })
+        .map(|(name, _)| name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentHash;
+
+    fn hex(data: &[u8]) -> String {
+        ContentHash::of(data).to_string()
+    }
+
+    // Known answers from NIST FIPS 180-2
+    #[test]
+    fn nist_vectors() {
+        assert_eq!(
+            hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let hash = ContentHash::of(b"abc");
+        assert_eq!(ContentHash::from_hex(&hash.to_string()), Some(hash));
+        assert_eq!(ContentHash::from_hex("abc"), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Overview of problems with the configured fonts
+
+use {crate::CustomFontPaths, egui::FontDefinitions, std::collections::BTreeMap};
+
+/// Health of a single font, see [`font_statuses`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FontStatus {
+    /// Nothing wrong with the font
+    Ok,
+    /// The font isn't used by any family
+    Unused,
+    /// The custom font's file no longer exists, so it can't be reloaded
+    MissingFile,
+    /// The font data can't be parsed
+    ParseFailed,
+}
+
+impl FontStatus {
+    /// All statuses, from best to worst
+    pub const ALL: [Self; 4] = [Self::Ok, Self::Unused, Self::MissingFile, Self::ParseFailed];
+    /// Human readable description
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Unused => "Unused",
+            Self::MissingFile => "Missing file",
+            Self::ParseFailed => "Failed to parse",
+        }
+    }
+}
+
+/// Determine the status of each font in `font_defs`
+///
+/// A font with several problems gets the status of the worst one.
+pub fn font_statuses(
+    font_defs: &FontDefinitions,
+    custom: Option<&CustomFontPaths>,
+) -> BTreeMap<String, FontStatus> {
+    font_defs
+        .font_data
+        .iter()
+        .map(|(name, font)| {
+            let status = if ttf_parser::Face::parse(&font.font, font.index).is_err() {
+                FontStatus::ParseFailed
+            } else if custom
+                .and_then(|custom| custom.get(name))
+                .filter(|path| !crate::catalog::is_bundled(path))
+                .is_some_and(|path| {
+                    !std::path::Path::new(crate::split_custom_font_path(path).0).is_file()
+                })
+            {
+                FontStatus::MissingFile
+            } else if !font_defs.families.values().flatten().any(|n| n == name) {
+                FontStatus::Unused
+            } else {
+                FontStatus::Ok
+            };
+            (name.clone(), status)
+        })
+        .collect()
+}
+
+/// Show the number of fonts with each status. Clicking one filters the font list by it.
+pub(crate) fn dashboard_ui(
+    ui: &mut egui::Ui,
+    statuses: &BTreeMap<String, FontStatus>,
+    filter: &mut Option<FontStatus>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        for status in FontStatus::ALL {
+            let count = statuses.values().filter(|&&s| s == status).count();
+            let selected = *filter == Some(status);
+            if count == 0 && !selected {
+                continue;
+            }
+            let re = ui
+                .selectable_label(selected, format!("{}: {count}", status.label()))
+                .on_hover_text("Show only fonts with this status");
+            if re.clicked() {
+                *filter = (!selected).then_some(status);
+            }
+        }
+    });
+}
@@ -0,0 +1,69 @@
+//! Contextual help shown next to the parts of the ui it explains
+
+use std::collections::HashMap;
+
+/// A concept explained by a help icon in the ui
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HelpTopic {
+    /// The list of fonts
+    Fonts,
+    /// Families, and how they differ from fonts
+    Families,
+    /// Fallback order within a family
+    FallbackOrder,
+    /// Font tweaks (scale and offsets)
+    Tweaks,
+}
+
+impl HelpTopic {
+    /// The help text shown unless the host overrides it
+    pub fn default_text(self) -> &'static str {
+        match self {
+            Self::Fonts => {
+                "Fonts are the font files available to the application. \
+                 A font is only used if a family lists it."
+            }
+            Self::Families => {
+                "A family is a list of fonts that text is drawn with. \
+                 Proportional is used for most text, Monospace for code. \
+                 Applications can also define named families of their own."
+            }
+            Self::FallbackOrder => {
+                "Each character is drawn with the first font in the family that has it. \
+                 Later fonts are fallbacks, e.g. for emoji or other scripts."
+            }
+            Self::Tweaks => {
+                "Tweaks adjust the size and vertical position of a font's glyphs, \
+                 so fallback fonts line up with the primary font of the family."
+            }
+        }
+    }
+}
+
+/// Help texts, with the host's overrides
+#[derive(Default)]
+pub(crate) struct HelpTexts {
+    overrides: HashMap<HelpTopic, String>,
+}
+
+impl HelpTexts {
+    pub(crate) fn set(&mut self, topic: HelpTopic, text: String) {
+        self.overrides.insert(topic, text);
+    }
+    fn get(&self, topic: HelpTopic) -> &str {
+        self.overrides
+            .get(&topic)
+            .map_or(topic.default_text(), String::as_str)
+    }
+    /// Show a help icon explaining `topic`. Nothing is shown if the help text is empty.
+    pub(crate) fn icon(&self, ui: &mut egui::Ui, topic: HelpTopic) {
+        let text = self.get(topic);
+        if text.is_empty() {
+            return;
+        }
+        let re = ui
+            .add(egui::Label::new(egui::RichText::new("❓").weak()).sense(egui::Sense::hover()))
+            .on_hover_text(text);
+        re.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, text));
+    }
+}
@@ -0,0 +1,299 @@
+//! Metadata read from the tables of font files
+
+use egui::{FontData, FontDefinitions};
+
+/// Read the name table entry `name_id` (see [`ttf_parser::name_id`]), preferring English
+pub(crate) fn name_string(names: ttf_parser::name::Names, name_id: u16) -> Option<String> {
+    let decode = |name: ttf_parser::name::Name| {
+        name.to_string().or_else(|| {
+            // Macintosh Roman, decoding the ASCII subset is good enough for metadata
+            (name.platform_id == ttf_parser::PlatformId::Macintosh && name.encoding_id == 0)
+                .then(|| name.name.iter().map(|&b| char::from(b)).collect())
+        })
+    };
+    let names = || names.into_iter().filter(|n| n.name_id == name_id);
+    names()
+        .filter(|n| n.language_id == 0x0409)
+        .find_map(decode)
+        .or_else(|| names().find_map(decode))
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty())
+}
+
+/// Descriptive metadata a font declares in its `name` table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontInfo {
+    /// Family name, e.g. `Noto Sans`
+    pub family: Option<String>,
+    /// Style within the family, e.g. `Bold Italic`
+    pub style: Option<String>,
+    /// Full name, usually the family and style combined
+    pub full_name: Option<String>,
+    /// Version string, e.g. `Version 2.013`
+    pub version: Option<String>,
+    /// Copyright notice
+    pub copyright: Option<String>,
+}
+
+impl FontInfo {
+    /// Read the metadata of face `index` in the font file `data`. Returns `None` if it can't
+    /// be parsed.
+    pub fn from_bytes(data: &[u8], index: u32) -> Option<Self> {
+        use ttf_parser::name_id;
+        crate::format::check_font_data(data, index).ok()?;
+        let face = ttf_parser::Face::parse(data, index).ok()?;
+        let name = |preferred, fallback| {
+            name_string(face.names(), preferred).or_else(|| name_string(face.names(), fallback))
+        };
+        Some(Self {
+            family: name(name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY),
+            style: name(name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY),
+            full_name: name_string(face.names(), name_id::FULL_NAME),
+            version: name_string(face.names(), name_id::VERSION),
+            copyright: name_string(face.names(), name_id::COPYRIGHT_NOTICE),
+        })
+    }
+    /// Read the metadata of `font`. Returns `None` if it can't be parsed.
+    pub fn of(font: &FontData) -> Option<Self> {
+        Self::from_bytes(&font.font, font.index)
+    }
+    /// The family name followed by the style, unless it's the regular style, e.g. `Noto Sans Bold`
+    ///
+    /// Suitable as the identifier of the font.
+    pub fn display_name(&self) -> Option<String> {
+        let family = self.family.as_deref()?;
+        Some(match self.style.as_deref() {
+            Some(style) if !style.eq_ignore_ascii_case("regular") => format!("{family} {style}"),
+            _ => family.to_owned(),
+        })
+    }
+    /// Show the metadata as rows of a 2 column grid
+    pub(crate) fn grid_rows_ui(&self, ui: &mut egui::Ui) {
+        for (label, value) in [
+            ("Family", &self.family),
+            ("Style", &self.style),
+            ("Version", &self.version),
+            ("Copyright", &self.copyright),
+        ] {
+            if let Some(value) = value {
+                ui.label(label);
+                ui.add(egui::Label::new(value.as_str()).wrap());
+                ui.end_row();
+            }
+        }
+    }
+}
+
+/// Weight and width classes a font declares in its `OS/2` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontClass {
+    /// Weight class, from 100 (thin) to 900 (black)
+    pub weight: u16,
+    /// Width class, from 1 (ultra condensed) to 9 (ultra expanded)
+    pub width: u16,
+}
+
+impl FontClass {
+    /// Read the classes of `font`. Returns `None` if it can't be parsed.
+    pub fn of(font: &FontData) -> Option<Self> {
+        let face = ttf_parser::Face::parse(&font.font, font.index).ok()?;
+        Some(Self {
+            weight: face.weight().to_number(),
+            width: face.width().to_number(),
+        })
+    }
+    /// Common name of the weight class, rounded to the nearest hundred
+    pub fn weight_label(self) -> &'static str {
+        match (self.weight + 50) / 100 {
+            0 | 1 => "Thin",
+            2 => "ExtraLight",
+            3 => "Light",
+            4 => "Regular",
+            5 => "Medium",
+            6 => "SemiBold",
+            7 => "Bold",
+            8 => "ExtraBold",
+            _ => "Black",
+        }
+    }
+    /// Common name of the width class
+    pub fn width_label(self) -> &'static str {
+        match self.width {
+            0 | 1 => "UltraCondensed",
+            2 => "ExtraCondensed",
+            3 => "Condensed",
+            4 => "SemiCondensed",
+            5 => "Normal",
+            6 => "SemiExpanded",
+            7 => "Expanded",
+            8 => "ExtraExpanded",
+            _ => "UltraExpanded",
+        }
+    }
+}
+
+/// Show the weight and width badges of `font`
+pub(crate) fn class_badges_ui(ui: &mut egui::Ui, font: &FontData) {
+    let Some(class) = FontClass::of(font) else {
+        return;
+    };
+    for (label, hover) in [
+        (
+            class.weight_label(),
+            format!("Weight class {}", class.weight),
+        ),
+        (class.width_label(), format!("Width class {}", class.width)),
+    ] {
+        let text = egui::RichText::new(label).small().weak();
+        ui.add(egui::Label::new(text)).on_hover_text(hover);
+    }
+}
+
+/// Embedding permissions a font declares in its `OS/2` table (`fsType`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingPermissions {
+    /// The font may be embedded and permanently installed
+    Installable,
+    /// The font must not be embedded without the legal owner's permission
+    Restricted,
+    /// The font may be embedded for previewing and printing only
+    PreviewAndPrint,
+    /// The font may be embedded in documents that can be edited
+    Editable,
+}
+
+impl EmbeddingPermissions {
+    /// Human readable description
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Installable => "Installable embedding",
+            Self::Restricted => "Restricted license embedding",
+            Self::PreviewAndPrint => "Preview & print embedding",
+            Self::Editable => "Editable embedding",
+        }
+    }
+}
+
+/// License related metadata of a configured font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontLicense {
+    /// Identifier of the font in the font definitions
+    pub name: String,
+    /// Family name stored in the font
+    pub family_name: Option<String>,
+    /// Copyright notice
+    pub copyright: Option<String>,
+    /// License description
+    pub license: Option<String>,
+    /// URL of the license
+    pub license_url: Option<String>,
+    /// Embedding permissions (`fsType`)
+    pub embedding: Option<EmbeddingPermissions>,
+    /// Whether the font allows being subset
+    pub subsetting_allowed: bool,
+}
+
+impl FontLicense {
+    /// Read the license metadata of `font`. Returns `None` if it can't be parsed.
+    pub fn of(name: &str, font: &FontData) -> Option<Self> {
+        use ttf_parser::{name_id, Permissions};
+        crate::format::check_font_data(&font.font, font.index).ok()?;
+        let face = ttf_parser::Face::parse(&font.font, font.index).ok()?;
+        Some(Self {
+            name: name.to_owned(),
+            family_name: name_string(face.names(), name_id::FAMILY),
+            copyright: name_string(face.names(), name_id::COPYRIGHT_NOTICE),
+            license: name_string(face.names(), name_id::LICENSE),
+            license_url: name_string(face.names(), name_id::LICENSE_URL),
+            embedding: face.permissions().map(|p| match p {
+                Permissions::Installable => EmbeddingPermissions::Installable,
+                Permissions::Restricted => EmbeddingPermissions::Restricted,
+                Permissions::PreviewAndPrint => EmbeddingPermissions::PreviewAndPrint,
+                Permissions::Editable => EmbeddingPermissions::Editable,
+            }),
+            subsetting_allowed: face.is_subsetting_allowed(),
+        })
+    }
+}
+
+/// Collect the license metadata of all fonts in `font_defs` that can be parsed
+pub fn license_summary(font_defs: &FontDefinitions) -> Vec<FontLicense> {
+    font_defs
+        .font_data
+        .iter()
+        .filter_map(|(name, font)| FontLicense::of(name, font))
+        .collect()
+}
+
+/// Render a license summary as Markdown, for including in third party asset documentation
+pub fn license_summary_markdown(licenses: &[FontLicense]) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::from("# Fonts\n");
+    let unknown = "unknown";
+    for lic in licenses {
+        let _ = write!(
+            out,
+            "\n## {}\n\n- Family: {}\n- Copyright: {}\n- License: {}\n",
+            lic.name,
+            lic.family_name.as_deref().unwrap_or(unknown),
+            lic.copyright.as_deref().unwrap_or(unknown),
+            lic.license.as_deref().unwrap_or(unknown),
+        );
+        if let Some(url) = &lic.license_url {
+            let _ = writeln!(out, "- License URL: {url}");
+        }
+        let _ = writeln!(
+            out,
+            "- Embedding: {}{}",
+            lic.embedding.map_or(unknown, EmbeddingPermissions::label),
+            if lic.subsetting_allowed {
+                ""
+            } else {
+                ", no subsetting"
+            }
+        );
+    }
+    out
+}
+
+/// Show the license summary, with a button to copy it
+pub(crate) fn license_ui(ui: &mut egui::Ui, font_defs: &FontDefinitions) {
+    let licenses = license_summary(font_defs);
+    for lic in &licenses {
+        ui.horizontal_wrapped(|ui| {
+            ui.strong(&lic.name);
+            ui.label(lic.license.as_deref().unwrap_or("No license information"))
+                .on_hover_text(lic.copyright.as_deref().unwrap_or("No copyright notice"));
+            if let Some(embedding) = lic.embedding {
+                ui.weak(embedding.label());
+            }
+        });
+    }
+    if ui
+        .button("📋 Copy summary")
+        .on_hover_text("Copy the license summary as Markdown")
+        .clicked()
+    {
+        ui.ctx().copy_text(license_summary_markdown(&licenses));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::FontInfo, egui::FontDefinitions};
+
+    #[test]
+    fn reads_names() {
+        let defs = FontDefinitions::default();
+        let info = FontInfo::of(&defs.font_data["Hack"]).unwrap();
+        assert_eq!(info.family.as_deref(), Some("Hack"));
+    }
+
+    #[test]
+    fn rejects_malformed_collection() {
+        // A collection header claiming far more faces than the data holds
+        let data = b"ttcf\0\x01\0\0\xff\xff\xff\xff\0\0\0\x10";
+        assert_eq!(FontInfo::from_bytes(data, 0), None);
+        assert_eq!(FontInfo::from_bytes(data, 3), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Recording the changes the user makes to font definitions
+
+use {
+    egui::{FontDefinitions, FontFamily, FontTweak},
+    std::{sync::Arc, time::SystemTime},
+};
+
+/// A single change to font definitions
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Change {
+    /// A font was added
+    FontAdded {
+        /// Identifier of the font
+        name: String,
+    },
+    /// A font was removed
+    FontRemoved {
+        /// Identifier of the font
+        name: String,
+    },
+    /// The data of a font was replaced, e.g. by reloading its file
+    FontDataReplaced {
+        /// Identifier of the font
+        name: String,
+    },
+    /// The face index or tweak of a font changed
+    FontPropertiesChanged {
+        /// Identifier of the font
+        name: String,
+        /// The new face index
+        index: u32,
+        /// The new tweak
+        tweak: FontTweak,
+    },
+    /// A family was added
+    FamilyAdded {
+        /// The added family
+        family: FontFamily,
+        /// Its fonts
+        fonts: Vec<String>,
+    },
+    /// A family was removed
+    FamilyRemoved {
+        /// The removed family
+        family: FontFamily,
+    },
+    /// The fonts of a family were reordered, without adding or removing any
+    FamilyReordered {
+        /// The family
+        family: FontFamily,
+        /// Its fonts in the new order
+        fonts: Vec<String>,
+    },
+    /// Fonts were added to or removed from a family
+    FamilyChanged {
+        /// The family
+        family: FontFamily,
+        /// Its new fonts
+        fonts: Vec<String>,
+    },
+}
+
+/// A [`Change`] along with when it happened
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JournalEntry {
+    /// When the change happened. Not available on the web.
+    pub time: Option<SystemTime>,
+    /// What changed
+    pub change: Change,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn system_now() -> Option<SystemTime> {
+    Some(SystemTime::now())
+}
+
+// `SystemTime::now` panics on the web
+#[cfg(target_arch = "wasm32")]
+fn system_now() -> Option<SystemTime> {
+    None
+}
+
+/// The changes that turn `before` into `after`
+pub fn diff_font_defs(before: &FontDefinitions, after: &FontDefinitions) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (name, old) in &before.font_data {
+        match after.font_data.get(name) {
+            None => changes.push(Change::FontRemoved { name: name.clone() }),
+            Some(new) if Arc::ptr_eq(old, new) => {}
+            Some(new) if old.font != new.font => {
+                changes.push(Change::FontDataReplaced { name: name.clone() });
+            }
+            Some(new) => {
+                if old.index != new.index || old.tweak != new.tweak {
+                    changes.push(Change::FontPropertiesChanged {
+                        name: name.clone(),
+                        index: new.index,
+                        tweak: new.tweak,
+                    });
+                }
+            }
+        }
+    }
+    for name in after.font_data.keys() {
+        if !before.font_data.contains_key(name) {
+            changes.push(Change::FontAdded { name: name.clone() });
+        }
+    }
+    for (family, old) in &before.families {
+        let Some(new) = after.families.get(family) else {
+            changes.push(Change::FamilyRemoved {
+                family: family.clone(),
+            });
+            continue;
+        };
+        if old == new {
+            continue;
+        }
+        let mut old_sorted = old.clone();
+        let mut new_sorted = new.clone();
+        old_sorted.sort();
+        new_sorted.sort();
+        let (family, fonts) = (family.clone(), new.clone());
+        changes.push(if old_sorted == new_sorted {
+            Change::FamilyReordered { family, fonts }
+        } else {
+            Change::FamilyChanged { family, fonts }
+        });
+    }
+    for (family, fonts) in &after.families {
+        if !before.families.contains_key(family) {
+            changes.push(Change::FamilyAdded {
+                family: family.clone(),
+                fonts: fonts.clone(),
+            });
+        }
+    }
+    changes
+}
+
+/// Append `changes` to `journal`
+pub(crate) fn record(journal: &mut Vec<JournalEntry>, changes: &[Change]) {
+    if changes.is_empty() {
+        return;
+    }
+    let time = system_now();
+    journal.extend(changes.iter().map(|change| JournalEntry {
+        time,
+        change: change.clone(),
+    }));
+}
+
+impl Change {
+    /// The event telling the host about this change
+    pub(crate) fn to_msg(&self) -> crate::FontDefsUiMsg {
+        use crate::FontDefsUiMsg as Msg;
+        match self {
+            Self::FontAdded { name } => Msg::FontAdded { name: name.clone() },
+            Self::FontRemoved { name } => Msg::FontRemoved { name: name.clone() },
+            Self::FontDataReplaced { name } | Self::FontPropertiesChanged { name, .. } => {
+                Msg::FontChanged { name: name.clone() }
+            }
+            Self::FamilyAdded { family, .. }
+            | Self::FamilyRemoved { family }
+            | Self::FamilyReordered { family, .. }
+            | Self::FamilyChanged { family, .. } => Msg::FamilyChanged {
+                family: family.clone(),
+            },
+        }
+    }
+}
@@ -6,194 +6,697 @@
 //! with [`FontCfgUi::show`].
 //!
 //! The ui will automatically apply the changes to the egui context when the user clicks the `Apply`
-//! button.
+//! button. To apply the configuration from your own code (e.g. at startup), use [`apply`], or
+//! [`FontCfgUi::request_apply`].
 //!
-//! This library doesn't handle serialization, but it's fairly easy to do it yourself:
+//! To persist the configuration, the quickest way is enabling the `serde` feature and
+//! serializing a [`FontConfigSnapshot`] with any format serde supports (see below). To persist
+//! egui's own types instead:
 //!
-//! - Make sure `egui`'s `serialize` feature is enabled
+//! - Enable `egui`'s `serde` feature, which this crate's `serde` feature also does
 //! - Serialize the [`egui::FontFamily`] of your font data
 //! - Serialize [`CustomFontPaths`], and use [`load_custom_fonts`] to load the custom fonts
 //!   that the user added.
+//! - After loading a persisted family list, use [`remap_renamed_builtin_fonts`] to fix up
+//!   references to built-in egui fonts that were renamed in newer egui versions.
+//! - Use [`validate`] to find problems in a loaded configuration, like families referring to
+//!   fonts that no longer exist.
+//! - To notice font files that changed since the configuration was saved, also serialize
+//!   [`custom_font_hashes`], and load with [`load_custom_fonts_verified`] instead.
+//! - If a font could come from several places (a custom path, a bundled font or an installed
+//!   font), load with [`load_fonts_by_priority`] to choose which is tried first, and pass the
+//!   result to [`FontCfgUi::font_resolutions`] to show the user where each font came from.
+//! - On the web, where fonts can't be loaded back from their paths, persist the fonts' data
+//!   instead with [`custom_font_bytes`] and [`encode_custom_font_bytes`], and restore it with
+//!   [`decode_custom_font_bytes`] and [`load_custom_font_bytes`].
+//!
+//! Alternatively, [`FontConfigSnapshot`] captures the whole configuration (custom font paths,
+//! families and tweaks) in a simple text format of its own, and restores it with
+//! [`FontConfigSnapshot::apply_to_defs`]. [`FontConfigDiff`] stores only the changes the user
+//! made to the fonts your application ships with, in the same kind of format. With the
+//! `serde` feature, both can also be persisted in any format serde supports.
+//! To keep the fonts the user added apart from your own, see [`FontCfgUi::user_namespace`].
+//! Both are built on [`FontEntry`] and [`FamilySpec`], the crate's own description of fonts and
+//! families, which you can also use for a format of your own. Unlike egui's types, they don't
+//! change between egui releases.
+//!
+//! To credit the fonts in use (e.g. in a third party notices file), see [`license_summary`].
+//!
+//! To warn the user when another instance of your application is editing the same saved
+//! configuration, see `FontCfgUi::config_lock`.
+//!
+//! To find out why some text shows up as boxes, see [`diagnose_missing_glyphs`]. The dialog
+//! offers the same as a tool that also suggests fonts covering the missing characters.
+//!
+//! To let end users of a shipped application pick fonts without access to everything else,
+//! offer them [presets](FontCfgUi::presets) with [`FontCfgUi::show_kiosk`].
+//!
+//! To edit a configuration from code, with the same checks the dialog makes (e.g. in a
+//! settings sync or a migration), keep it as a [`FontConfig`].
+//!
+//! To show errors and other notifications globally (e.g. as toasts), see
+//! [`FontCfgUi::notifications`].
+//!
+//! ## Optional features
+//!
+//! - `fs` (default): Provide `FsFontSource`, the [`FontSource`] loading fonts from the file
+//!   system, used by [`FontCfgUi`] and the functions taking an optional source unless another
+//!   source is set. Also provides `load_custom_fonts` and `ConfigLock`. Without it, nothing
+//!   touches the file system.
+//! - `system-fonts`: Offer the fonts installed on the system in the add new font form, and
+//!   enumerate them with `system_fonts`. Implies `fs`.
+//! - `font-conversion`: Convert fonts egui can't load into ones it can when adding them, where
+//!   possible (currently Mac OS font suitcases), see `convert_font`.
+//! - `bitmap-fonts`: Accept BDF and PCF bitmap fonts, converting them to TrueType fonts that
+//!   render like the bitmaps.
+//! - `compression`: Compress the data of removed fonts while they're in the trash, and export
+//!   custom font data compressed with `encode_custom_font_bytes_compressed`.
+//! - `serde`: Implement `Serialize` and `Deserialize` for [`FontConfigSnapshot`],
+//!   [`FontConfigDiff`] and the types they consist of, as well as [`JournalEntry`]. Enables
+//!   `egui`'s `serde` feature.
+//!
+//! ## Keyboard usage
+//!
+//! Tab and the arrow keys move between controls. In the add new font form, Enter adds the font
+//! (if the form is filled in correctly) and Escape closes the form, discarding its contents.
+//! Delete removes the focused font or family entry. In [`FontCfgWindow`], Ctrl+Z undoes the last
+//! edit and Ctrl+Shift+Z or Ctrl+Y redoes it (Cmd on macOS), unless a text field has focus.
+//!
+//! Every control has an accessible name that says what it acts on, like `Remove Hack from
+//! Proportional family`, so assistive technology and ui tests (e.g. with `egui_kittest`, by
+//! label) can find them. Labels with an icon are named without it, e.g. `Apply`.
 #![warn(missing_docs)]
 
+mod adopt;
+#[cfg(feature = "bitmap-fonts")]
+mod bitmap;
+mod calibrate;
+mod catalog;
+#[cfg(feature = "compression")]
+mod compress;
+mod config_diff;
+mod confirm;
+mod coverage;
+mod details;
+mod diagnose;
+mod exclusions;
+mod format;
+mod glyph_diff;
+mod hash;
+mod health;
+mod help;
+mod info;
+mod journal;
+mod live_preview;
+mod loading;
+#[cfg(feature = "fs")]
+mod lock;
+mod metrics;
+mod model;
+mod monitors;
+mod namespace;
+mod notify;
+mod ops;
+mod picker;
+mod presets;
+mod preview;
+mod rename;
+mod resolve;
+mod sandbox;
+mod snapshot;
+mod source;
+mod stats;
+#[cfg(feature = "system-fonts")]
+mod system_fonts;
+mod templates;
+mod textfmt;
+mod trash;
+mod tweaks;
+mod ui;
+mod undo;
+mod upload;
+mod usage;
+mod validate;
+mod variants;
+mod zoom;
+
+pub use adopt::import_fonts;
+pub use catalog::{load_bundled_fonts, BundledFont};
+#[cfg(feature = "compression")]
+pub use compress::{compress_font_data, decompress_font_data};
+pub use config_diff::FontConfigDiff;
+pub use confirm::ConfirmationPolicy;
+pub use diagnose::{diagnose_missing_glyphs, MissingGlyph};
+pub use exclusions::{
+    apply_fallback_exclusions, exclude_codepoints, format_codepoint_ranges, parse_codepoint_ranges,
+    FallbackExclusions,
+};
+#[cfg(any(feature = "font-conversion", feature = "bitmap-fonts"))]
+pub use format::convert_font;
+pub use format::{detect_font_format, FontFormat};
+pub use hash::{find_duplicate_font, ContentHash};
+pub use health::{font_statuses, FontStatus};
+pub use help::HelpTopic;
+pub use info::{
+    license_summary, license_summary_markdown, EmbeddingPermissions, FontClass, FontInfo,
+    FontLicense,
+};
+pub use journal::{diff_font_defs, Change, JournalEntry};
+pub use loading::{AsyncFontReader, FontLoadQueue, FontReader, ReadDone, Spawner, Task};
+#[cfg(feature = "fs")]
+pub use lock::ConfigLock;
+pub use metrics::{baseline_report, FontMetrics, MetricsMismatch};
+pub use model::{
+    FamilyId, FamilySpec, FontConfig, FontConfigError, FontConfigMut, FontEntry, Tweaks,
+};
+pub use monitors::Monitor;
+pub use namespace::{
+    merge_namespace, namespace_of, namespaced, split_namespace, NAMESPACE_SEPARATOR,
+};
+pub use notify::Notification;
+pub use ops::{FontConfigOps, Op, OpError};
+pub use picker::FontFilePicker;
+pub use presets::{
+    preset_conflicts, resolve_preset_conflicts, ConflictResolution, FontPreset, FontPresets,
+    PresetConflict, PresetHotkeys,
+};
+pub use rename::{rename_fonts, RenameError};
+pub use resolve::{load_fonts_by_priority, FontOrigin, FontResolution};
+pub use snapshot::FontConfigSnapshot;
+#[cfg(feature = "fs")]
+pub use source::FsFontSource;
+pub use source::{
+    load_custom_fonts_from, load_custom_fonts_verified_from, FontLoadError, FontSource,
+};
+pub use stats::{dry_run_apply, ApplyReport, AtlasStats};
+#[cfg(feature = "system-fonts")]
+pub use system_fonts::{system_font_dirs, system_fonts, SystemFont};
+pub use templates::{new_family, rename_family, FamilyRenameError, FamilyTemplate};
+pub use textfmt::ParseError;
+pub use tweaks::{adjust_family_tweaks, TweakAdjustment};
+pub use ui::{AddFormPlacement, FontCfgUi, FontCfgWindow, FontDefsUiMsg, LayoutMode};
+#[cfg(feature = "compression")]
+pub use upload::encode_custom_font_bytes_compressed;
+pub use upload::{
+    custom_font_bytes, decode_custom_font_bytes, encode_custom_font_bytes, load_custom_font_bytes,
+    CustomFontBytes, FontUploader, UploadedFont,
+};
+pub use usage::{
+    font_usage, report_family_use, report_text_style_use, reset_font_usage, FontUsage,
+};
+pub use validate::{validate, FontCfgIssue, IssueSeverity, ValidationIssue, Validator};
+pub use variants::{
+    bold_family_for, italic_family_for, register_style_variants, variant_family,
+    variant_family_name, variant_font_name, FontStyle, FontVariants, StyleVariantError,
+};
 use {
-    egui::{ahash::HashMap, FontData, FontDefinitions},
-    std::{collections::BTreeMap, sync::Arc},
+    egui::{ahash::HashMap, FontData, FontDefinitions, FontFamily},
+    std::{
+        collections::{BTreeMap, BTreeSet, HashSet},
+        sync::{Arc, Weak},
+        time::Duration,
+    },
 };
 
-/// The state of the font configuration ui
-#[derive(Default)]
-pub struct FontCfgUi {
-    name_buf: String,
-    path_buf: String,
-    err_msg: String,
-    add_new: bool,
+/// Where fonts added with the add new font form go in the families picked there
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// Last, as a fallback for characters the other fonts don't cover
+    #[default]
+    Back,
+    /// First, so the font is used wherever it covers a character
+    Front,
 }
 
 /// Keeps track of custom font paths added by the user
 ///
-/// The key is the identifier of the font, the value is the path to the font.
+/// The key is the identifier of the font, the value is the path to the font. For faces of
+/// font collections other than the first, the face index is appended, see [`custom_font_path`].
 pub type CustomFontPaths = HashMap<String, String>;
 
+/// The path to record in [`CustomFontPaths`] for face `index` of the font file at `path`
+///
+/// This is `path` itself for the first face, and `path#index` for the others.
+pub fn custom_font_path(path: &str, index: u32) -> String {
+    match index {
+        0 => path.to_owned(),
+        _ => format!("{path}#{index}"),
+    }
+}
+
+/// Split a path recorded in [`CustomFontPaths`] into the path of the font file and the face
+/// index, see [`custom_font_path`]
+pub fn split_custom_font_path(path: &str) -> (&str, u32) {
+    path.rsplit_once('#')
+        .filter(|(_, index)| index.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|(file, index)| Some((file, index.parse().ok()?)))
+        .unwrap_or((path, 0))
+}
+
+/// Reason a font identifier was rejected by [`sanitize_font_name`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontNameError {
+    /// The identifier is empty, or only consists of whitespace
+    Empty,
+    /// The identifier contains a control character
+    ControlChar(char),
+}
+
+impl std::fmt::Display for FontNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Font identifier can't be empty"),
+            Self::ControlChar(c) => {
+                write!(f, "Font identifier can't contain control character {c:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FontNameError {}
+
+/// Trim a font identifier, and reject it if it's empty or contains control characters
+///
+/// `normalize` can be used to apply Unicode normalization (e.g. NFC) to the trimmed identifier,
+/// so identifiers that only differ in normalization form don't end up as separate keys.
+pub fn sanitize_font_name(
+    name: &str,
+    normalize: Option<fn(&str) -> String>,
+) -> Result<String, FontNameError> {
+    let name = name.trim();
+    let name = match normalize {
+        Some(normalize) => normalize(name),
+        None => name.to_owned(),
+    };
+    if name.is_empty() {
+        return Err(FontNameError::Empty);
+    }
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        return Err(FontNameError::ControlChar(c));
+    }
+    Ok(name)
+}
+
+/// Read the custom font recorded as `path` (see [`split_custom_font_path`]), failing with
+/// [`std::io::ErrorKind::InvalidData`] if it isn't a font egui can load
+///
+/// Fonts of formats egui can't load are converted, like when they were added.
+#[cfg(feature = "fs")]
+fn read_custom_font(path: &str) -> std::io::Result<FontData> {
+    let (file, index) = split_custom_font_path(path);
+    let data = std::fs::read(file)?;
+    let data = format::ensure_loadable(data, index).map_err(|e| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{path}: {e}"))
+    })?;
+    Ok(FontData {
+        index,
+        ..FontData::from_owned(data)
+    })
+}
+
 /// Helper function to load custom fonts from a [`CustomFontPaths`] to a [`FontData`].
+///
+/// The fonts are read from the file system. See [`load_custom_fonts_from`] for other sources.
+/// Fonts added from the bundled fonts are skipped, see [`load_bundled_fonts`].
+/// Files that aren't valid fonts are rejected with [`std::io::ErrorKind::InvalidData`], rather
+/// than making egui fail later.
+#[cfg(feature = "fs")]
 pub fn load_custom_fonts(
     custom: &CustomFontPaths,
     font_data: &mut BTreeMap<String, Arc<FontData>>,
 ) -> std::io::Result<()> {
-    for (k, v) in custom {
-        let data = std::fs::read(v)?;
-        font_data.insert(k.to_owned(), Arc::new(FontData::from_owned(data)));
+    for (k, v) in custom.iter().filter(|(_, v)| !catalog::is_bundled(v)) {
+        font_data.insert(k.to_owned(), Arc::new(read_custom_font(v)?));
     }
     Ok(())
 }
 
-/// Message returned by [`FontCfgUi::show`]
-pub enum FontDefsUiMsg {
-    /// No event happened
-    None,
-    /// A save was requested
-    SaveRequest,
-}
-
-impl FontCfgUi {
-    /// Show the font definitions ui
-    ///
-    /// # Arguments
-    ///
-    /// - `ui`: The [`egui::Ui`] to show the ui on
-    /// - `font_defs`: The [`egui::FontDefinitions`] to edit
-    /// - `custom`: An optional [`CustomFontPaths`] to save custom font paths to
-    pub fn show(
-        &mut self,
-        ui: &mut egui::Ui,
-        font_defs: &mut FontDefinitions,
-        mut custom: Option<&mut CustomFontPaths>,
-    ) -> FontDefsUiMsg {
-        let mut msg = FontDefsUiMsg::None;
-        ui.set_max_width(300.0);
-        ui.horizontal(|ui| {
-            ui.heading("Fonts");
-            if ui.button("+").clicked() {
-                self.add_new = true;
-                self.err_msg.clear();
-            }
-        });
-        if self.add_new {
-            ui.add(
-                egui::TextEdit::singleline(&mut self.name_buf).hint_text("Identifier for new font"),
-            );
-            ui.add(egui::TextEdit::singleline(&mut self.path_buf).hint_text("Path to new font"));
-            if ui.button("Add new font").clicked() {
-                let font_data = match std::fs::read(&self.path_buf) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        self.err_msg = e.to_string();
-                        return FontDefsUiMsg::None;
-                    }
-                };
-                let data = Arc::new(egui::FontData::from_owned(font_data));
-                font_defs.font_data.insert(self.name_buf.clone(), data);
-                if let Some(custom) = &mut custom {
-                    custom.insert(self.name_buf.clone(), self.path_buf.clone());
-                }
-                self.name_buf.clear();
-                self.path_buf.clear();
-                self.err_msg.clear();
-                self.add_new = false;
+/// Content hashes of custom fonts, keyed by font identifier like [`CustomFontPaths`]
+pub type CustomFontHashes = HashMap<String, ContentHash>;
+
+/// Hash the loaded data of the custom fonts, for saving along with [`CustomFontPaths`]
+pub fn custom_font_hashes(
+    custom: &CustomFontPaths,
+    font_defs: &FontDefinitions,
+) -> CustomFontHashes {
+    custom
+        .keys()
+        .filter_map(|name| {
+            let font = font_defs.font_data.get(name)?;
+            Some((name.clone(), ContentHash::of(&font.font)))
+        })
+        .collect()
+}
+
+/// A custom font file whose contents changed since its hash was recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashMismatch {
+    /// Identifier of the font
+    pub name: String,
+    /// Path of the font file
+    pub path: String,
+    /// The recorded hash
+    pub expected: ContentHash,
+    /// The hash of the file as it is now
+    pub actual: ContentHash,
+}
+
+/// Like [`load_custom_fonts`], but also verifies the fonts against the recorded `hashes`
+///
+/// Fonts whose contents changed are still loaded, but reported in the returned list.
+/// Fonts without a recorded hash aren't verified.
+/// See [`load_custom_fonts_verified_from`] for other sources than the file system.
+#[cfg(feature = "fs")]
+pub fn load_custom_fonts_verified(
+    custom: &CustomFontPaths,
+    hashes: &CustomFontHashes,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+) -> std::io::Result<Vec<HashMismatch>> {
+    load_verified(custom, hashes, font_data, read_custom_font)
+}
+
+/// Load the custom fonts with `load`, reporting the ones that don't match `hashes`
+fn load_verified<E>(
+    custom: &CustomFontPaths,
+    hashes: &CustomFontHashes,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+    load: impl Fn(&str) -> Result<FontData, E>,
+) -> Result<Vec<HashMismatch>, E> {
+    let mut mismatches = Vec::new();
+    for (k, v) in custom.iter().filter(|(_, v)| !catalog::is_bundled(v)) {
+        let font = load(v)?;
+        if let Some(expected) = hashes.get(k) {
+            let actual = ContentHash::of(&font.font);
+            if actual != *expected {
+                mismatches.push(HashMismatch {
+                    name: k.clone(),
+                    path: v.clone(),
+                    expected: *expected,
+                    actual,
+                });
             }
         }
-        if !self.err_msg.is_empty() {
-            ui.label(egui::RichText::new(&self.err_msg).color(egui::Color32::DARK_RED));
-        }
-        font_defs.font_data.retain(|name, _font| {
-            let mut retain = true;
-            ui.horizontal(|ui| {
-                ui.label(name);
-                if ui.button("-").clicked() {
-                    if let Some(custom) = &mut custom {
-                        custom.remove(name);
-                    }
-                    retain = false;
-                }
-            });
-            retain
-        });
-        ui.separator();
-        ui.heading("Families");
-        let mut push_new_to = None;
-        font_defs.families.retain(|family, fonts| {
-            let mut retain = true;
-            ui.horizontal(|ui| {
-                ui.label(family.to_string());
-                if ui.button("+").clicked() {
-                    push_new_to = Some(family.clone());
-                }
-                if ui.button("-").clicked() {
-                    retain = false;
-                }
-            });
-            fonts.retain_mut(|font_name| {
-                let mut retain = true;
-                ui.horizontal(|ui| {
-                    ui.text_edit_singleline(font_name);
-                    if ui.button("-").clicked() {
-                        retain = false;
-                    }
+        font_data.insert(k.to_owned(), Arc::new(font));
+    }
+    Ok(mismatches)
+}
+
+/// An external font file the configuration depends on, see [`required_font_files`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredFontFile {
+    /// Identifier of the font
+    pub name: String,
+    /// Path of the font file
+    pub path: String,
+    /// Hash of the loaded font data
+    pub hash: ContentHash,
+    /// Whether any family refers to the font
+    pub used: bool,
+}
+
+/// List the external files the configuration depends on, e.g. for bundling them in an installer
+///
+/// These are the custom fonts present in `font_defs`, except for bundled ones. The hashes are computed from the loaded
+/// font data, so they describe the files as they were when loaded.
+pub fn required_font_files(
+    custom: &CustomFontPaths,
+    font_defs: &FontDefinitions,
+) -> Vec<RequiredFontFile> {
+    let mut files: Vec<_> = custom
+        .iter()
+        .filter(|(_, path)| !catalog::is_bundled(path))
+        .filter_map(|(name, path)| {
+            let font = font_defs.font_data.get(name)?;
+            Some(RequiredFontFile {
+                name: name.clone(),
+                path: split_custom_font_path(path).0.to_owned(),
+                hash: ContentHash::of(&font.font),
+                used: font_defs.families.values().any(|f| f.contains(name)),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    files
+}
+
+/// Names egui's built-in fonts went by in earlier versions, paired with their current names
+const RENAMED_BUILTIN_FONTS: &[(&str, &str)] = &[("ProggyClean", "Hack")];
+
+/// A family entry that refers to a built-in egui font by a name it no longer has
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamedFontRef {
+    /// The family containing the stale entry
+    pub family: FontFamily,
+    /// The name stored in the family list
+    pub old_name: String,
+    /// The name the font goes by in the running egui version
+    pub new_name: &'static str,
+}
+
+/// Find family entries that refer to built-in egui fonts under an outdated name.
+///
+/// Entries that name a font actually present in `font_data` are never reported, so custom fonts
+/// that happen to share an old built-in name are left alone.
+pub fn find_renamed_builtin_fonts(font_defs: &FontDefinitions) -> Vec<RenamedFontRef> {
+    let mut refs = Vec::new();
+    for (family, fonts) in &font_defs.families {
+        for name in fonts {
+            if font_defs.font_data.contains_key(name) {
+                continue;
+            }
+            let Some(&(_, new_name)) = RENAMED_BUILTIN_FONTS.iter().find(|(old, _)| old == name)
+            else {
+                continue;
+            };
+            if font_defs.font_data.contains_key(new_name) {
+                refs.push(RenamedFontRef {
+                    family: family.clone(),
+                    old_name: name.clone(),
+                    new_name,
                 });
-                retain
-            });
-            retain
-        });
-        if let Some(key) = push_new_to {
-            font_defs
-                .families
-                .get_mut(&key)
-                .unwrap()
-                .push(String::new());
+            }
         }
-        ui.separator();
-        ui.horizontal(|ui| {
-            if ui
-                .button("✅ Apply")
-                .on_hover_text("Apply the font config to the current egui context")
-                .clicked()
-            {
-                ui.ctx().set_fonts(font_defs.clone());
+    }
+    refs
+}
+
+/// Replace family entries found by [`find_renamed_builtin_fonts`] with the current font names.
+///
+/// Returns the number of entries that were remapped.
+pub fn remap_renamed_builtin_fonts(font_defs: &mut FontDefinitions) -> usize {
+    let refs = find_renamed_builtin_fonts(font_defs);
+    for r in &refs {
+        if let Some(fonts) = font_defs.families.get_mut(&r.family) {
+            for name in fonts.iter_mut().filter(|name| **name == r.old_name) {
+                *name = r.new_name.to_owned();
             }
-            if ui
-                .button("💾 Save")
-                .on_hover_text("Save the custom font paths")
-                .clicked()
-            {
-                msg = FontDefsUiMsg::SaveRequest;
+            // The old and new name might both be present now, keep the first one only
+            let mut seen = HashSet::new();
+            fonts.retain(|name| seen.insert(name.clone()));
+        }
+    }
+    refs.len()
+}
+
+/// Add `name` to `families` at `position`, moving it there if it's already in them
+fn insert_into_families(
+    font_defs: &mut FontDefinitions,
+    name: &str,
+    families: &BTreeSet<FontFamily>,
+    position: InsertPosition,
+) {
+    for family in families {
+        let Some(fonts) = font_defs.families.get_mut(family) else {
+            continue;
+        };
+        fonts.retain(|f| f != name);
+        match position {
+            InsertPosition::Front => fonts.insert(0, name.to_owned()),
+            InsertPosition::Back => fonts.push(name.to_owned()),
+        }
+    }
+}
+
+/// Whether `path` has the extension of a font file egui can load
+pub(crate) fn is_font_file(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            let bitmap = cfg!(feature = "bitmap-fonts")
+                && ["bdf", "pcf"].iter().any(|e| ext.eq_ignore_ascii_case(e));
+            bitmap
+                || ["ttf", "otf", "ttc", "otc"]
+                    .iter()
+                    .any(|e| ext.eq_ignore_ascii_case(e))
+        })
+}
+
+/// Error returned when font definitions can't be applied to an egui context
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyError {
+    /// A family refers to a font that isn't present in the font data
+    MissingFont {
+        /// The family containing the entry
+        family: FontFamily,
+        /// The name of the missing font
+        name: String,
+    },
+    /// There is no preset with this name
+    UnknownPreset(String),
+}
+
+impl std::fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFont { family, name } => {
+                write!(f, "Family {family} refers to missing font {name:?}")
             }
-        });
-        msg
-    }
-}
-
-/// A convenience window wrapper around [`FontCfgUi`], to show it in a window
-#[derive(Default)]
-pub struct FontCfgWindow {
-    ui: FontCfgUi,
-    /// Whether the window should be open
-    pub open: bool,
-}
-
-impl FontCfgWindow {
-    /// Show the font defs ui window
-    pub fn show(
-        &mut self,
-        ctx: &egui::Context,
-        font_defs: &mut FontDefinitions,
-        custom: Option<&mut CustomFontPaths>,
-    ) -> FontDefsUiMsg {
-        let mut msg = FontDefsUiMsg::None;
-        egui::Window::new("Font definitions")
-            .open(&mut self.open)
-            .show(ctx, |ui| {
-                msg = self.ui.show(ui, font_defs, custom);
+            Self::UnknownPreset(name) => write!(f, "There is no preset called {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+/// Check that `font_defs` can be applied without making egui panic
+pub fn check_applicable(font_defs: &FontDefinitions) -> Result<(), ApplyError> {
+    for (family, fonts) in &font_defs.families {
+        if let Some(name) = fonts
+            .iter()
+            .find(|name| !font_defs.font_data.contains_key(*name))
+        {
+            return Err(ApplyError::MissingFont {
+                family: family.clone(),
+                name: name.clone(),
             });
-        msg
+        }
+    }
+    Ok(())
+}
+
+/// Identifies font definitions without holding a copy of the font data
+#[derive(Clone)]
+pub(crate) struct DefsFingerprint {
+    families: BTreeMap<FontFamily, Vec<String>>,
+    font_data: Vec<(String, Weak<FontData>)>,
+}
+
+impl DefsFingerprint {
+    pub(crate) fn new(font_defs: &FontDefinitions) -> Self {
+        Self {
+            families: font_defs.families.clone(),
+            font_data: font_defs
+                .font_data
+                .iter()
+                .map(|(k, v)| (k.clone(), Arc::downgrade(v)))
+                .collect(),
+        }
+    }
+    /// Cheap equality check that compares font data by pointer instead of by content
+    pub(crate) fn matches(&self, font_defs: &FontDefinitions) -> bool {
+        self.families == font_defs.families
+            && self.font_data.len() == font_defs.font_data.len()
+            && self
+                .font_data
+                .iter()
+                .zip(&font_defs.font_data)
+                .all(|((k1, v1), (k2, v2))| k1 == k2 && std::ptr::eq(v1.as_ptr(), Arc::as_ptr(v2)))
+    }
+}
+
+fn last_applied_id() -> egui::Id {
+    egui::Id::new("egui_fontcfg_last_applied")
+}
+
+fn unchanged_since_last_apply(ctx: &egui::Context, font_defs: &FontDefinitions) -> bool {
+    ctx.data(|d| {
+        d.get_temp::<DefsFingerprint>(last_applied_id())
+            .is_some_and(|last| last.matches(font_defs))
+    })
+}
+
+/// Fonts were handed to the egui context
+struct Pushed {
+    set_fonts_time: Option<Duration>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<std::time::Instant> {
+    Some(std::time::Instant::now())
+}
+
+// `Instant::now` panics on the web
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<std::time::Instant> {
+    None
+}
+
+fn push_fonts(ctx: &egui::Context, font_defs: FontDefinitions) -> Pushed {
+    let fingerprint = DefsFingerprint::new(&font_defs);
+    let start = now();
+    ctx.set_fonts(font_defs);
+    let set_fonts_time = start.map(|start| start.elapsed());
+    ctx.data_mut(|d| d.insert_temp(last_applied_id(), fingerprint));
+    Pushed { set_fonts_time }
+}
+
+fn apply_ref(
+    ctx: &egui::Context,
+    font_defs: &FontDefinitions,
+) -> Result<Option<Pushed>, ApplyError> {
+    check_applicable(font_defs)?;
+    if unchanged_since_last_apply(ctx, font_defs) {
+        return Ok(None);
+    }
+    Ok(Some(push_fonts(ctx, font_defs.clone())))
+}
+
+/// Apply `font_defs` to the egui context
+///
+/// The definitions are validated with [`check_applicable`] first. Returns `Ok(false)` if they
+/// are unchanged since the last apply, in which case the context is left alone.
+pub fn apply(ctx: &egui::Context, font_defs: &FontDefinitions) -> Result<bool, ApplyError> {
+    apply_ref(ctx, font_defs).map(|pushed| pushed.is_some())
+}
+
+/// Like [`apply`], but takes the definitions by value, so no copy of them has to be made
+pub fn apply_owned(ctx: &egui::Context, font_defs: FontDefinitions) -> Result<bool, ApplyError> {
+    check_applicable(&font_defs)?;
+    if unchanged_since_last_apply(ctx, &font_defs) {
+        return Ok(false);
+    }
+    push_fonts(ctx, font_defs);
+    Ok(true)
+}
+
+/// Create font data from `data`, keeping the face index and tweak of `old`
+fn with_new_bytes(old: &FontData, data: Vec<u8>) -> Arc<FontData> {
+    Arc::new(FontData {
+        index: old.index,
+        ..FontData::from_owned(data).tweak(old.tweak)
+    })
+}
+
+/// Load the custom font recorded as `path`, keeping the tweak of `old`
+fn reload_font_data(
+    source: Option<&dyn FontSource>,
+    old: &FontData,
+    path: &str,
+) -> Result<Arc<FontData>, FontLoadError> {
+    let font = source::load_custom_font(source, path)?;
+    Ok(Arc::new(font.tweak(old.tweak)))
+}
+
+/// Replace the bytes of the font called `name`, keeping its face index, tweak,
+/// and its place in all families
+///
+/// Returns `false` if there is no such font.
+pub fn replace_font_data(font_defs: &mut FontDefinitions, name: &str, data: Vec<u8>) -> bool {
+    match font_defs.font_data.get_mut(name) {
+        Some(font) => {
+            *font = with_new_bytes(font, data);
+            true
+        }
+        None => false,
     }
 }
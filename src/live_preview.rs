@@ -0,0 +1,115 @@
+//! A pane rendering a sample text with one family or font at several sizes
+
+use {
+    crate::preview::PreviewFonts,
+    egui::{FontDefinitions, FontFamily, FontId},
+};
+
+/// Sizes the sample text is rendered at
+const SIZES: [f32; 5] = [10.0, 14.0, 20.0, 28.0, 40.0];
+
+/// What the live preview renders the sample text with
+#[derive(Clone, PartialEq)]
+enum Target {
+    Family(FontFamily),
+    /// A single font, without falling back to any others
+    Font(String),
+}
+
+/// State of the live preview pane
+pub(crate) struct LivePreview {
+    text: String,
+    target: Target,
+    /// Renders [`Target::Font`], as that needs definitions of its own
+    font_preview: PreviewFonts,
+}
+
+impl Default for LivePreview {
+    fn default() -> Self {
+        Self {
+            text: "The quick brown fox\n1234567890".into(),
+            target: Target::Family(FontFamily::Proportional),
+            font_preview: PreviewFonts::default(),
+        }
+    }
+}
+
+impl LivePreview {
+    /// Show the pane. `pending` renders families, and must use `font_defs`.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        pending: &mut PreviewFonts,
+        font_defs: &FontDefinitions,
+    ) {
+        let label = match &self.target {
+            Target::Family(family) => format!("Family {family}"),
+            Target::Font(name) => format!("Font {name}"),
+        };
+        egui::ComboBox::from_label("Render with")
+            .selected_text(label)
+            .show_ui(ui, |ui| {
+                for family in font_defs.families.keys() {
+                    let target = Target::Family(family.clone());
+                    ui.selectable_value(&mut self.target, target, format!("Family {family}"));
+                }
+                ui.separator();
+                for name in font_defs.font_data.keys() {
+                    let target = Target::Font(name.clone());
+                    ui.selectable_value(&mut self.target, target, format!("Font {name}"));
+                }
+            });
+        let re = ui.add(
+            egui::TextEdit::multiline(&mut self.text)
+                .desired_rows(2)
+                .hint_text("Sample text"),
+        );
+        crate::ui::a11y_text_label(&re, "Live preview sample text", &self.text);
+        let (preview, family) = match &self.target {
+            Target::Family(family) => {
+                if !font_defs.families.contains_key(family) {
+                    ui.weak(format!("There is no {family} family"));
+                    return;
+                }
+                if let Err(e) = pending.prepare(ui.ctx(), font_defs) {
+                    ui.weak(e);
+                    return;
+                }
+                (pending, family.clone())
+            }
+            Target::Font(name) => {
+                let Some(font) = font_defs.font_data.get(name) else {
+                    ui.weak(format!("There is no font called {name}"));
+                    return;
+                };
+                let family = FontFamily::Name("live_preview".into());
+                let mut defs = FontDefinitions::empty();
+                defs.font_data.insert(name.clone(), font.clone());
+                defs.families.insert(family.clone(), vec![name.clone()]);
+                if let Err(e) = self.font_preview.prepare(ui.ctx(), &defs) {
+                    ui.weak(e);
+                    return;
+                }
+                (&mut self.font_preview, family)
+            }
+        };
+        let color = ui.visuals().text_color();
+        egui::Grid::new("live_preview")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for size in SIZES {
+                    ui.weak(format!("{size} pt"));
+                    if let Some(galley) =
+                        preview.layout(&self.text, FontId::new(size, family.clone()), color)
+                    {
+                        let (rect, _) = ui.allocate_exact_size(
+                            preview.display_size(ui.ctx(), &galley),
+                            egui::Sense::hover(),
+                        );
+                        preview.paint(ui.painter(), rect.min, &galley);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
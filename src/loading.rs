@@ -0,0 +1,271 @@
+//! Reading font files in the background, so large fonts don't stall the ui
+
+use {
+    crate::FontSource,
+    egui::FontFamily,
+    std::{
+        collections::BTreeSet,
+        sync::{mpsc, Arc},
+    },
+};
+
+/// Reads the file at a path, see [`FontLoadQueue::with_reader`]
+pub type FontReader = Arc<dyn Fn(&str) -> std::io::Result<Vec<u8>> + Send + Sync>;
+
+/// A blocking job, like reading a font file, to run in the background
+pub type Task = Box<dyn FnOnce() + Send>;
+
+/// Runs [`Task`]s in the background, see [`FontLoadQueue::spawner`]
+pub type Spawner = Arc<dyn Fn(Task) + Send + Sync>;
+
+/// Called with the contents of a file once it was read, see [`FontLoadQueue::with_async_reader`]
+pub type ReadDone = Box<dyn FnOnce(std::io::Result<Vec<u8>>) + Send>;
+
+/// Starts reading the file at a path, see [`FontLoadQueue::with_async_reader`]
+pub type AsyncFontReader = Arc<dyn Fn(&str, ReadDone) + Send + Sync>;
+
+/// A [`FontSource`] shared with background threads
+pub(crate) type SharedFontSource = Arc<dyn FontSource + Send + Sync>;
+
+/// How a [`FontLoadQueue`] reads files
+enum Reader {
+    /// The font source of the [`FontCfgUi`](crate::FontCfgUi), or the file system if it has none
+    Default(Option<SharedFontSource>),
+    /// Blocks until the file is read, so it runs on the spawner
+    Blocking(FontReader),
+    /// Reports back when it's done
+    Callback(AsyncFontReader),
+}
+
+/// Runs tasks on new threads
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn default_spawner() -> Spawner {
+    Arc::new(|task| {
+        std::thread::spawn(task);
+    })
+}
+
+/// Runs tasks right away, as the web has no threads to spawn
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn default_spawner() -> Spawner {
+    Arc::new(|task| task())
+}
+
+/// Run `job` on `spawner`, receiving its result on the returned channel
+#[cfg(feature = "system-fonts")]
+pub(crate) fn spawn<T: Send + 'static>(
+    spawner: &Spawner,
+    job: impl FnOnce() -> T + Send + 'static,
+) -> mpsc::Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    spawner(Box::new(move || {
+        // Nobody may be waiting for the result anymore, which is fine
+        let _ = sender.send(job());
+    }));
+    receiver
+}
+
+/// A font whose file is being read
+pub(crate) struct PendingFont {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    /// Families to add the font to once it's loaded
+    pub(crate) families: BTreeSet<FontFamily>,
+    pub(crate) position: crate::InsertPosition,
+    /// Replace the font of the same name, see [`crate::FontConfigMut::replace_font`]
+    pub(crate) replace: bool,
+}
+
+/// A font whose file was read, successfully or not
+pub(crate) struct FinishedFont {
+    pub(crate) pending: PendingFont,
+    pub(crate) result: std::io::Result<Vec<u8>>,
+}
+
+/// Reads font files on background threads, see [`FontCfgUi::async_loading`](crate::FontCfgUi::async_loading)
+pub struct FontLoadQueue {
+    reader: Reader,
+    spawner: Spawner,
+    /// Fonts being read, in the order they were queued
+    pending: Vec<PendingFont>,
+    sender: mpsc::Sender<(String, std::io::Result<Vec<u8>>)>,
+    receiver: mpsc::Receiver<(String, std::io::Result<Vec<u8>>)>,
+}
+
+/// Reads files with the [font source](crate::FontCfgUi::font_source) of the ui the queue is
+/// used with, or from the file system if it has none
+impl Default for FontLoadQueue {
+    fn default() -> Self {
+        Self::new(Reader::Default(None))
+    }
+}
+
+impl FontLoadQueue {
+    /// Read files with `reader` instead of [`std::fs::read`]
+    ///
+    /// The reader is called on a background thread, see [`Self::spawner`].
+    pub fn with_reader(
+        reader: impl Fn(&str) -> std::io::Result<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(Reader::Blocking(Arc::new(reader)))
+    }
+    /// Read files with `reader`, which starts reading the file at the path and calls the
+    /// [`ReadDone`] callback with its contents once it's done
+    ///
+    /// Unlike [`Self::with_reader`], this doesn't block, so it works with readers that are
+    /// futures, e.g. on the web:
+    ///
+    /// ```ignore
+    /// FontLoadQueue::with_async_reader(|path, done| {
+    ///     let path = path.to_owned();
+    ///     wasm_bindgen_futures::spawn_local(async move { done(fetch_font(&path).await) });
+    /// })
+    /// ```
+    ///
+    /// The reader is called on the ui thread, and isn't run on the [`Self::spawner`].
+    pub fn with_async_reader(reader: impl Fn(&str, ReadDone) + Send + Sync + 'static) -> Self {
+        Self::new(Reader::Callback(Arc::new(reader)))
+    }
+    fn new(reader: Reader) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            reader,
+            spawner: default_spawner(),
+            pending: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+    /// Run the file reads with `spawner` instead of on new threads
+    ///
+    /// This lets the reads run on the runtime or thread pool the application already uses,
+    /// e.g. with tokio:
+    ///
+    /// ```ignore
+    /// FontLoadQueue::default().spawner(|task| drop(tokio::task::spawn_blocking(task)))
+    /// ```
+    ///
+    /// The tasks block while reading, so spawn them somewhere blocking is allowed.
+    /// By default, they run on new threads, except on wasm32, where they run right away.
+    pub fn spawner(mut self, spawner: impl Fn(Task) + Send + Sync + 'static) -> Self {
+        self.spawner = Arc::new(spawner);
+        self
+    }
+    /// The spawner the reads run on, for other background work
+    #[cfg(any(feature = "compression", feature = "system-fonts"))]
+    pub(crate) fn spawner_handle(&self) -> Spawner {
+        self.spawner.clone()
+    }
+    /// Read with `source`, if the queue reads with the default reader
+    pub(crate) fn set_default_source(&mut self, source: SharedFontSource) {
+        if let Reader::Default(default) = &mut self.reader {
+            *default = Some(source);
+        }
+    }
+    /// Whether any files are still being read
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+    pub(crate) fn is_pending(&self, name: &str) -> bool {
+        self.pending.iter().any(|p| p.name == name)
+    }
+    pub(crate) fn pending(&self) -> &[PendingFont] {
+        &self.pending
+    }
+    /// Start reading the file at `path`, calling `done` with the result
+    fn start_read(&self, path: &str, done: ReadDone) {
+        match &self.reader {
+            Reader::Blocking(reader) => {
+                let (reader, path) = (reader.clone(), path.to_owned());
+                (self.spawner)(Box::new(move || done(reader(&path))));
+            }
+            Reader::Default(source) => {
+                let (source, path) = (source.clone(), path.to_owned());
+                (self.spawner)(Box::new(move || {
+                    let source = source.as_deref().map(|source| source as _);
+                    let result = crate::source::load(source, &path)
+                        .map_err(|e| std::io::Error::other(e.message));
+                    done(result)
+                }));
+            }
+            Reader::Callback(reader) => reader(path, done),
+        }
+    }
+    /// Start reading the file of `font`
+    pub(crate) fn load(&mut self, font: PendingFont) {
+        let sender = self.sender.clone();
+        let name = font.name.clone();
+        let file = crate::split_custom_font_path(&font.path).0.to_owned();
+        self.pending.push(font);
+        self.start_read(
+            &file,
+            Box::new(move |result| {
+                // The queue may have been dropped in the meantime, which is fine
+                let _ = sender.send((name, result));
+            }),
+        );
+    }
+    /// Read the file at `path`, without adding it to the queue
+    pub(crate) fn read(&self, path: &str) -> mpsc::Receiver<std::io::Result<Vec<u8>>> {
+        let (sender, receiver) = mpsc::channel();
+        self.start_read(
+            path,
+            Box::new(move |result| {
+                let _ = sender.send(result);
+            }),
+        );
+        receiver
+    }
+    /// Take the fonts whose files were read since the last call
+    pub(crate) fn poll(&mut self) -> Vec<FinishedFont> {
+        let mut finished = Vec::new();
+        while let Ok((name, result)) = self.receiver.try_recv() {
+            if let Some(i) = self.pending.iter().position(|p| p.name == name) {
+                finished.push(FinishedFont {
+                    pending: self.pending.remove(i),
+                    result,
+                });
+            }
+        }
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FontLoadQueue, PendingFont, ReadDone},
+        std::sync::{Arc, Mutex},
+    };
+
+    fn pending(name: &str) -> PendingFont {
+        PendingFont {
+            name: name.into(),
+            path: format!("{name}.ttf"),
+            families: Default::default(),
+            position: Default::default(),
+            replace: false,
+        }
+    }
+
+    #[test]
+    fn async_reader_reports_back_later() {
+        let started: Arc<Mutex<Vec<(String, ReadDone)>>> = Arc::default();
+        let reads = started.clone();
+        let mut queue = FontLoadQueue::with_async_reader(move |path, done| {
+            reads.lock().unwrap().push((path.to_owned(), done));
+        });
+        queue.load(pending("a"));
+        queue.load(pending("b"));
+        assert!(queue.poll().is_empty());
+        let reads: Vec<_> = started.lock().unwrap().drain(..).collect();
+        for (path, done) in reads {
+            done(Ok(path.into_bytes()));
+        }
+        let finished = queue.poll();
+        let names: Vec<_> = finished.iter().map(|f| &*f.pending.name).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(*finished[1].result.as_ref().unwrap(), b"b.ttf");
+        assert!(!queue.is_loading());
+    }
+}
@@ -0,0 +1,195 @@
+//! Noticing other instances of the application editing the same font configuration
+
+use std::{
+    io::Write as _,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How long a lock file may go without being refreshed before it's considered stale, where
+/// it can't be checked whether its holder is still running
+const STALE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// How often the holder refreshes the lock file
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+
+/// An advisory lock on a configuration file, held by creating a lock file next to it
+///
+/// The lock file contains the process id of its holder. Locks left behind by processes that
+/// exited are taken over. Where that can't be checked, or the lock file is unreadable, locks
+/// that weren't refreshed for five minutes are taken over instead, so the holder should call
+/// [`Self::refresh`] regularly. The lock is released when this is dropped.
+#[derive(Debug)]
+pub struct ConfigLock {
+    lock_path: PathBuf,
+    held: bool,
+    /// Process id of the other process holding the lock, if known
+    holder: Option<u32>,
+    /// When the lock file was last refreshed, if this process created it
+    refreshed: Option<Instant>,
+}
+
+impl ConfigLock {
+    /// Try to lock the configuration file at `config_path`, using `<config_path>.lock`
+    ///
+    /// Check [`Self::is_held`] to see whether it worked.
+    pub fn new(config_path: impl AsRef<Path>) -> Self {
+        let mut lock_path = config_path.as_ref().as_os_str().to_owned();
+        lock_path.push(".lock");
+        let mut lock = Self {
+            lock_path: lock_path.into(),
+            held: false,
+            holder: None,
+            refreshed: None,
+        };
+        lock.retry();
+        lock
+    }
+    /// Whether this process holds the lock
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+    /// Process id of the other process holding the lock, if it isn't held by this one
+    pub fn holder(&self) -> Option<u32> {
+        self.holder
+    }
+    /// Try to acquire the lock again, e.g. after the user closed the other instance
+    pub fn retry(&mut self) {
+        if self.held {
+            return;
+        }
+        let pid = std::process::id();
+        let created = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&self.lock_path);
+        match created {
+            Ok(mut file) => {
+                self.held = write!(file, "{pid}").is_ok();
+                self.holder = None;
+                self.refreshed = self.held.then(Instant::now);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let holder = std::fs::read_to_string(&self.lock_path)
+                    .ok()
+                    .and_then(|s| s.trim().parse().ok());
+                if holder != Some(pid) && self.is_stale(holder) {
+                    // Left behind by a process that didn't exit cleanly
+                    if std::fs::remove_file(&self.lock_path).is_ok() {
+                        return self.retry();
+                    }
+                }
+                self.held = holder == Some(pid);
+                self.holder = holder.filter(|&holder| holder != pid);
+                self.refreshed = self.held.then(Instant::now);
+            }
+            // The lock can't be created (e.g. read-only location), so there's nothing to
+            // guard against. Report it as held rather than nagging the user.
+            Err(_) => {
+                self.held = true;
+                self.holder = None;
+                self.refreshed = None;
+            }
+        }
+    }
+    /// Keep the lock from being considered stale, or notice that it was taken over
+    ///
+    /// Cheap enough to call every frame; the lock file is only touched every minute.
+    pub fn refresh(&mut self) {
+        let Some(refreshed) = self.refreshed else {
+            return;
+        };
+        if refreshed.elapsed() < REFRESH_EVERY {
+            return;
+        }
+        let touched = self.is_ours()
+            && std::fs::OpenOptions::new()
+                .write(true)
+                .open(&self.lock_path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+                .is_ok();
+        if touched {
+            self.refreshed = Some(Instant::now());
+        } else {
+            // Another instance considered the lock stale and took it over
+            self.held = false;
+            self.refreshed = None;
+            self.retry();
+        }
+    }
+    /// Whether the lock file contains the id of this process
+    fn is_ours(&self) -> bool {
+        std::fs::read_to_string(&self.lock_path)
+            .is_ok_and(|s| s.trim() == std::process::id().to_string())
+    }
+    /// Whether the existing lock file, held by `holder`, was left behind
+    fn is_stale(&self, holder: Option<u32>) -> bool {
+        if let Some(running) = holder.and_then(process_running) {
+            return !running;
+        }
+        std::fs::metadata(&self.lock_path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > STALE_AFTER)
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        if self.held && self.is_ours() {
+            let _ = std::fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+/// Whether the process `pid` is running, if this can be checked
+fn process_running(pid: u32) -> Option<bool> {
+    cfg!(target_os = "linux").then(|| Path::new("/proc").join(pid.to_string()).exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_file(name: &str, contents: &str, age: Duration) -> PathBuf {
+        let config = std::env::temp_dir().join(format!("egui-fontcfg-{name}.ron"));
+        let lock_path = config.with_extension("ron.lock");
+        std::fs::write(&lock_path, contents).unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+        config
+    }
+
+    #[test]
+    fn takes_over_stale_garbled_locks() {
+        let config = lock_file("garbled-stale", "not a pid", STALE_AFTER * 2);
+        let lock = ConfigLock::new(&config);
+        assert!(lock.is_held());
+        assert!(lock.is_ours());
+    }
+
+    #[test]
+    fn keeps_fresh_garbled_locks() {
+        let config = lock_file("garbled-fresh", "", Duration::ZERO);
+        let lock = ConfigLock::new(&config);
+        assert!(!lock.is_held());
+        assert_eq!(lock.holder(), None);
+        std::fs::remove_file(&lock.lock_path).unwrap();
+    }
+
+    #[test]
+    fn notices_being_taken_over() {
+        let config = std::env::temp_dir().join("egui-fontcfg-taken-over.ron");
+        let mut lock = ConfigLock::new(&config);
+        assert!(lock.is_held());
+        std::fs::write(&lock.lock_path, "").unwrap();
+        lock.refreshed = Some(Instant::now() - REFRESH_EVERY);
+        lock.refresh();
+        assert!(!lock.is_held());
+        std::fs::remove_file(&lock.lock_path).unwrap();
+    }
+}
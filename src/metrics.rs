@@ -0,0 +1,154 @@
+//! Comparing the vertical metrics of fonts that share a family
+
+use egui::{FontData, FontDefinitions, FontFamily, FontTweak};
+
+/// Vertical metrics of a font, in ems
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    /// Distance from the baseline to the top of the line
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the line (negative)
+    pub descent: f32,
+    /// Extra gap between lines
+    pub line_gap: f32,
+    /// Height of lowercase letters like `x`, if the font declares it
+    pub x_height: Option<f32>,
+}
+
+impl FontMetrics {
+    /// Read the metrics of `font`. Returns `None` if it can't be parsed.
+    pub fn of(font: &FontData) -> Option<Self> {
+        let face = ttf_parser::Face::parse(&font.font, font.index).ok()?;
+        let upm = f32::from(face.units_per_em());
+        Some(Self {
+            ascent: f32::from(face.ascender()) / upm,
+            descent: f32::from(face.descender()) / upm,
+            line_gap: f32::from(face.line_gap()) / upm,
+            x_height: face
+                .x_height()
+                .filter(|h| *h > 0)
+                .map(|h| f32::from(h) / upm),
+        })
+    }
+    fn height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/// A font whose metrics don't line up with the primary font of a family
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsMismatch {
+    /// The family both fonts are in
+    pub family: FontFamily,
+    /// The first font of the family
+    pub primary: String,
+    /// The fallback font that doesn't line up
+    pub font: String,
+    /// How far the baseline of `font` sits below that of `primary`, in ems
+    pub baseline_shift: f32,
+    /// The x-height of `font` (including its tweaked scale) divided by that of `primary`
+    pub x_height_ratio: Option<f32>,
+    /// A tweak for `font` that should roughly compensate for the mismatch
+    pub suggested_tweak: FontTweak,
+}
+
+/// Baseline shifts smaller than this (in ems) aren't reported
+const BASELINE_TOLERANCE: f32 = 0.02;
+/// x-height ratios closer to 1 than this aren't reported
+const X_HEIGHT_TOLERANCE: f32 = 0.05;
+
+/// Find fonts whose baselines or x-heights visibly differ from the primary font of their family
+///
+/// The results are approximations based on the metrics declared by the fonts, and on how
+/// egui positions fallback glyphs within a row.
+pub fn baseline_report(font_defs: &FontDefinitions) -> Vec<MetricsMismatch> {
+    let mut report = Vec::new();
+    for (family, fonts) in &font_defs.families {
+        let Some((primary_name, rest)) = fonts.split_first() else {
+            continue;
+        };
+        let Some(primary) = font_defs.font_data.get(primary_name) else {
+            continue;
+        };
+        let Some(pm) = FontMetrics::of(primary) else {
+            continue;
+        };
+        for name in rest {
+            let Some(font) = font_defs.font_data.get(name) else {
+                continue;
+            };
+            let Some(fm) = FontMetrics::of(font) else {
+                continue;
+            };
+            // egui centers the height difference of fallback fonts within the row
+            let baseline_shift = fm.ascent + 0.5 * (pm.height() - fm.height()) - pm.ascent;
+            let x_height_ratio = pm
+                .x_height
+                .zip(fm.x_height)
+                .map(|(p, f)| (f * font.tweak.scale) / (p * primary.tweak.scale));
+            let baseline_off = baseline_shift.abs() > BASELINE_TOLERANCE;
+            let x_height_off = x_height_ratio.is_some_and(|r| (r - 1.0).abs() > X_HEIGHT_TOLERANCE);
+            if !baseline_off && !x_height_off {
+                continue;
+            }
+            let mut suggested_tweak = font.tweak;
+            if let Some(ratio) = x_height_ratio.filter(|_| x_height_off) {
+                suggested_tweak.scale /= ratio;
+            }
+            if baseline_off {
+                // The y offset factor is relative to the scaled glyph height of the font
+                let glyph_height = (fm.ascent - fm.descent) * suggested_tweak.scale;
+                suggested_tweak.y_offset_factor -= baseline_shift / glyph_height;
+            }
+            report.push(MetricsMismatch {
+                family: family.clone(),
+                primary: primary_name.clone(),
+                font: name.clone(),
+                baseline_shift,
+                x_height_ratio,
+                suggested_tweak,
+            });
+        }
+    }
+    report
+}
+
+/// Show the baseline report, letting the user apply the suggested tweaks
+pub(crate) fn report_ui(ui: &mut egui::Ui, font_defs: &mut FontDefinitions) {
+    let report = baseline_report(font_defs);
+    if report.is_empty() {
+        ui.label("All fallback fonts line up with their primary fonts");
+        return;
+    }
+    let mut apply = None;
+    egui::Grid::new("baseline_report")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, m) in report.iter().enumerate() {
+                ui.label(format!("{}: {}", m.family, m.font));
+                ui.label(format!("baseline {:+.3} em", m.baseline_shift))
+                    .on_hover_text(format!("Relative to {}", m.primary));
+                match m.x_height_ratio {
+                    Some(r) => ui.label(format!("x-height ×{r:.2}")),
+                    None => ui.label("x-height ?"),
+                };
+                if ui
+                    .button("Apply suggestion")
+                    .on_hover_text(format!(
+                        "Scale {:.2}, y offset factor {:.3}",
+                        m.suggested_tweak.scale, m.suggested_tweak.y_offset_factor
+                    ))
+                    .clicked()
+                {
+                    apply = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(m) = apply.map(|i| &report[i]) {
+        if let Some(font) = font_defs.font_data.get_mut(&m.font) {
+            std::sync::Arc::make_mut(font).tweak = m.suggested_tweak;
+        }
+    }
+}
@@ -0,0 +1,751 @@
+//! The crate's own description of a font configuration, and editing it without the ui
+//!
+//! Persisted formats are built on [`FontEntry`] and [`FamilySpec`], which are independent of
+//! egui's types, so they stay readable when egui changes its types.
+
+use {
+    crate::{
+        format, insert_into_families, new_family, rename_family, rename_fonts, sanitize_font_name,
+        validate, CustomFontPaths, FamilyRenameError, FamilyTemplate, FontNameError,
+        InsertPosition, RenameError, ValidationIssue, Validator,
+    },
+    egui::{FontData, FontDefinitions, FontFamily, FontTweak},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::Arc,
+    },
+};
+
+/// Adjustments to how a font is rendered, mirroring [`egui::FontTweak`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tweaks {
+    /// Scale of the glyphs, relative to the requested size
+    pub scale: f32,
+    /// Vertical offset, as a fraction of the font size
+    pub y_offset_factor: f32,
+    /// Vertical offset, in points
+    pub y_offset: f32,
+    /// Baseline shift, as a fraction of the font size
+    pub baseline_offset_factor: f32,
+}
+
+impl Default for Tweaks {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            y_offset_factor: 0.0,
+            y_offset: 0.0,
+            baseline_offset_factor: 0.0,
+        }
+    }
+}
+
+impl From<FontTweak> for Tweaks {
+    fn from(tweak: FontTweak) -> Self {
+        Self {
+            scale: tweak.scale,
+            y_offset_factor: tweak.y_offset_factor,
+            y_offset: tweak.y_offset,
+            baseline_offset_factor: tweak.baseline_offset_factor,
+        }
+    }
+}
+
+impl From<Tweaks> for FontTweak {
+    fn from(tweaks: Tweaks) -> Self {
+        Self {
+            scale: tweaks.scale,
+            y_offset_factor: tweaks.y_offset_factor,
+            y_offset: tweaks.y_offset,
+            baseline_offset_factor: tweaks.baseline_offset_factor,
+        }
+    }
+}
+
+/// Identifies a font family, mirroring [`egui::FontFamily`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FamilyId {
+    /// The family used for most text
+    Proportional,
+    /// The family used for code
+    Monospace,
+    /// A family the application refers to by name
+    Named(String),
+}
+
+impl From<&FontFamily> for FamilyId {
+    fn from(family: &FontFamily) -> Self {
+        match family {
+            FontFamily::Proportional => Self::Proportional,
+            FontFamily::Monospace => Self::Monospace,
+            FontFamily::Name(name) => Self::Named(name.to_string()),
+        }
+    }
+}
+
+impl From<FontFamily> for FamilyId {
+    fn from(family: FontFamily) -> Self {
+        (&family).into()
+    }
+}
+
+impl From<&FamilyId> for FontFamily {
+    fn from(id: &FamilyId) -> Self {
+        match id {
+            FamilyId::Proportional => Self::Proportional,
+            FamilyId::Monospace => Self::Monospace,
+            FamilyId::Named(name) => Self::Name(name.as_str().into()),
+        }
+    }
+}
+
+impl From<FamilyId> for FontFamily {
+    fn from(id: FamilyId) -> Self {
+        (&id).into()
+    }
+}
+
+/// A font of the configuration, without its data
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontEntry {
+    /// Identifier of the font
+    pub name: String,
+    /// Where the font was loaded from, for fonts the user added (see [`CustomFontPaths`])
+    pub path: Option<String>,
+    /// Face index in the font file
+    pub index: u32,
+    /// Rendering adjustments
+    pub tweaks: Tweaks,
+}
+
+impl FontEntry {
+    /// Describe `font`, called `name`, recording `path` as where it was loaded from
+    pub fn new(name: impl Into<String>, font: &FontData, path: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            path,
+            index: font.index,
+            tweaks: font.tweak.into(),
+        }
+    }
+    /// All fonts of `font_defs`, with their paths from `custom`
+    pub fn from_defs(font_defs: &FontDefinitions, custom: &CustomFontPaths) -> Vec<Self> {
+        font_defs
+            .font_data
+            .iter()
+            .map(|(name, font)| Self::new(name, font, custom.get(name).cloned()))
+            .collect()
+    }
+    /// Set the face index and tweak of `font` to the ones of this entry
+    ///
+    /// Leaves `font` alone if they already match, so it keeps being shared.
+    pub fn apply_to(&self, font: &mut Arc<FontData>) {
+        let tweak = self.tweaks.into();
+        if font.index != self.index || font.tweak != tweak {
+            let font = Arc::make_mut(font);
+            font.index = self.index;
+            font.tweak = tweak;
+        }
+    }
+    /// Make the font from `data` that this entry describes
+    pub fn to_font_data(&self, data: Vec<u8>) -> FontData {
+        FontData {
+            index: self.index,
+            tweak: self.tweaks.into(),
+            ..FontData::from_owned(data)
+        }
+    }
+}
+
+/// A font family and the fonts it consists of
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FamilySpec {
+    /// Which family this is
+    pub id: FamilyId,
+    /// Identifiers of the fonts, in fallback order
+    pub fonts: Vec<String>,
+}
+
+impl FamilySpec {
+    /// All families of `font_defs`
+    pub fn from_defs(font_defs: &FontDefinitions) -> Vec<Self> {
+        font_defs
+            .families
+            .iter()
+            .map(|(family, fonts)| Self {
+                id: family.into(),
+                fonts: fonts.clone(),
+            })
+            .collect()
+    }
+    /// Replace the families of `font_defs` with `families`
+    pub fn apply_to_defs(families: &[Self], font_defs: &mut FontDefinitions) {
+        font_defs.families = families
+            .iter()
+            .map(|spec| ((&spec.id).into(), spec.fonts.clone()))
+            .collect();
+    }
+}
+
+/// Reason an edit of a [`FontConfig`] was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontConfigError {
+    /// The font identifier isn't valid
+    InvalidName(FontNameError),
+    /// There already is a font with this identifier
+    FontExists(String),
+    /// There is no font with this identifier
+    NoSuchFont(String),
+    /// The font data can't be loaded by egui
+    InvalidFont(String),
+    /// The family name is empty, or only consists of whitespace
+    EmptyFamilyName,
+    /// There already is a family with this name
+    FamilyExists(FontFamily),
+    /// There is no such family
+    NoSuchFamily(FontFamily),
+    /// The font isn't in the family
+    NotInFamily(String, FontFamily),
+}
+
+impl std::fmt::Display for FontConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName(e) => e.fmt(f),
+            Self::FontExists(name) => write!(f, "A font called {name} already exists"),
+            Self::NoSuchFont(name) => write!(f, "There is no font called {name}"),
+            Self::InvalidFont(e) => f.write_str(e),
+            Self::EmptyFamilyName => f.write_str("Family name can't be empty"),
+            Self::FamilyExists(family) => write!(f, "The family {family} already exists"),
+            Self::NoSuchFamily(family) => write!(f, "There is no family {family}"),
+            Self::NotInFamily(name, family) => write!(f, "{name} isn't in the family {family}"),
+        }
+    }
+}
+
+impl std::error::Error for FontConfigError {}
+
+/// Font definitions along with the paths of the custom fonts, for editing a configuration
+/// from code
+///
+/// The edits make the same checks [`FontCfgUi`](crate::FontCfgUi) does, so a configuration
+/// built here is one the user could have made. Show it to the user with
+/// [`FontCfgUi::show_config`](crate::FontCfgUi::show_config).
+#[derive(Clone, Default)]
+pub struct FontConfig {
+    /// The fonts and families
+    pub font_defs: FontDefinitions,
+    /// Where the fonts added by the user were loaded from
+    pub custom: CustomFontPaths,
+}
+
+impl FontConfig {
+    /// Edit `font_defs`, whose custom fonts were loaded from the paths in `custom`
+    pub fn new(font_defs: FontDefinitions, custom: CustomFontPaths) -> Self {
+        Self { font_defs, custom }
+    }
+    /// Borrow the configuration for editing, see [`FontConfigMut`]
+    pub fn edit(&mut self) -> FontConfigMut<'_> {
+        FontConfigMut::new(&mut self.font_defs, Some(&mut self.custom))
+    }
+    /// See [`FontConfigMut::add_font`]
+    pub fn add_font(
+        &mut self,
+        name: &str,
+        data: Vec<u8>,
+        path: Option<String>,
+    ) -> Result<String, FontConfigError> {
+        self.edit().add_font(name, data, path)
+    }
+    /// See [`FontConfigMut::remove_font`]
+    pub fn remove_font(&mut self, name: &str) -> Result<Arc<FontData>, FontConfigError> {
+        self.edit().remove_font(name)
+    }
+    /// See [`FontConfigMut::rename_font`]
+    pub fn rename_font(&mut self, old: &str, new: &str) -> Result<(), RenameError> {
+        self.edit().rename_font(old, new)
+    }
+    /// See [`FontConfigMut::add_to_family`]
+    pub fn add_to_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+        position: InsertPosition,
+    ) -> Result<(), FontConfigError> {
+        self.edit().add_to_family(family, name, position)
+    }
+    /// See [`FontConfigMut::remove_from_family`]
+    pub fn remove_from_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+    ) -> Result<(), FontConfigError> {
+        self.edit().remove_from_family(family, name)
+    }
+    /// See [`FontConfigMut::move_in_family`]
+    pub fn move_in_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+        position: usize,
+    ) -> Result<(), FontConfigError> {
+        self.edit().move_in_family(family, name, position)
+    }
+    /// See [`FontConfigMut::add_family`]
+    pub fn add_family(
+        &mut self,
+        name: &str,
+        template: &FamilyTemplate,
+    ) -> Result<FontFamily, FontConfigError> {
+        self.edit().add_family(name, template)
+    }
+    /// See [`FontConfigMut::remove_family`]
+    pub fn remove_family(&mut self, family: &FontFamily) -> Result<Vec<String>, FontConfigError> {
+        self.edit().remove_family(family)
+    }
+    /// See [`FontConfigMut::rename_family`]
+    pub fn rename_family(
+        &mut self,
+        family: &FontFamily,
+        new_name: &str,
+    ) -> Result<(), FamilyRenameError> {
+        self.edit().rename_family(family, new_name)
+    }
+    /// The problems the ui would report, found by the built-in checks and `validators`
+    pub fn validate(&self, validators: &[Validator]) -> Vec<ValidationIssue> {
+        validate::run(&self.font_defs, validators)
+    }
+}
+
+/// A font configuration borrowed from wherever it's kept, for editing it like a [`FontConfig`]
+///
+/// This is what [`FontCfgUi`](crate::FontCfgUi) edits the font definitions passed to it with.
+/// Without custom font paths, the paths fonts are loaded from aren't recorded.
+pub struct FontConfigMut<'a> {
+    /// The fonts and families
+    pub font_defs: &'a mut FontDefinitions,
+    /// Where the fonts added by the user were loaded from, if they're kept track of
+    pub custom: Option<&'a mut CustomFontPaths>,
+}
+
+impl<'a> FontConfigMut<'a> {
+    /// Edit `font_defs`, whose custom fonts were loaded from the paths in `custom`
+    pub fn new(
+        font_defs: &'a mut FontDefinitions,
+        custom: Option<&'a mut CustomFontPaths>,
+    ) -> Self {
+        Self { font_defs, custom }
+    }
+    /// Add the font in `data` as `name`, recording `path` as where it was loaded from
+    ///
+    /// The identifier is trimmed, and the data converted if it's in a format egui can't load
+    /// but the enabled features can convert. Returns the identifier the font was added under.
+    pub fn add_font(
+        &mut self,
+        name: &str,
+        data: Vec<u8>,
+        path: Option<String>,
+    ) -> Result<String, FontConfigError> {
+        let name = self.new_font_name(name)?;
+        let data = format::ensure_loadable(data, 0).map_err(FontConfigError::InvalidFont)?;
+        self.insert_font(&name, FontData::from_owned(data), path)
+    }
+    /// Add `font` as `name`, like [`Self::add_font`], for data that's ready to be used (e.g.
+    /// with a face index other than 0)
+    pub fn insert_font(
+        &mut self,
+        name: &str,
+        font: FontData,
+        path: Option<String>,
+    ) -> Result<String, FontConfigError> {
+        let name = self.new_font_name(name)?;
+        format::check_font_data(&font.font, font.index).map_err(FontConfigError::InvalidFont)?;
+        self.font_defs
+            .font_data
+            .insert(name.clone(), Arc::new(font));
+        if let (Some(path), Some(custom)) = (path, self.custom.as_deref_mut()) {
+            custom.insert(name.clone(), path);
+        }
+        Ok(name)
+    }
+    /// Add `font` as `name` like [`Self::insert_font`], replacing the font of that name if
+    /// there is one (e.g. after the user confirmed overwriting it)
+    ///
+    /// The family entries of the replaced font are kept. Its custom path is replaced by `path`,
+    /// or removed if there is none. Returns the identifier and the replaced font's data.
+    pub fn replace_font(
+        &mut self,
+        name: &str,
+        font: FontData,
+        path: Option<String>,
+    ) -> Result<(String, Option<Arc<FontData>>), FontConfigError> {
+        let name = sanitize_font_name(name, None).map_err(FontConfigError::InvalidName)?;
+        format::check_font_data(&font.font, font.index).map_err(FontConfigError::InvalidFont)?;
+        let replaced = self
+            .font_defs
+            .font_data
+            .insert(name.clone(), Arc::new(font));
+        if let Some(custom) = self.custom.as_deref_mut() {
+            match path {
+                Some(path) => custom.insert(name.clone(), path),
+                None => custom.remove(&name),
+            };
+        }
+        Ok((name, replaced))
+    }
+    /// Remove the font called `name`, its custom path and its family entries
+    ///
+    /// Returns the font's data.
+    pub fn remove_font(&mut self, name: &str) -> Result<Arc<FontData>, FontConfigError> {
+        let (data, _) = self.take_font(name)?;
+        for fonts in self.font_defs.families.values_mut() {
+            fonts.retain(|f| f != name);
+        }
+        Ok(data)
+    }
+    /// Remove the font called `name` and its custom path, but keep its family entries, e.g. to
+    /// put it back later
+    ///
+    /// Returns the font's data and custom path.
+    pub fn take_font(
+        &mut self,
+        name: &str,
+    ) -> Result<(Arc<FontData>, Option<String>), FontConfigError> {
+        let data = self
+            .font_defs
+            .font_data
+            .remove(name)
+            .ok_or_else(|| FontConfigError::NoSuchFont(name.to_owned()))?;
+        let path = self
+            .custom
+            .as_deref_mut()
+            .and_then(|custom| custom.remove(name));
+        Ok((data, path))
+    }
+    /// Rename the font `old` to `new`, updating the references to it
+    pub fn rename_font(&mut self, old: &str, new: &str) -> Result<(), RenameError> {
+        self.rename_fonts(&BTreeMap::from([(old.to_owned(), new.to_owned())]))
+    }
+    /// Rename several fonts at once, see [`rename_fonts`]
+    pub fn rename_fonts(&mut self, renames: &BTreeMap<String, String>) -> Result<(), RenameError> {
+        rename_fonts(self.font_defs, self.custom.as_deref_mut(), renames)
+    }
+    /// Add the font `name` to `family` at `position`, moving it there if it's already in it
+    pub fn add_to_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+        position: InsertPosition,
+    ) -> Result<(), FontConfigError> {
+        if !self.font_defs.families.contains_key(family) {
+            return Err(FontConfigError::NoSuchFamily(family.clone()));
+        }
+        self.add_to_families(&BTreeSet::from([family.clone()]), name, position)
+    }
+    /// Add the font `name` to each of `families` at `position`, skipping the ones that
+    /// don't exist
+    pub fn add_to_families(
+        &mut self,
+        families: &BTreeSet<FontFamily>,
+        name: &str,
+        position: InsertPosition,
+    ) -> Result<(), FontConfigError> {
+        if !self.font_defs.font_data.contains_key(name) {
+            return Err(FontConfigError::NoSuchFont(name.to_owned()));
+        }
+        insert_into_families(self.font_defs, name, families, position);
+        Ok(())
+    }
+    /// Remove the font `name` from `family`, keeping the font itself
+    pub fn remove_from_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+    ) -> Result<(), FontConfigError> {
+        let fonts = self.family_fonts_mut(family)?;
+        let len = fonts.len();
+        fonts.retain(|f| f != name);
+        if fonts.len() == len {
+            return Err(FontConfigError::NotInFamily(
+                name.to_owned(),
+                family.clone(),
+            ));
+        }
+        Ok(())
+    }
+    /// Move the font `name` to `position` in `family`, where 0 is tried first
+    ///
+    /// Positions past the end put the font last.
+    pub fn move_in_family(
+        &mut self,
+        family: &FontFamily,
+        name: &str,
+        position: usize,
+    ) -> Result<(), FontConfigError> {
+        let fonts = self.family_fonts_mut(family)?;
+        let from = fonts
+            .iter()
+            .position(|f| f == name)
+            .ok_or_else(|| FontConfigError::NotInFamily(name.to_owned(), family.clone()))?;
+        let font = fonts.remove(from);
+        fonts.insert(position.min(fonts.len()), font);
+        Ok(())
+    }
+    /// Create the named family `name` with the fonts `template` picks
+    pub fn add_family(
+        &mut self,
+        name: &str,
+        template: &FamilyTemplate,
+    ) -> Result<FontFamily, FontConfigError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(FontConfigError::EmptyFamilyName);
+        }
+        let family = FontFamily::Name(name.into());
+        if let FamilyTemplate::PrimaryWithFallback(primary) = template {
+            if !self.font_defs.font_data.contains_key(primary) {
+                return Err(FontConfigError::NoSuchFont(primary.clone()));
+            }
+        }
+        if !new_family(self.font_defs, name, template) {
+            return Err(FontConfigError::FamilyExists(family));
+        }
+        Ok(family)
+    }
+    /// Remove `family`, returning its fonts
+    pub fn remove_family(&mut self, family: &FontFamily) -> Result<Vec<String>, FontConfigError> {
+        self.font_defs
+            .families
+            .remove(family)
+            .ok_or_else(|| FontConfigError::NoSuchFamily(family.clone()))
+    }
+    /// Rename the named family `family` to `new_name`
+    pub fn rename_family(
+        &mut self,
+        family: &FontFamily,
+        new_name: &str,
+    ) -> Result<(), FamilyRenameError> {
+        rename_family(self.font_defs, family, new_name)
+    }
+    /// The problems the ui would report, found by the built-in checks and `validators`
+    pub fn validate(&self, validators: &[Validator]) -> Vec<ValidationIssue> {
+        validate::run(self.font_defs, validators)
+    }
+    /// `name` trimmed, if it's a valid identifier that isn't taken
+    fn new_font_name(&self, name: &str) -> Result<String, FontConfigError> {
+        let name = sanitize_font_name(name, None).map_err(FontConfigError::InvalidName)?;
+        if self.font_defs.font_data.contains_key(&name) {
+            return Err(FontConfigError::FontExists(name));
+        }
+        Ok(name)
+    }
+    fn family_fonts_mut(
+        &mut self,
+        family: &FontFamily,
+    ) -> Result<&mut Vec<String>, FontConfigError> {
+        self.font_defs
+            .families
+            .get_mut(family)
+            .ok_or_else(|| FontConfigError::NoSuchFamily(family.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FontConfig, FontConfigError},
+        crate::{
+            CustomFontPaths, FamilyRenameError, FamilyTemplate, FontNameError, InsertPosition,
+            RenameError,
+        },
+        egui::{FontDefinitions, FontFamily},
+        std::borrow::Cow,
+    };
+
+    fn config() -> FontConfig {
+        FontConfig::new(FontDefinitions::default(), CustomFontPaths::default())
+    }
+
+    fn hack_data() -> Vec<u8> {
+        FontDefinitions::default().font_data["Hack"].font.to_vec()
+    }
+
+    fn family_fonts<'a>(config: &'a FontConfig, family: &FontFamily) -> &'a [String] {
+        &config.font_defs.families[family]
+    }
+
+    #[test]
+    fn adds_and_removes_fonts() {
+        let mut config = config();
+        let name = config
+            .add_font(" Mine ", hack_data(), Some("mine.ttf".into()))
+            .unwrap();
+        assert_eq!(name, "Mine");
+        assert_eq!(
+            config.custom.get("Mine").map(String::as_str),
+            Some("mine.ttf")
+        );
+        config
+            .add_to_family(&FontFamily::Monospace, "Mine", InsertPosition::Front)
+            .unwrap();
+        assert_eq!(family_fonts(&config, &FontFamily::Monospace)[0], "Mine");
+        config.remove_font("Mine").unwrap();
+        assert!(!config.font_defs.font_data.contains_key("Mine"));
+        assert!(config.custom.is_empty());
+        assert!(!family_fonts(&config, &FontFamily::Monospace).contains(&"Mine".to_owned()));
+    }
+
+    #[test]
+    fn rejects_bad_fonts() {
+        let mut config = config();
+        assert_eq!(
+            config.add_font("  ", hack_data(), None),
+            Err(FontConfigError::InvalidName(FontNameError::Empty))
+        );
+        assert_eq!(
+            config.add_font("Hack", hack_data(), None),
+            Err(FontConfigError::FontExists("Hack".into()))
+        );
+        assert!(matches!(
+            config.add_font("Junk", b"not a font".to_vec(), None),
+            Err(FontConfigError::InvalidFont(_))
+        ));
+        assert!(!config.font_defs.font_data.contains_key("Junk"));
+        assert_eq!(
+            config.remove_font("Missing").unwrap_err(),
+            FontConfigError::NoSuchFont("Missing".into())
+        );
+        assert_eq!(
+            config.add_to_family(&FontFamily::Monospace, "Missing", InsertPosition::Back),
+            Err(FontConfigError::NoSuchFont("Missing".into()))
+        );
+    }
+
+    #[test]
+    fn replaces_fonts() {
+        let mut config = config();
+        config.custom.insert("Hack".into(), "old.ttf".into());
+        let font = egui::FontData::from_owned(hack_data());
+        assert_eq!(
+            config.edit().insert_font("Hack", font.clone(), None),
+            Err(FontConfigError::FontExists("Hack".into()))
+        );
+        let (name, replaced) = config
+            .edit()
+            .replace_font("Hack", font, Some("new.ttf".into()))
+            .unwrap();
+        assert_eq!(name, "Hack");
+        assert!(replaced.is_some());
+        assert!(matches!(
+            config.font_defs.font_data["Hack"].font,
+            Cow::Owned(_)
+        ));
+        assert_eq!(
+            config.custom.get("Hack").map(String::as_str),
+            Some("new.ttf")
+        );
+        assert!(family_fonts(&config, &FontFamily::Monospace).contains(&"Hack".to_owned()));
+    }
+
+    #[test]
+    fn renames_fonts() {
+        let mut config = config();
+        config.rename_font("Hack", "Code").unwrap();
+        assert!(config.font_defs.font_data.contains_key("Code"));
+        assert!(family_fonts(&config, &FontFamily::Monospace).contains(&"Code".to_owned()));
+        assert_eq!(
+            config.rename_font("Hack", "Other"),
+            Err(RenameError::Missing("Hack".into()))
+        );
+        assert_eq!(
+            config.rename_font("Code", "Ubuntu-Light"),
+            Err(RenameError::Conflict("Ubuntu-Light".into()))
+        );
+    }
+
+    #[test]
+    fn edits_family_entries() {
+        let mut config = config();
+        let mono = FontFamily::Monospace;
+        config.move_in_family(&mono, "Hack", 100).unwrap();
+        assert_eq!(family_fonts(&config, &mono).last().unwrap(), "Hack");
+        config.move_in_family(&mono, "Hack", 0).unwrap();
+        assert_eq!(family_fonts(&config, &mono)[0], "Hack");
+        config.remove_from_family(&mono, "Hack").unwrap();
+        assert_eq!(
+            config.remove_from_family(&mono, "Hack"),
+            Err(FontConfigError::NotInFamily("Hack".into(), mono.clone()))
+        );
+        assert_eq!(
+            config.move_in_family(&mono, "Hack", 0),
+            Err(FontConfigError::NotInFamily("Hack".into(), mono))
+        );
+        assert!(config.font_defs.font_data.contains_key("Hack"));
+        let missing = FontFamily::Name("Missing".into());
+        assert_eq!(
+            config.add_to_family(&missing, "Hack", InsertPosition::Back),
+            Err(FontConfigError::NoSuchFamily(missing.clone()))
+        );
+        assert_eq!(
+            config.remove_from_family(&missing, "Hack"),
+            Err(FontConfigError::NoSuchFamily(missing.clone()))
+        );
+        assert_eq!(
+            config.remove_family(&missing),
+            Err(FontConfigError::NoSuchFamily(missing))
+        );
+    }
+
+    #[test]
+    fn edits_families() {
+        let mut config = config();
+        let heading = config
+            .add_family(" Heading ", &FamilyTemplate::CopyOfMonospace)
+            .unwrap();
+        assert_eq!(heading, FontFamily::Name("Heading".into()));
+        assert_eq!(
+            family_fonts(&config, &heading),
+            family_fonts(&config, &FontFamily::Monospace)
+        );
+        assert_eq!(
+            config.add_family("Heading", &FamilyTemplate::Empty),
+            Err(FontConfigError::FamilyExists(heading.clone()))
+        );
+        assert_eq!(
+            config.add_family(" ", &FamilyTemplate::Empty),
+            Err(FontConfigError::EmptyFamilyName)
+        );
+        assert_eq!(
+            config.add_family(
+                "Other",
+                &FamilyTemplate::PrimaryWithFallback("Missing".into())
+            ),
+            Err(FontConfigError::NoSuchFont("Missing".into()))
+        );
+        assert_eq!(
+            config.rename_family(&FontFamily::Monospace, "Code"),
+            Err(FamilyRenameError::NotNamed)
+        );
+        config.rename_family(&heading, "Title").unwrap();
+        let title = FontFamily::Name("Title".into());
+        assert!(config.remove_family(&title).is_ok());
+        assert!(!config.font_defs.families.contains_key(&title));
+    }
+
+    #[test]
+    fn validates() {
+        let mut config = config();
+        assert!(config.validate(&[]).is_empty());
+        config
+            .font_defs
+            .families
+            .get_mut(&FontFamily::Proportional)
+            .unwrap()
+            .push("Missing".into());
+        assert!(!config.validate(&[]).is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+//! Previewing the fonts at the scale factors of the user's monitors
+
+use {
+    crate::preview::{default_sample_text, PreviewFonts},
+    egui::{FontDefinitions, FontFamily, FontId},
+};
+
+/// A monitor the application can be shown on, see [`FontCfgUi::set_monitors`](crate::FontCfgUi::set_monitors)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// Name shown to the user, e.g. `Laptop screen`
+    pub name: String,
+    /// Physical pixels per egui point on this monitor, including the zoom factor
+    pub pixels_per_point: f32,
+}
+
+impl Monitor {
+    /// A monitor called `name` with the given scale
+    pub fn new(name: impl Into<String>, pixels_per_point: f32) -> Self {
+        Self {
+            name: name.into(),
+            pixels_per_point,
+        }
+    }
+}
+
+/// Previews of the fonts for each monitor
+#[derive(Default)]
+pub(crate) struct MonitorPreviews {
+    pub(crate) monitors: Vec<Monitor>,
+    previews: Vec<PreviewFonts>,
+}
+
+impl MonitorPreviews {
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, font_defs: &FontDefinitions) {
+        if self.monitors.is_empty() {
+            ui.weak("The application didn't report its monitors");
+            return;
+        }
+        self.previews
+            .resize_with(self.monitors.len(), PreviewFonts::default);
+        ui.weak("Rendered pixel for pixel, as the text would appear on each monitor");
+        let size = egui::TextStyle::Body.resolve(ui.style()).size;
+        let color = ui.visuals().text_color();
+        for (monitor, preview) in self.monitors.iter().zip(&mut self.previews) {
+            ui.label(format!(
+                "{} ({:.0}%)",
+                monitor.name,
+                monitor.pixels_per_point * 100.0
+            ));
+            preview.set_ppp_override(Some(monitor.pixels_per_point));
+            if let Err(e) = preview.prepare(ui.ctx(), font_defs) {
+                ui.weak(e);
+                continue;
+            }
+            for family in [FontFamily::Proportional, FontFamily::Monospace] {
+                let text = default_sample_text(&family);
+                let Some(galley) = preview.layout(text, FontId::new(size, family), color) else {
+                    continue;
+                };
+                let (rect, _) = ui.allocate_exact_size(
+                    preview.display_size(ui.ctx(), &galley),
+                    egui::Sense::hover(),
+                );
+                preview.paint(ui.painter(), rect.min, &galley);
+            }
+        }
+    }
+}
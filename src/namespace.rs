@@ -0,0 +1,122 @@
+//! Namespaced font identifiers, like `user/Noto Sans`, for telling apart where fonts came from
+
+use {egui::FontDefinitions, std::collections::BTreeSet};
+
+/// Separates the namespace of an identifier from the rest
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// The namespace of the font identifier `name`, if it has one
+///
+/// `namespace_of("user/Noto Sans")` is `Some("user")`.
+pub fn namespace_of(name: &str) -> Option<&str> {
+    name.split_once(NAMESPACE_SEPARATOR).map(|(ns, _)| ns)
+}
+
+/// Put `name` into `namespace`, unless it's already in it
+pub fn namespaced(namespace: &str, name: &str) -> String {
+    if namespace_of(name) == Some(namespace) {
+        name.to_owned()
+    } else {
+        format!("{namespace}{NAMESPACE_SEPARATOR}{name}")
+    }
+}
+
+/// Only the fonts of `font_defs` that are in `namespace`, e.g. for persisting user additions
+/// separately from the fonts the app registers
+///
+/// The families are kept whole, so [`merge_namespace`] can put the fonts back into their
+/// places. They can refer to fonts that aren't in the result.
+pub fn split_namespace(font_defs: &FontDefinitions, namespace: &str) -> FontDefinitions {
+    FontDefinitions {
+        font_data: font_defs
+            .font_data
+            .iter()
+            .filter(|(name, _)| namespace_of(name) == Some(namespace))
+            .map(|(name, data)| (name.clone(), data.clone()))
+            .collect(),
+        families: font_defs.families.clone(),
+    }
+}
+
+/// Add the fonts of `namespace` from `source` to `target`, e.g. the persisted user additions
+/// to the app's default fonts
+///
+/// Fonts of `namespace` already in `target` are replaced. Each family entry is placed after
+/// the entry preceding it in `source` that `target` also has, or first if there is none,
+/// so fonts the app added since `source` was saved keep their places.
+pub fn merge_namespace(target: &mut FontDefinitions, source: &FontDefinitions, namespace: &str) {
+    let merged: BTreeSet<_> = source
+        .font_data
+        .keys()
+        .filter(|name| namespace_of(name) == Some(namespace))
+        .cloned()
+        .collect();
+    target
+        .font_data
+        .retain(|name, _| namespace_of(name) != Some(namespace));
+    for fonts in target.families.values_mut() {
+        fonts.retain(|name| namespace_of(name) != Some(namespace));
+    }
+    for name in &merged {
+        target
+            .font_data
+            .insert(name.clone(), source.font_data[name].clone());
+    }
+    for (family, source_fonts) in &source.families {
+        for (i, name) in source_fonts.iter().enumerate() {
+            if !merged.contains(name) {
+                continue;
+            }
+            let fonts = target.families.entry(family.clone()).or_default();
+            let pos = source_fonts[..i]
+                .iter()
+                .rev()
+                .find_map(|prev| fonts.iter().position(|f| f == prev))
+                .map_or(0, |pos| pos + 1);
+            fonts.insert(pos, name.clone());
+        }
+    }
+}
+
+/// Let the user pick which namespace the font list is filtered to
+///
+/// `Some("")` stands for fonts without a namespace.
+pub(crate) fn filter_ui(
+    ui: &mut egui::Ui,
+    font_defs: &FontDefinitions,
+    filter: &mut Option<String>,
+) {
+    let namespaces: BTreeSet<_> = font_defs
+        .font_data
+        .keys()
+        .filter_map(|name| namespace_of(name))
+        .collect();
+    if namespaces.is_empty() {
+        *filter = None;
+        return;
+    }
+    let label = |filter: &Option<String>| match filter.as_deref() {
+        None => "All".to_owned(),
+        Some("") => "No namespace".to_owned(),
+        Some(ns) => ns.to_owned(),
+    };
+    egui::ComboBox::from_label("Namespace")
+        .selected_text(label(filter))
+        .show_ui(ui, |ui| {
+            for choice in [None, Some(String::new())]
+                .into_iter()
+                .chain(namespaces.into_iter().map(|ns| Some(ns.to_owned())))
+            {
+                let text = label(&choice);
+                ui.selectable_value(filter, choice, text);
+            }
+        });
+}
+
+/// Whether the font called `name` passes the namespace filter
+pub(crate) fn matches_filter(filter: Option<&str>, name: &str) -> bool {
+    match filter {
+        None => true,
+        Some(ns) => namespace_of(name).unwrap_or("") == ns,
+    }
+}
@@ -0,0 +1,76 @@
+//! Handing user facing notifications to the host, e.g. for showing them as toasts
+
+/// A user facing event, see [`FontCfgUi::notifications`](crate::FontCfgUi::notifications)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    /// Something the user did failed
+    Error(String),
+    /// The fonts were applied to the egui context
+    Applied,
+    /// A preset was applied
+    PresetSwitched(String),
+}
+
+impl std::fmt::Display for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error(msg) => f.write_str(msg),
+            Self::Applied => f.write_str("Fonts applied"),
+            Self::PresetSwitched(name) => write!(f, "Switched to preset {name}"),
+        }
+    }
+}
+
+/// Callback receiving notifications
+pub(crate) type NotificationSink = Box<dyn FnMut(Notification) + Send>;
+
+/// Queues notifications until the next flush, dropping repeats of recent ones
+pub(crate) struct Notifier {
+    sink: Option<NotificationSink>,
+    /// Identical notifications within this many seconds of each other are only emitted once
+    pub(crate) interval: f64,
+    queue: Vec<Notification>,
+    /// Emitted notifications along with the time they were emitted at
+    recent: Vec<(Notification, f64)>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            sink: None,
+            interval: 2.0,
+            queue: Vec::new(),
+            recent: Vec::new(),
+        }
+    }
+}
+
+impl Notifier {
+    pub(crate) fn set_sink(&mut self, sink: NotificationSink) {
+        self.sink = Some(sink);
+    }
+    /// Whether the host takes notifications
+    pub(crate) fn is_active(&self) -> bool {
+        self.sink.is_some()
+    }
+    pub(crate) fn push(&mut self, notification: Notification) {
+        if self.is_active() {
+            self.queue.push(notification);
+        }
+    }
+    /// Hand the queued notifications to the host. `now` is the time in seconds.
+    pub(crate) fn flush(&mut self, now: f64) {
+        let Some(sink) = &mut self.sink else {
+            return;
+        };
+        let interval = self.interval;
+        self.recent.retain(|(_, time)| now - time < interval);
+        for notification in self.queue.drain(..) {
+            if self.recent.iter().any(|(n, _)| *n == notification) {
+                continue;
+            }
+            sink(notification.clone());
+            self.recent.push((notification, now));
+        }
+    }
+}
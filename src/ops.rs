@@ -0,0 +1,310 @@
+//! Scriptable edits of font definitions, for automating repetitive configuration
+
+use {
+    crate::{
+        textfmt::{self, ParseError},
+        CustomFontPaths, FontSource,
+    },
+    egui::{FontDefinitions, FontFamily, FontTweak},
+    std::sync::Arc,
+};
+
+const FORMAT: textfmt::Format = textfmt::Format {
+    header: "egui-fontcfg ops 1",
+    what: "operations script",
+};
+
+/// An edit performed by [`FontConfigOps::run`]
+///
+/// Patterns match font identifiers, with `*` matching any number of characters and `?`
+/// matching a single one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// Load the font at `path` and add it as `name`, replacing any font of that name
+    ///
+    /// The path is recorded in the custom font paths, so it can name a face of a collection
+    /// (see [`custom_font_path`](crate::custom_font_path)).
+    AddFromPath {
+        /// Identifier of the font
+        name: String,
+        /// Path of the font file, recorded in the custom font paths
+        path: String,
+    },
+    /// Remove the fonts matching the pattern, along with their family entries
+    RemoveMatching(String),
+    /// Move the font `name` to `position` in `family`, adding it if it isn't in the family
+    ///
+    /// Positions past the end put the font last. The family is created if necessary.
+    Reorder {
+        /// The family to change
+        family: FontFamily,
+        /// Identifier of the font
+        name: String,
+        /// 0-based position, where 0 is tried first
+        position: usize,
+    },
+    /// Set the tweak of the fonts matching the pattern
+    SetTweak {
+        /// Pattern of font identifiers
+        pattern: String,
+        /// The new tweak
+        tweak: FontTweak,
+    },
+}
+
+/// Error from [`FontConfigOps::run`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpError {
+    /// Index of the operation that failed
+    pub op: usize,
+    /// What went wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for OpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Operation {}: {}", self.op + 1, self.message)
+    }
+}
+
+impl std::error::Error for OpError {}
+
+/// Whether `name` matches the glob `pattern`
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position to resume from after the last `*`, as (pattern index, name index)
+    let mut star = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    p = sp + 1;
+                    n = sn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Runs [`Op`]s against font definitions
+pub struct FontConfigOps<'a> {
+    font_defs: &'a mut FontDefinitions,
+    custom: Option<&'a mut CustomFontPaths>,
+    source: Option<&'a dyn FontSource>,
+}
+
+impl<'a> FontConfigOps<'a> {
+    /// Edit `font_defs`, recording the paths of added fonts in `custom`
+    pub fn new(
+        font_defs: &'a mut FontDefinitions,
+        custom: Option<&'a mut CustomFontPaths>,
+    ) -> Self {
+        Self {
+            font_defs,
+            custom,
+            source: None,
+        }
+    }
+    /// Load the fonts of [`Op::AddFromPath`] from `source` instead of the file system
+    pub fn font_source(mut self, source: &'a dyn FontSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+    /// Perform `ops` in order
+    ///
+    /// If one fails, nothing is changed.
+    pub fn run(&mut self, ops: &[Op]) -> Result<(), OpError> {
+        let mut defs = self.font_defs.clone();
+        let mut custom = self.custom.as_deref().cloned().unwrap_or_default();
+        for (i, op) in ops.iter().enumerate() {
+            run_op(&mut defs, &mut custom, self.source, op)
+                .map_err(|message| OpError { op: i, message })?;
+        }
+        *self.font_defs = defs;
+        if let Some(target) = &mut self.custom {
+            **target = custom;
+        }
+        Ok(())
+    }
+    /// Write `ops` in a line based text format, readable by [`Self::parse`]
+    pub fn to_text(ops: &[Op]) -> String {
+        let mut out = FORMAT.start();
+        for op in ops {
+            match op {
+                Op::AddFromPath { name, path } => {
+                    textfmt::push_line(&mut out, "add-from-path", [name.as_str(), path.as_str()]);
+                }
+                Op::RemoveMatching(pattern) => {
+                    textfmt::push_line(&mut out, "remove-matching", [pattern.as_str()]);
+                }
+                Op::Reorder {
+                    family,
+                    name,
+                    position,
+                } => {
+                    let family = textfmt::family_to_field(&family.into());
+                    let position = position.to_string();
+                    textfmt::push_line(
+                        &mut out,
+                        "reorder",
+                        [family.as_str(), name.as_str(), position.as_str()],
+                    );
+                }
+                Op::SetTweak { pattern, tweak } => {
+                    let tweak = textfmt::tweak_to_fields(&(*tweak).into());
+                    textfmt::push_line(
+                        &mut out,
+                        "set-tweak",
+                        std::iter::once(pattern.as_str()).chain(tweak.iter().map(String::as_str)),
+                    );
+                }
+            }
+        }
+        out
+    }
+    /// Read operations written by [`Self::to_text`]
+    pub fn parse(text: &str) -> Result<Vec<Op>, ParseError> {
+        let mut ops = Vec::new();
+        for line in FORMAT.body(text)? {
+            let (line_no, keyword, fields) = line?;
+            let err = |message: String| ParseError {
+                line: line_no,
+                message,
+            };
+            let op = match (keyword.as_str(), fields.as_slice()) {
+                ("add-from-path", [name, path]) => Op::AddFromPath {
+                    name: name.clone(),
+                    path: path.clone(),
+                },
+                ("remove-matching", [pattern]) => Op::RemoveMatching(pattern.clone()),
+                ("reorder", [family, name, position]) => Op::Reorder {
+                    family: textfmt::family_from_field(family).map_err(err)?.into(),
+                    name: name.clone(),
+                    position: position
+                        .parse()
+                        .map_err(|_| err(format!("{position:?} is not a valid position")))?,
+                },
+                ("set-tweak", [pattern, tweak @ ..]) => Op::SetTweak {
+                    pattern: pattern.clone(),
+                    tweak: textfmt::tweak_from_fields(tweak).map_err(err)?.into(),
+                },
+                _ => return Err(err(format!("Unexpected line starting with {keyword:?}"))),
+            };
+            ops.push(op);
+        }
+        Ok(ops)
+    }
+}
+
+fn run_op(
+    defs: &mut FontDefinitions,
+    custom: &mut CustomFontPaths,
+    source: Option<&dyn FontSource>,
+    op: &Op,
+) -> Result<(), String> {
+    match op {
+        Op::AddFromPath { name, path } => {
+            let font = crate::source::load_custom_font(source, path).map_err(|e| e.to_string())?;
+            defs.font_data.insert(name.clone(), Arc::new(font));
+            custom.insert(name.clone(), path.clone());
+        }
+        Op::RemoveMatching(pattern) => {
+            defs.font_data.retain(|name, _| !glob_match(pattern, name));
+            custom.retain(|name, _| !glob_match(pattern, name));
+            for fonts in defs.families.values_mut() {
+                fonts.retain(|name| !glob_match(pattern, name));
+            }
+        }
+        Op::Reorder {
+            family,
+            name,
+            position,
+        } => {
+            if !defs.font_data.contains_key(name) {
+                return Err(format!("There is no font called {name}"));
+            }
+            let fonts = defs.families.entry(family.clone()).or_default();
+            fonts.retain(|f| f != name);
+            fonts.insert((*position).min(fonts.len()), name.clone());
+        }
+        Op::SetTweak { pattern, tweak } => {
+            for (_, font) in defs
+                .font_data
+                .iter_mut()
+                .filter(|(name, _)| glob_match(pattern, name))
+            {
+                Arc::make_mut(font).tweak = *tweak;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FontConfigOps, Op},
+        crate::textfmt::ParseError,
+        egui::{FontFamily, FontTweak},
+    };
+
+    #[test]
+    fn round_trip() {
+        let ops = [
+            Op::AddFromPath {
+                name: "My font".into(),
+                path: "/fonts/tab\there.otf".into(),
+            },
+            Op::RemoveMatching("emoji-*".into()),
+            Op::Reorder {
+                family: FontFamily::Name("Headings".into()),
+                name: "My font".into(),
+                position: 3,
+            },
+            Op::SetTweak {
+                pattern: "Ubuntu?Light".into(),
+                tweak: FontTweak {
+                    scale: 0.9,
+                    y_offset_factor: 0.1,
+                    y_offset: 2.0,
+                    baseline_offset_factor: -0.05,
+                },
+            },
+        ];
+        let text = FontConfigOps::to_text(&ops);
+        assert_eq!(FontConfigOps::parse(&text), Ok(ops.to_vec()));
+    }
+
+    #[test]
+    fn error_lines() {
+        let error = |text: &str| FontConfigOps::parse(text).unwrap_err();
+        assert_eq!(
+            error(""),
+            ParseError {
+                line: 1,
+                message: "Not an egui-fontcfg operations script".into(),
+            }
+        );
+        let text = "egui-fontcfg ops 1\nremove-matching\t*\n\nreorder\tproportional\tHack\tfirst\n";
+        assert_eq!(
+            error(text),
+            ParseError {
+                line: 4,
+                message: "\"first\" is not a valid position".into(),
+            }
+        );
+    }
+}
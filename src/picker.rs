@@ -0,0 +1,22 @@
+//! Letting the host plug in a file dialog for picking font files
+
+use std::path::PathBuf;
+
+/// Picks a font file for the add new font form
+///
+/// See [`FontCfgUi::file_picker`](crate::FontCfgUi::file_picker). Implemented for closures,
+/// so a file dialog crate can be plugged in directly, e.g. with `rfd`:
+///
+/// ```ignore
+/// ui.file_picker(|| rfd::FileDialog::new().add_filter("Fonts", &["ttf", "otf"]).pick_file())
+/// ```
+pub trait FontFilePicker {
+    /// Let the user pick a file. Returns `None` if they cancelled.
+    fn pick_font_file(&mut self) -> Option<PathBuf>;
+}
+
+impl<F: FnMut() -> Option<PathBuf>> FontFilePicker for F {
+    fn pick_font_file(&mut self) -> Option<PathBuf> {
+        self()
+    }
+}
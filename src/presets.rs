@@ -0,0 +1,433 @@
+//! Named font configurations that can be switched between at runtime
+
+use {
+    crate::{apply_ref, font_usage, ApplyError, CustomFontPaths, FontUsage},
+    egui::{FontDefinitions, FontFamily, KeyboardShortcut},
+};
+
+/// A named font configuration
+#[derive(Clone, Default)]
+pub struct FontPreset {
+    /// The font definitions to use
+    pub font_defs: FontDefinitions,
+    /// Paths of the custom fonts in [`Self::font_defs`]
+    pub custom: CustomFontPaths,
+}
+
+/// An ordered collection of [`FontPreset`]s, remembering which one was applied last
+#[derive(Default)]
+pub struct FontPresets {
+    presets: Vec<(String, FontPreset)>,
+    active: Option<String>,
+}
+
+impl FontPresets {
+    /// Add a preset, replacing any existing preset with the same name
+    pub fn insert(&mut self, name: impl Into<String>, preset: FontPreset) {
+        let name = name.into();
+        match self.presets.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = preset,
+            None => self.presets.push((name, preset)),
+        }
+    }
+    /// Remove the preset called `name`
+    pub fn remove(&mut self, name: &str) -> Option<FontPreset> {
+        let idx = self.presets.iter().position(|(n, _)| n == name)?;
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        Some(self.presets.remove(idx).1)
+    }
+    /// Get the preset called `name`
+    pub fn get(&self, name: &str) -> Option<&FontPreset> {
+        self.presets.iter().find(|(n, _)| n == name).map(|(_, p)| p)
+    }
+    /// The names of the presets, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.iter().map(|(n, _)| n.as_str())
+    }
+    /// Whether there are no presets
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+    /// The name of the preset that was applied last, if it still exists
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+    /// Apply the preset called `name` to the egui context
+    ///
+    /// Returns `Ok(false)` if the preset's fonts were already applied.
+    pub fn apply_preset(&mut self, ctx: &egui::Context, name: &str) -> Result<bool, ApplyError> {
+        let preset = self
+            .get(name)
+            .ok_or_else(|| ApplyError::UnknownPreset(name.to_owned()))?;
+        let pushed = apply_ref(ctx, &preset.font_defs)?.is_some();
+        self.active = Some(name.to_owned());
+        Ok(pushed)
+    }
+    /// Apply the preset after (or before, if `forward` is false) the active one, wrapping around
+    ///
+    /// Returns the name of the applied preset, or `None` if there are no presets.
+    /// The preset is applied as it is, see [`preset_conflicts`] for checking it first.
+    pub fn cycle(
+        &mut self,
+        ctx: &egui::Context,
+        forward: bool,
+    ) -> Result<Option<&str>, ApplyError> {
+        let Some(name) = self.neighbor(forward).map(str::to_owned) else {
+            return Ok(None);
+        };
+        self.apply_preset(ctx, &name)?;
+        Ok(self.active.as_deref())
+    }
+    /// The name of the preset after (or before) the active one, wrapping around
+    pub(crate) fn neighbor(&self, forward: bool) -> Option<&str> {
+        let len = self.presets.len();
+        if len == 0 {
+            return None;
+        }
+        let current = self
+            .active
+            .as_deref()
+            .and_then(|a| self.presets.iter().position(|(n, _)| n == a));
+        let idx = match (current, forward) {
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+        Some(&self.presets[idx].0)
+    }
+    /// Apply `font_defs`, the resolved fonts of the preset called `name`
+    pub(crate) fn apply_resolved(
+        &mut self,
+        ctx: &egui::Context,
+        name: &str,
+        font_defs: &FontDefinitions,
+    ) -> Result<bool, ApplyError> {
+        let pushed = apply_ref(ctx, font_defs)?.is_some();
+        self.active = Some(name.to_owned());
+        Ok(pushed)
+    }
+}
+
+/// A font that a named family relies on, which switching to a preset would drop
+///
+/// Found by [`preset_conflicts`]. Without its fonts, the preset would leave the family
+/// missing or unable to render anything, while the application still uses it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetConflict {
+    /// The named family
+    pub family: FontFamily,
+    /// The font in the family, which the preset doesn't have
+    pub font: String,
+}
+
+/// What to do about a [`PresetConflict`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Carry the font over from the current configuration into the preset's family
+    #[default]
+    Keep,
+    /// Use this font of the preset in its place
+    Remap(String),
+    /// Leave the font out of the family
+    Remove,
+}
+
+/// The fonts `current` has in named families the application uses, which `preset` would drop
+///
+/// A family counts as used if [`font_usage`](crate::font_usage) reported it. If no uses
+/// were reported at all, every named family counts. Families the preset gives fonts of its
+/// own aren't conflicts.
+pub fn preset_conflicts(
+    current: &FontDefinitions,
+    preset: &FontDefinitions,
+    usage: &FontUsage,
+) -> Vec<PresetConflict> {
+    let mut conflicts = Vec::new();
+    for (family, fonts) in &current.families {
+        let FontFamily::Name(_) = family else {
+            continue;
+        };
+        if !usage.families.is_empty() && usage.families.get(family).is_none_or(|&n| n == 0) {
+            continue;
+        }
+        let covered = preset
+            .families
+            .get(family)
+            .is_some_and(|fonts| fonts.iter().any(|f| preset.font_data.contains_key(f)));
+        if covered {
+            continue;
+        }
+        conflicts.extend(
+            fonts
+                .iter()
+                .filter(|f| !preset.font_data.contains_key(*f))
+                .map(|f| PresetConflict {
+                    family: family.clone(),
+                    font: f.clone(),
+                }),
+        );
+    }
+    conflicts
+}
+
+/// The preset with `resolutions` of its conflicts with `current` applied
+///
+/// Kept fonts are copied from `current`, along with their paths from `current_custom`.
+pub fn resolve_preset_conflicts(
+    preset: &FontPreset,
+    current: &FontDefinitions,
+    current_custom: &CustomFontPaths,
+    resolutions: &[(PresetConflict, ConflictResolution)],
+) -> FontPreset {
+    let mut resolved = preset.clone();
+    for (conflict, resolution) in resolutions {
+        let name = match resolution {
+            ConflictResolution::Keep => {
+                let Some(font) = current.font_data.get(&conflict.font) else {
+                    continue;
+                };
+                resolved
+                    .font_defs
+                    .font_data
+                    .insert(conflict.font.clone(), font.clone());
+                if let Some(path) = current_custom.get(&conflict.font) {
+                    resolved.custom.insert(conflict.font.clone(), path.clone());
+                }
+                &conflict.font
+            }
+            ConflictResolution::Remap(name) => name,
+            ConflictResolution::Remove => {
+                // The family must still exist, or using it would panic
+                resolved
+                    .font_defs
+                    .families
+                    .entry(conflict.family.clone())
+                    .or_default();
+                continue;
+            }
+        };
+        let fonts = resolved
+            .font_defs
+            .families
+            .entry(conflict.family.clone())
+            .or_default();
+        fonts.retain(|f| resolved.font_defs.font_data.contains_key(f));
+        if !fonts.contains(name) {
+            fonts.push(name.clone());
+        }
+    }
+    resolved
+}
+
+/// Keyboard shortcuts for cycling through presets, see [`FontCfgWindow::preset_hotkeys`]
+///
+/// [`FontCfgWindow::preset_hotkeys`]: crate::FontCfgWindow::preset_hotkeys
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresetHotkeys {
+    /// Switch to the next preset
+    pub next: Option<KeyboardShortcut>,
+    /// Switch to the previous preset
+    pub previous: Option<KeyboardShortcut>,
+}
+
+impl PresetHotkeys {
+    /// Consume the pressed shortcut. Returns `Some(true)` for next, `Some(false)` for previous.
+    pub(crate) fn pressed(&self, ctx: &egui::Context) -> Option<bool> {
+        ctx.input_mut(|i| {
+            if self.next.is_some_and(|s| i.consume_shortcut(&s)) {
+                Some(true)
+            } else if self.previous.is_some_and(|s| i.consume_shortcut(&s)) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Form for managing presets
+#[derive(Default)]
+pub(crate) struct PresetsForm {
+    name: String,
+    pending: Option<PendingSwitch>,
+}
+
+/// A preset switch waiting for the user to resolve its conflicts
+struct PendingSwitch {
+    name: String,
+    /// Whether to apply the preset after loading it
+    apply: bool,
+    resolutions: Vec<(PresetConflict, ConflictResolution)>,
+}
+
+impl PresetsForm {
+    /// Show the form. Returns the error message if applying a preset failed.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        presets: &mut FontPresets,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+    ) -> Option<String> {
+        if self.pending.is_some() {
+            return self.resolution_ui(ui, presets, font_defs, custom);
+        }
+        let mut load = None;
+        let mut apply = None;
+        let mut remove = None;
+        for name in presets.names() {
+            ui.horizontal(|ui| {
+                if presets.active() == Some(name) {
+                    ui.strong(name);
+                } else {
+                    ui.label(name);
+                }
+                if ui
+                    .button("Load")
+                    .on_hover_text("Load the preset for editing")
+                    .clicked()
+                {
+                    load = Some(name.to_owned());
+                }
+                if ui
+                    .button("Apply")
+                    .on_hover_text("Load the preset and apply it")
+                    .clicked()
+                {
+                    apply = Some(name.to_owned());
+                }
+                if ui.button("🗑").on_hover_text("Delete preset").clicked() {
+                    remove = Some(name.to_owned());
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            let re = ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Preset name"));
+            crate::ui::a11y_text_label(&re, "Name of new preset", &self.name);
+            let name = self.name.trim();
+            let re = ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save as preset"))
+                .on_hover_text("Save the current configuration as a preset");
+            if re.clicked() {
+                let preset = FontPreset {
+                    font_defs: font_defs.clone(),
+                    custom: custom.as_deref().cloned().unwrap_or_default(),
+                };
+                presets.insert(name, preset);
+                self.name.clear();
+            }
+        });
+        if let Some(name) = remove {
+            presets.remove(&name);
+        }
+        let name = load.or(apply.clone())?;
+        let preset = presets.get(&name)?;
+        let usage = font_usage(ui.ctx());
+        let conflicts = preset_conflicts(font_defs, &preset.font_defs, &usage);
+        if !conflicts.is_empty() {
+            self.pending = Some(PendingSwitch {
+                name,
+                apply: apply.is_some(),
+                resolutions: conflicts
+                    .into_iter()
+                    .map(|c| (c, ConflictResolution::Keep))
+                    .collect(),
+            });
+            return None;
+        }
+        *font_defs = preset.font_defs.clone();
+        if let Some(custom) = &mut custom {
+            custom.clone_from(&preset.custom);
+        }
+        let name = apply?;
+        presets
+            .apply_preset(ui.ctx(), &name)
+            .err()
+            .map(|e| e.to_string())
+    }
+    /// Let the user decide what happens to the fonts the pending switch would drop
+    fn resolution_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        presets: &mut FontPresets,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> Option<String> {
+        let pending = self.pending.as_mut()?;
+        let Some(preset) = presets.get(&pending.name) else {
+            self.pending = None;
+            return None;
+        };
+        ui.label(format!(
+            "Preset {} doesn't have fonts that families used by the application rely on",
+            pending.name
+        ));
+        egui::Grid::new("preset_conflicts")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (i, (conflict, resolution)) in pending.resolutions.iter_mut().enumerate() {
+                    ui.label(format!("{} in {}", conflict.font, conflict.family));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(resolution, ConflictResolution::Keep, "Keep")
+                            .on_hover_text("Carry the font over into the preset");
+                        let remapped = matches!(resolution, ConflictResolution::Remap(_));
+                        let first = preset.font_defs.font_data.keys().next();
+                        let re = ui
+                            .add_enabled(
+                                first.is_some(),
+                                egui::RadioButton::new(remapped, "Remap to"),
+                            )
+                            .on_hover_text("Use one of the preset's fonts instead")
+                            .on_disabled_hover_text("The preset has no fonts");
+                        if let (true, false, Some(first)) = (re.clicked(), remapped, first) {
+                            *resolution = ConflictResolution::Remap(first.clone());
+                        }
+                        if let ConflictResolution::Remap(name) = resolution {
+                            egui::ComboBox::from_id_salt(("preset_conflict_remap", i))
+                                .selected_text(name.as_str())
+                                .show_ui(ui, |ui| {
+                                    for font in preset.font_defs.font_data.keys() {
+                                        ui.selectable_value(name, font.clone(), font);
+                                    }
+                                });
+                        }
+                        ui.radio_value(resolution, ConflictResolution::Remove, "Remove")
+                            .on_hover_text("Leave the font out of the family");
+                    });
+                    ui.end_row();
+                }
+            });
+        let (switch, cancel) = ui
+            .horizontal(|ui| (ui.button("Switch").clicked(), ui.button("Cancel").clicked()))
+            .inner;
+        if cancel {
+            self.pending = None;
+            return None;
+        }
+        if !switch {
+            return None;
+        }
+        let pending = self.pending.take()?;
+        let no_paths = CustomFontPaths::default();
+        let resolved = resolve_preset_conflicts(
+            preset,
+            font_defs,
+            custom.as_deref().unwrap_or(&no_paths),
+            &pending.resolutions,
+        );
+        if let Some(custom) = custom {
+            custom.clone_from(&resolved.custom);
+        }
+        *font_defs = resolved.font_defs;
+        if !pending.apply {
+            return None;
+        }
+        presets
+            .apply_resolved(ui.ctx(), &pending.name, font_defs)
+            .err()
+            .map(|e| e.to_string())
+    }
+}
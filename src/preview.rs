@@ -0,0 +1,273 @@
+//! Rendering text with font definitions that aren't applied to the egui context
+//!
+//! This uses a separate [`Fonts`] instance with its own font atlas texture,
+//! so the fonts of the rest of the application are untouched.
+
+use {
+    crate::{check_applicable, DefsFingerprint},
+    egui::{
+        epaint::{text::Fonts, Mesh, Vertex},
+        FontDefinitions, FontId, Galley, Pos2, TextureHandle,
+    },
+    std::sync::Arc,
+};
+
+/// Check that all fonts in `font_defs` can be parsed, so building a [`Fonts`] won't panic
+pub(crate) fn check_parseable(font_defs: &FontDefinitions) -> Result<(), String> {
+    for (name, font) in &font_defs.font_data {
+        if let Err(e) = ttf_parser::Face::parse(&font.font, font.index) {
+            return Err(format!("Font {name:?} can't be parsed: {e}"));
+        }
+    }
+    Ok(())
+}
+
+/// A [`Fonts`] instance for previewing, along with its atlas texture
+#[derive(Default)]
+pub(crate) struct PreviewFonts {
+    fingerprint: Option<DefsFingerprint>,
+    fonts: Option<Fonts>,
+    texture: Option<TextureHandle>,
+    /// Rasterize at this scale instead of the context's, showing the result pixel for pixel
+    ppp_override: Option<f32>,
+}
+
+impl PreviewFonts {
+    pub(crate) fn ppp_override(&self) -> Option<f32> {
+        self.ppp_override
+    }
+    pub(crate) fn set_ppp_override(&mut self, ppp: Option<f32>) {
+        if ppp != self.ppp_override {
+            self.ppp_override = ppp;
+            // The texture filtering depends on the override
+            self.texture = None;
+        }
+    }
+    /// How much galleys are magnified when painted, so each texel covers one physical pixel
+    fn display_scale(&self, ctx: &egui::Context) -> f32 {
+        self.ppp_override
+            .map_or(1.0, |ppp| ppp / ctx.pixels_per_point())
+    }
+    /// The size `galley` takes up when painted
+    pub(crate) fn display_size(&self, ctx: &egui::Context, galley: &Galley) -> egui::Vec2 {
+        galley.size() * self.display_scale(ctx)
+    }
+    /// Make the preview use `font_defs`, rebuilding the fonts only if they changed
+    ///
+    /// Must be called every frame before laying out any text.
+    pub(crate) fn prepare(
+        &mut self,
+        ctx: &egui::Context,
+        font_defs: &FontDefinitions,
+    ) -> Result<(), String> {
+        let ppp = self.ppp_override.unwrap_or_else(|| ctx.pixels_per_point());
+        let max_texture_side = ctx.input(|i| i.max_texture_side);
+        if !self
+            .fingerprint
+            .as_ref()
+            .is_some_and(|fp| fp.matches(font_defs))
+        {
+            self.fonts = None;
+            self.fingerprint = None;
+            check_applicable(font_defs).map_err(|e| e.to_string())?;
+            check_parseable(font_defs)?;
+            self.fonts = Some(Fonts::new(ppp, max_texture_side, font_defs.clone()));
+            self.fingerprint = Some(DefsFingerprint::new(font_defs));
+            // Force a full upload of the new atlas
+            self.texture = None;
+        }
+        if let Some(fonts) = &self.fonts {
+            fonts.begin_pass(ppp, max_texture_side);
+        }
+        Ok(())
+    }
+    /// Lay out a single line of text. Returns `None` if the family of `font_id` doesn't exist.
+    pub(crate) fn layout(
+        &self,
+        text: &str,
+        font_id: FontId,
+        color: egui::Color32,
+    ) -> Option<Arc<Galley>> {
+        let fonts = self.fonts.as_ref()?;
+        if !fonts.families().contains(&font_id.family) {
+            return None;
+        }
+        Some(fonts.layout_no_wrap(text.to_owned(), font_id, color))
+    }
+    /// Height of a row of text. Returns `None` if the family of `font_id` doesn't exist.
+    pub(crate) fn row_height(&self, font_id: &FontId) -> Option<f32> {
+        let fonts = self.fonts.as_ref()?;
+        fonts
+            .families()
+            .contains(&font_id.family)
+            .then(|| fonts.row_height(font_id))
+    }
+    /// Paint a galley laid out by [`Self::layout`], with its top left corner at `pos`
+    pub(crate) fn paint(&mut self, painter: &egui::Painter, pos: Pos2, galley: &Galley) {
+        let scale = self.display_scale(painter.ctx());
+        let Some(fonts) = &self.fonts else {
+            return;
+        };
+        let options = if self.ppp_override.is_some() {
+            egui::TextureOptions::NEAREST
+        } else {
+            egui::TextureOptions::LINEAR
+        };
+        // Laying out text may have rasterized new glyphs into the atlas
+        let atlas_changed = fonts.font_image_delta().is_some();
+        let texture = match &mut self.texture {
+            Some(texture) => {
+                if atlas_changed {
+                    texture.set(fonts.image(), options);
+                }
+                texture
+            }
+            None => self.texture.insert(painter.ctx().load_texture(
+                "egui_fontcfg_preview_atlas",
+                fonts.image(),
+                options,
+            )),
+        };
+        let [w, h] = fonts.font_image_size();
+        let uv_normalizer = egui::vec2(1.0 / w as f32, 1.0 / h as f32);
+        let mut mesh = Mesh::with_texture(texture.id());
+        for row in &galley.rows {
+            let offset = mesh.vertices.len() as u32;
+            mesh.indices
+                .extend(row.visuals.mesh.indices.iter().map(|i| i + offset));
+            mesh.vertices
+                .extend(row.visuals.mesh.vertices.iter().map(|v| Vertex {
+                    pos: pos + v.pos.to_vec2() * scale,
+                    uv: (v.uv.to_vec2() * uv_normalizer).to_pos2(),
+                    color: v.color,
+                }));
+        }
+        painter.add(mesh);
+    }
+}
+
+/// Show how the row height of each text style changes with `font_defs` compared to the
+/// fonts currently used by the context
+pub(crate) fn line_height_ui(
+    ui: &mut egui::Ui,
+    preview: &mut PreviewFonts,
+    font_defs: &FontDefinitions,
+) {
+    if let Err(e) = preview.prepare(ui.ctx(), font_defs) {
+        ui.label(egui::RichText::new(e).color(egui::Color32::DARK_RED));
+        return;
+    }
+    let styles = ui.style().text_styles.clone();
+    egui::Grid::new("line_height_impact")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Text style");
+            ui.strong("Current");
+            ui.strong("Pending");
+            ui.strong("Change");
+            ui.end_row();
+            for (style, font_id) in &styles {
+                let before = ui.fonts(|f| f.row_height(font_id));
+                ui.label(style.to_string());
+                ui.label(format!("{before:.1}"));
+                match preview.row_height(font_id) {
+                    Some(after) => {
+                        ui.label(format!("{after:.1}"));
+                        let change = after - before;
+                        let text = format!("{change:+.1}");
+                        if change.abs() >= 0.5 {
+                            ui.strong(text);
+                        } else {
+                            ui.label(text);
+                        }
+                    }
+                    None => {
+                        ui.label("—");
+                        ui.label(format!("Family {} is missing", font_id.family));
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// The sample text shown for a family when the user hasn't set one
+pub(crate) fn default_sample_text(family: &egui::FontFamily) -> &'static str {
+    match family {
+        egui::FontFamily::Monospace => "fn main() { let x = 0x2A; }",
+        _ => "The quick brown fox jumps over the lazy dog",
+    }
+}
+
+/// Sample texts for revealing specific aspects of how egui renders a font
+pub(crate) const SAMPLE_PRESETS: &[(&str, &str)] = &[
+    ("Pangram", "The quick brown fox jumps over the lazy dog"),
+    (
+        "Kerning pairs",
+        "AV AW AY LT LV LY PA TA Te To Ty VA Va We Yo",
+    ),
+    (
+        "Programming ligatures",
+        "-> => != == === <= >= :: ++ && || /* */ </>",
+    ),
+    ("Confusable glyphs", "Il1| O0o rn m {} [] () ;: ,."),
+    ("Digits and symbols", "0123456789 +-*/= $€£¥ %#@"),
+];
+
+/// Menu offering [`SAMPLE_PRESETS`]. Returns the picked text.
+pub(crate) fn sample_presets_menu(ui: &mut egui::Ui) -> Option<&'static str> {
+    let mut picked = None;
+    ui.menu_button("▾", |ui| {
+        for (label, text) in SAMPLE_PRESETS {
+            if ui.button(*label).on_hover_text(*text).clicked() {
+                picked = Some(*text);
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Pick a sample text");
+    picked
+}
+
+/// Scales the preview can be rendered at, besides the context's own
+const PPP_CHOICES: [f32; 3] = [1.0, 1.5, 2.0];
+
+/// Let the user pick the scale the preview is rendered at
+pub(crate) fn ppp_toggle_ui(ui: &mut egui::Ui, preview: &mut PreviewFonts) {
+    ui.horizontal(|ui| {
+        let mut ppp = preview.ppp_override();
+        ui.label("Preview scale")
+            .on_hover_text("Render previews at another scale, shown pixel for pixel");
+        ui.selectable_value(&mut ppp, None, "Screen")
+            .on_hover_text(format!(
+                "The scale of this screen ({})",
+                ui.ctx().pixels_per_point()
+            ));
+        for choice in PPP_CHOICES {
+            ui.selectable_value(&mut ppp, Some(choice), format!("{choice:.1}×"));
+        }
+        preview.set_ppp_override(ppp);
+    });
+}
+
+/// Show `text` rendered with `family` at body text size
+pub(crate) fn family_sample_ui(
+    ui: &mut egui::Ui,
+    preview: &mut PreviewFonts,
+    family: &egui::FontFamily,
+    text: &str,
+) -> egui::Response {
+    let size = egui::TextStyle::Body.resolve(ui.style()).size;
+    let color = ui.visuals().text_color();
+    match preview.layout(text, FontId::new(size, family.clone()), color) {
+        Some(galley) => {
+            let size = preview.display_size(ui.ctx(), &galley);
+            let (rect, re) = ui.allocate_exact_size(size, egui::Sense::click());
+            preview.paint(ui.painter(), rect.min, &galley);
+            re
+        }
+        None => ui.weak("No preview available"),
+    }
+}
@@ -0,0 +1,206 @@
+//! Renaming font identifiers while keeping references to them intact
+
+use {
+    crate::{sanitize_font_name, CustomFontPaths, FontNameError},
+    egui::FontDefinitions,
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+/// Reason renaming fonts failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// There is no font with this identifier
+    Missing(String),
+    /// The new identifier is already taken by another font
+    Conflict(String),
+    /// The new identifier isn't a valid font identifier
+    InvalidName(FontNameError),
+}
+
+impl std::fmt::Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "There is no font called {name}"),
+            Self::Conflict(name) => write!(f, "A font called {name} already exists"),
+            Self::InvalidName(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Rename fonts according to `renames` (old identifier → new identifier)
+///
+/// Family entries and custom font paths referring to the old identifiers are updated.
+/// All renames are checked before anything is changed, so on error `font_defs` is left untouched.
+/// Fonts can swap identifiers within a single call.
+pub fn rename_fonts(
+    font_defs: &mut FontDefinitions,
+    custom: Option<&mut CustomFontPaths>,
+    renames: &BTreeMap<String, String>,
+) -> Result<(), RenameError> {
+    let mut new_names = BTreeSet::new();
+    for (old, new) in renames {
+        if !font_defs.font_data.contains_key(old) {
+            return Err(RenameError::Missing(old.clone()));
+        }
+        sanitize_font_name(new, None).map_err(RenameError::InvalidName)?;
+        let taken = font_defs.font_data.contains_key(new) && !renames.contains_key(new);
+        if taken || !new_names.insert(new) {
+            return Err(RenameError::Conflict(new.clone()));
+        }
+    }
+    for (new, data) in take_renamed(renames, |old| font_defs.font_data.remove(old)) {
+        font_defs.font_data.insert(new.clone(), data);
+    }
+    for name in font_defs.families.values_mut().flatten() {
+        if let Some(new) = renames.get(name) {
+            name.clone_from(new);
+        }
+    }
+    if let Some(custom) = custom {
+        for (new, path) in take_renamed(renames, |old| custom.remove(old)) {
+            custom.insert(new.clone(), path);
+        }
+    }
+    Ok(())
+}
+
+/// Take the state kept about the fonts in `renames` with `remove`, paired with their new
+/// identifiers to insert it under. Taking everything first allows fonts to swap identifiers.
+pub(crate) fn take_renamed<V>(
+    renames: &BTreeMap<String, String>,
+    mut remove: impl FnMut(&str) -> Option<V>,
+) -> Vec<(&String, V)> {
+    renames
+        .iter()
+        .filter_map(|(old, new)| Some((new, remove(old)?)))
+        .collect()
+}
+
+/// Form for adding a prefix and/or suffix to the identifiers of selected fonts
+#[derive(Default)]
+pub(crate) struct BatchRenameForm {
+    selected: BTreeSet<String>,
+    prefix: String,
+    suffix: String,
+}
+
+impl BatchRenameForm {
+    /// Show the form. Returns the renames (old identifier → new identifier) the user asked
+    /// for, to be performed with [`rename_fonts`], after which the form should be cleared.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &FontDefinitions,
+    ) -> Option<Result<BTreeMap<String, String>, RenameError>> {
+        self.selected
+            .retain(|name| font_defs.font_data.contains_key(name));
+        ui.horizontal_wrapped(|ui| {
+            for name in font_defs.font_data.keys() {
+                let mut checked = self.selected.contains(name);
+                if ui.checkbox(&mut checked, name).changed() {
+                    if checked {
+                        self.selected.insert(name.clone());
+                    } else {
+                        self.selected.remove(name);
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            let re = ui.add(
+                egui::TextEdit::singleline(&mut self.prefix)
+                    .hint_text("Prefix")
+                    .desired_width(100.0),
+            );
+            crate::ui::a11y_text_label(&re, "Prefix to add to fonts", &self.prefix);
+            let re = ui.add(
+                egui::TextEdit::singleline(&mut self.suffix)
+                    .hint_text("Suffix")
+                    .desired_width(100.0),
+            );
+            crate::ui::a11y_text_label(&re, "Suffix to add to fonts", &self.suffix);
+        });
+        let problem = if self.selected.is_empty() {
+            Some("Select the fonts to rename")
+        } else if self.prefix.is_empty() && self.suffix.is_empty() {
+            Some("Provide a prefix or suffix")
+        } else {
+            None
+        };
+        let mut re = ui.add_enabled(problem.is_none(), egui::Button::new("Rename selected"));
+        if let Some(problem) = problem {
+            re = re.on_disabled_hover_text(problem);
+        }
+        if !re.clicked() {
+            return None;
+        }
+        let renames = self
+            .selected
+            .iter()
+            .map(|name| {
+                let new = format!("{}{name}{}", self.prefix, self.suffix);
+                Ok((name.clone(), sanitize_font_name(&new, None)?))
+            })
+            .collect::<Result<_, FontNameError>>()
+            .map_err(RenameError::InvalidName);
+        Some(renames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{rename_fonts, RenameError},
+        crate::CustomFontPaths,
+        egui::{FontDefinitions, FontFamily},
+        std::collections::BTreeMap,
+    };
+
+    #[test]
+    fn swap() {
+        let mut defs = FontDefinitions::default();
+        let mut custom = CustomFontPaths::default();
+        custom.insert("Hack".into(), "hack.ttf".into());
+        let hack = defs.font_data["Hack"].clone();
+        let renames = BTreeMap::from([
+            ("Hack".into(), "Ubuntu-Light".into()),
+            ("Ubuntu-Light".into(), "Hack".into()),
+        ]);
+        rename_fonts(&mut defs, Some(&mut custom), &renames).unwrap();
+        assert_eq!(defs.font_data["Ubuntu-Light"], hack);
+        assert_eq!(defs.families[&FontFamily::Monospace][0], "Ubuntu-Light");
+        assert_eq!(
+            custom.get("Ubuntu-Light").map(String::as_str),
+            Some("hack.ttf")
+        );
+        assert!(!custom.contains_key("Hack"));
+    }
+
+    #[test]
+    fn errors_leave_defs_untouched() {
+        let mut defs = FontDefinitions::default();
+        let before = defs.clone();
+        let rename = |defs: &mut FontDefinitions, old: &str, new: &str| {
+            rename_fonts(defs, None, &BTreeMap::from([(old.into(), new.into())]))
+        };
+        assert_eq!(
+            rename(&mut defs, "Nope", "New"),
+            Err(RenameError::Missing("Nope".into()))
+        );
+        assert_eq!(
+            rename(&mut defs, "Hack", "Ubuntu-Light"),
+            Err(RenameError::Conflict("Ubuntu-Light".into()))
+        );
+        assert!(matches!(
+            rename(&mut defs, "Hack", ""),
+            Err(RenameError::InvalidName(_))
+        ));
+        assert_eq!(
+            defs.font_data.keys().collect::<Vec<_>>(),
+            before.font_data.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(defs.families, before.families);
+    }
+}
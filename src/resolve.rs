@@ -0,0 +1,172 @@
+//! Loading fonts that several sources could provide, in a configurable order
+
+use {
+    crate::{source, BundledFont, CustomFontPaths, FontSource},
+    egui::{FontData, FontDefinitions},
+    std::sync::Arc,
+};
+
+/// A kind of place a font can be loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontOrigin {
+    /// The path recorded in [`CustomFontPaths`]
+    CustomPath,
+    /// A font compiled into the application with the same identifier, see [`BundledFont`]
+    Bundled,
+    /// An installed font whose name or file name (without extension) is the identifier
+    ///
+    /// Only available with the `system-fonts` feature.
+    System,
+}
+
+impl FontOrigin {
+    /// The order fonts are resolved in by default: the user's choice of file first, then the
+    /// fonts the application ships with, then the installed fonts
+    pub const DEFAULT_PRIORITY: [Self; 3] = [Self::CustomPath, Self::Bundled, Self::System];
+}
+
+impl std::fmt::Display for FontOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::CustomPath => "Custom path",
+            Self::Bundled => "Bundled",
+            Self::System => "Installed font",
+        })
+    }
+}
+
+/// Where a font ended up being loaded from, see [`load_fonts_by_priority`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontResolution {
+    /// Identifier of the font
+    pub name: String,
+    /// The source that provided the font, and where within it (path or bundled font name),
+    /// or `None` if no source could
+    pub winner: Option<(FontOrigin, String)>,
+    /// Sources of higher priority that were tried first, and why they didn't provide the font
+    pub skipped: Vec<(FontOrigin, String)>,
+}
+
+/// Load the fonts in `custom`, and the ones the families refer to that aren't loaded, from the
+/// first source in `priority` that has them
+///
+/// Sources left out of `priority` aren't used. Custom paths are loaded from `source`, or from
+/// the file system if it's `None`. Fonts are inserted into `font_defs` as they are found;
+/// the result tells which source each one came from, so the host can show it (see
+/// [`FontCfgUi::font_resolutions`](crate::FontCfgUi::font_resolutions)) and it's clear why the
+/// same configuration behaves differently on another machine.
+pub fn load_fonts_by_priority(
+    priority: &[FontOrigin],
+    source: Option<&dyn FontSource>,
+    custom: &CustomFontPaths,
+    catalog: &[BundledFont],
+    font_defs: &mut FontDefinitions,
+) -> Vec<FontResolution> {
+    let mut names: Vec<String> = custom.keys().cloned().collect();
+    for name in font_defs.families.values().flatten() {
+        if !font_defs.font_data.contains_key(name) && !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    names.sort();
+    #[cfg(feature = "system-fonts")]
+    let mut system_fonts = None;
+    let mut resolutions = Vec::with_capacity(names.len());
+    for name in names {
+        let mut resolution = FontResolution {
+            name,
+            winner: None,
+            skipped: Vec::new(),
+        };
+        for &origin in priority {
+            let found = match origin {
+                FontOrigin::CustomPath => load_custom(source, custom, catalog, &resolution.name),
+                FontOrigin::Bundled => load_bundled(catalog, &resolution.name),
+                #[cfg(feature = "system-fonts")]
+                FontOrigin::System => {
+                    let fonts = system_fonts.get_or_insert_with(crate::system_fonts);
+                    load_system(fonts, &resolution.name)
+                }
+                #[cfg(not(feature = "system-fonts"))]
+                FontOrigin::System => Err("Needs the system-fonts feature".to_owned()),
+            };
+            match found {
+                Ok((font, location)) => {
+                    font_defs
+                        .font_data
+                        .insert(resolution.name.clone(), Arc::new(font));
+                    resolution.winner = Some((origin, location));
+                    break;
+                }
+                Err(reason) => resolution.skipped.push((origin, reason)),
+            }
+        }
+        resolutions.push(resolution);
+    }
+    resolutions
+}
+
+fn load_custom(
+    source: Option<&dyn FontSource>,
+    custom: &CustomFontPaths,
+    catalog: &[BundledFont],
+    name: &str,
+) -> Result<(FontData, String), String> {
+    let path = custom.get(name).ok_or("No custom path is recorded")?;
+    // Bundled fonts the user added are recorded with a path naming them
+    if let Some(bundled) = crate::catalog::bundled_name(path) {
+        let (font, _) = load_bundled(catalog, bundled)?;
+        return Ok((font, path.clone()));
+    }
+    let font = source::load_custom_font(source, path).map_err(|e| e.message)?;
+    Ok((font, path.clone()))
+}
+
+fn load_bundled(catalog: &[BundledFont], name: &str) -> Result<(FontData, String), String> {
+    let font = catalog
+        .iter()
+        .find(|font| font.name == name)
+        .ok_or("Not among the bundled fonts")?;
+    Ok((FontData::from_static(font.data), font.name.clone()))
+}
+
+#[cfg(feature = "system-fonts")]
+fn load_system(fonts: &[crate::SystemFont], name: &str) -> Result<(FontData, String), String> {
+    let font = fonts
+        .iter()
+        .find(|font| {
+            font.display_name() == name || font.path.file_stem().is_some_and(|stem| stem == name)
+        })
+        .ok_or("No installed font has this name")?;
+    let data = std::fs::read(&font.path).map_err(|e| e.to_string())?;
+    crate::format::check_font_data(&data, font.index)?;
+    let font_data = FontData {
+        index: font.index,
+        ..FontData::from_owned(data)
+    };
+    Ok((font_data, font.path.display().to_string()))
+}
+
+/// Show where the font of `resolution` was loaded from, unless it has been replaced since
+pub(crate) fn resolution_ui(
+    ui: &mut egui::Ui,
+    resolution: &FontResolution,
+    custom_path: Option<&str>,
+) {
+    let Some((origin, location)) = &resolution.winner else {
+        return;
+    };
+    // The user may have pointed the font elsewhere since
+    if *origin == FontOrigin::CustomPath && custom_path != Some(location.as_str()) {
+        return;
+    }
+    let re = ui.weak(format!("Loaded from: {origin} ({location})"));
+    if !resolution.skipped.is_empty() {
+        let skipped: Vec<_> = resolution
+            .skipped
+            .iter()
+            .map(|(origin, reason)| format!("{origin}: {reason}"))
+            .collect();
+        re.on_hover_text(format!("Tried first:\n{}", skipped.join("\n")));
+    }
+}
@@ -0,0 +1,74 @@
+//! A miniature mock ui rendered with the pending font definitions
+
+use {
+    crate::preview::PreviewFonts,
+    egui::{TextStyle, Ui},
+};
+
+/// Show `text` in `style`, as laid out by `preview`
+fn text(ui: &mut Ui, preview: &mut PreviewFonts, text: &str, style: TextStyle) {
+    let font_id = style.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let Some(galley) = preview.layout(text, font_id, color) else {
+        ui.weak(format!("No {style} font"));
+        return;
+    };
+    let (rect, _) = ui.allocate_exact_size(
+        preview.display_size(ui.ctx(), &galley),
+        egui::Sense::hover(),
+    );
+    preview.paint(ui.painter(), rect.min, &galley);
+}
+
+/// Show something that looks like a button labeled `label`
+fn button(ui: &mut Ui, preview: &mut PreviewFonts, label: &str) {
+    let font_id = TextStyle::Button.resolve(ui.style());
+    let visuals = ui.visuals().widgets.inactive;
+    let Some(galley) = preview.layout(label, font_id, visuals.text_color()) else {
+        return;
+    };
+    let padding = ui.spacing().button_padding;
+    let size = preview.display_size(ui.ctx(), &galley) + 2.0 * padding;
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    ui.painter().rect(
+        rect,
+        visuals.rounding,
+        visuals.weak_bg_fill,
+        visuals.bg_stroke,
+    );
+    preview.paint(ui.painter(), rect.min + padding, &galley);
+}
+
+/// Show a mock ui with headings, body text, buttons and a code block, all using the fonts of
+/// `preview`. The preview must have been prepared this frame.
+pub(crate) fn mock_ui(ui: &mut Ui, preview: &mut PreviewFonts) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        text(ui, preview, "Settings", TextStyle::Heading);
+        text(
+            ui,
+            preview,
+            "Choose how the application looks and behaves.",
+            TextStyle::Body,
+        );
+        ui.horizontal(|ui| {
+            button(ui, preview, "Save");
+            button(ui, preview, "Cancel");
+            button(ui, preview, "Restore defaults…");
+        });
+        egui::Frame::none()
+            .fill(ui.visuals().code_bg_color)
+            .inner_margin(4.0)
+            .rounding(2.0)
+            .show(ui, |ui| {
+                text(ui, preview, "fn main() {", TextStyle::Monospace);
+                text(
+                    ui,
+                    preview,
+                    "    println!(\"{}\", 0x2A);",
+                    TextStyle::Monospace,
+                );
+                text(ui, preview, "}", TextStyle::Monospace);
+            });
+        text(ui, preview, "Version 1.0.0 · 12 KiB", TextStyle::Small);
+    });
+}
@@ -0,0 +1,265 @@
+//! Capturing the whole font configuration for persisting it
+
+use {
+    crate::{
+        source,
+        textfmt::{self, ParseError},
+        CustomFontPaths, FamilySpec, FontEntry, FontLoadError, FontSource,
+    },
+    egui::{FontDefinitions, FontFamily},
+    std::{collections::BTreeMap, sync::Arc},
+};
+
+const FORMAT: textfmt::Format = textfmt::Format {
+    header: "egui-fontcfg snapshot 1",
+    what: "snapshot",
+};
+
+/// Everything needed to restore a font configuration, without the font data itself
+///
+/// Fonts are referred to by name, and custom fonts are loaded from their paths again by
+/// [`Self::apply_to_defs`]. Use [`Self::to_text`] and [`Self::parse`] to persist it.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontConfigSnapshot {
+    /// Every font, with the paths of the fonts the user added (see [`CustomFontPaths`])
+    pub fonts: Vec<FontEntry>,
+    /// The fonts of every family, in fallback order
+    pub families: Vec<FamilySpec>,
+}
+
+impl FontConfigSnapshot {
+    /// Capture the configuration of `font_defs` and `custom`
+    pub fn from_defs(font_defs: &FontDefinitions, custom: &CustomFontPaths) -> Self {
+        Self {
+            fonts: FontEntry::from_defs(font_defs, custom),
+            families: FamilySpec::from_defs(font_defs),
+        }
+    }
+    /// Sort [`Self::families`] into `order`, e.g. the one the user arranged them in
+    /// (see [`FontCfgUi::family_display_order`](crate::FontCfgUi::family_display_order))
+    ///
+    /// Families that aren't in `order` keep their relative order, after the others.
+    /// The order is kept by [`Self::to_text`] and [`Self::parse`], and can be read back
+    /// with [`Self::family_order`].
+    pub fn order_families(&mut self, order: &[FontFamily]) {
+        self.families.sort_by_key(|spec| {
+            let family = FontFamily::from(&spec.id);
+            order
+                .iter()
+                .position(|f| *f == family)
+                .unwrap_or(order.len())
+        });
+    }
+    /// The families, in the order of [`Self::families`]
+    pub fn family_order(&self) -> Vec<FontFamily> {
+        self.families.iter().map(|spec| (&spec.id).into()).collect()
+    }
+    /// Restore the configuration onto `font_defs`, which should hold the fonts the
+    /// application registers (e.g. [`FontDefinitions::default`])
+    ///
+    /// Custom fonts are loaded from `source`, or from their files if it's `None`, and their
+    /// paths inserted into `custom`.
+    /// Bundled fonts have to be in `font_defs` already, see
+    /// [`load_bundled_fonts`](crate::load_bundled_fonts).
+    /// Fonts missing from the snapshot are removed, and family entries referring to fonts
+    /// that don't exist are dropped. Nothing is changed if loading a custom font fails.
+    ///
+    /// To let fonts your application adds in newer versions show up, store a
+    /// [`FontConfigDiff`](crate::FontConfigDiff) instead.
+    pub fn apply_to_defs(
+        &self,
+        source: Option<&dyn FontSource>,
+        font_defs: &mut FontDefinitions,
+        custom: &mut CustomFontPaths,
+    ) -> Result<(), FontLoadError> {
+        let mut loaded = BTreeMap::new();
+        for entry in &self.fonts {
+            if let Some(path) = entry
+                .path
+                .as_ref()
+                .filter(|p| !crate::catalog::is_bundled(p))
+            {
+                loaded.insert(entry.name.clone(), source::load_custom_font(source, path)?);
+            }
+        }
+        for (name, data) in loaded {
+            font_defs.font_data.insert(name, Arc::new(data));
+        }
+        let entries: BTreeMap<_, _> = self.fonts.iter().map(|e| (e.name.as_str(), e)).collect();
+        font_defs
+            .font_data
+            .retain(|name, _| entries.contains_key(name.as_str()));
+        for (name, font) in &mut font_defs.font_data {
+            entries[name.as_str()].apply_to(font);
+        }
+        FamilySpec::apply_to_defs(&self.families, font_defs);
+        for fonts in font_defs.families.values_mut() {
+            fonts.retain(|name| font_defs.font_data.contains_key(name));
+        }
+        for entry in &self.fonts {
+            if let Some(path) = &entry.path {
+                custom.insert(entry.name.clone(), path.clone());
+            }
+        }
+        Ok(())
+    }
+    /// Write the snapshot in a line based text format, readable by [`Self::parse`]
+    pub fn to_text(&self) -> String {
+        let mut out = FORMAT.start();
+        for entry in &self.fonts {
+            if let Some(path) = &entry.path {
+                textfmt::push_line(
+                    &mut out,
+                    "custom-font",
+                    [entry.name.as_str(), path.as_str()],
+                );
+            }
+        }
+        for entry in &self.fonts {
+            let index = entry.index.to_string();
+            let tweak = textfmt::tweak_to_fields(&entry.tweaks);
+            textfmt::push_line(
+                &mut out,
+                "font",
+                [entry.name.as_str(), index.as_str()]
+                    .into_iter()
+                    .chain(tweak.iter().map(String::as_str)),
+            );
+        }
+        for spec in &self.families {
+            let family = textfmt::family_to_field(&spec.id);
+            textfmt::push_line(
+                &mut out,
+                "family",
+                std::iter::once(family.as_str()).chain(spec.fonts.iter().map(String::as_str)),
+            );
+        }
+        out
+    }
+    /// Read a snapshot written by [`Self::to_text`]
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut snapshot = Self::default();
+        let mut paths = BTreeMap::new();
+        for line in FORMAT.body(text)? {
+            let (line_no, keyword, fields) = line?;
+            let err = |message: String| ParseError {
+                line: line_no,
+                message,
+            };
+            match (keyword.as_str(), fields.as_slice()) {
+                ("custom-font", [name, path]) => {
+                    paths.insert(name.clone(), path.clone());
+                }
+                ("font", [name, index, tweak @ ..]) => {
+                    let index = textfmt::index_from_field(index).map_err(err)?;
+                    let tweak = textfmt::tweak_from_fields(tweak).map_err(err)?;
+                    snapshot.fonts.push(FontEntry {
+                        name: name.clone(),
+                        path: None,
+                        index,
+                        tweaks: tweak,
+                    });
+                }
+                ("family", [family, fonts @ ..]) => {
+                    let family = textfmt::family_from_field(family).map_err(err)?;
+                    snapshot.families.push(FamilySpec {
+                        id: family,
+                        fonts: fonts.to_vec(),
+                    });
+                }
+                _ => return Err(err(format!("Unexpected line starting with {keyword:?}"))),
+            }
+        }
+        for entry in &mut snapshot.fonts {
+            entry.path = paths.remove(&entry.name);
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::FontConfigSnapshot,
+        crate::{textfmt::ParseError, CustomFontPaths},
+        egui::{FontDefinitions, FontFamily},
+        std::sync::Arc,
+    };
+
+    fn edited_defs() -> (FontDefinitions, CustomFontPaths) {
+        let mut defs = FontDefinitions::default();
+        let hack = defs.font_data["Hack"].clone();
+        let mut custom = Arc::unwrap_or_clone(hack);
+        custom.tweak.scale = 1.25;
+        custom.tweak.y_offset = -0.5;
+        defs.font_data
+            .insert("My\tfont # 1".into(), Arc::new(custom));
+        defs.families.insert(
+            FontFamily::Name("Héadings\n".into()),
+            vec!["My\tfont # 1".into()],
+        );
+        let mut paths = CustomFontPaths::default();
+        paths.insert("My\tfont # 1".into(), "C:\\fonts\\my font.ttf".into());
+        (defs, paths)
+    }
+
+    #[test]
+    fn round_trip() {
+        let (defs, custom) = edited_defs();
+        let snapshot = FontConfigSnapshot::from_defs(&defs, &custom);
+        assert_eq!(FontConfigSnapshot::parse(&snapshot.to_text()), Ok(snapshot));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let (defs, custom) = edited_defs();
+        let snapshot = FontConfigSnapshot::from_defs(&defs, &custom);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FontConfigSnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+
+    #[test]
+    fn error_lines() {
+        let error = |text: &str| FontConfigSnapshot::parse(text).unwrap_err();
+        assert_eq!(
+            error("egui-fontcfg diff 1\n"),
+            ParseError {
+                line: 1,
+                message: "Not an egui-fontcfg snapshot".into(),
+            }
+        );
+        let text = "egui-fontcfg snapshot 1\n# comment\nfont\tHack\t0\t1\t0\t0\t0\nfont\tUbuntu\tx\t1\t0\t0\t0\n";
+        assert_eq!(error(text).line, 4);
+        let text = "egui-fontcfg snapshot 1\n\nbogus\n";
+        assert_eq!(
+            error(text),
+            ParseError {
+                line: 3,
+                message: "Unexpected line starting with \"bogus\"".into(),
+            }
+        );
+    }
+
+    #[cfg(all(feature = "fs", feature = "bitmap-fonts"))]
+    #[test]
+    fn converts_custom_fonts() {
+        let path = std::env::temp_dir().join(format!("egui-fontcfg-{}.bdf", std::process::id()));
+        std::fs::write(&path, crate::bitmap::tests::BDF).unwrap();
+        let path = path.to_str().unwrap().to_owned();
+        let (mut defs, mut custom) = edited_defs();
+        custom.insert("My\tfont # 1".into(), path.clone());
+        let snapshot = FontConfigSnapshot::from_defs(&defs, &custom);
+        let result = snapshot.apply_to_defs(None, &mut defs, &mut custom);
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+        let font = &defs.font_data["My\tfont # 1"];
+        assert!(font.font.starts_with(&[0, 1, 0, 0]));
+        assert_eq!(font.tweak.scale, 1.25);
+        assert_eq!(custom["My\tfont # 1"], path);
+    }
+}
@@ -0,0 +1,195 @@
+//! Loading font data from places other than the file system, e.g. on the web
+
+use {
+    crate::{split_custom_font_path, CustomFontHashes, CustomFontPaths, HashMismatch},
+    egui::FontData,
+    std::{collections::BTreeMap, sync::Arc},
+};
+
+/// Error from [`FontSource::load`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontLoadError {
+    /// The path or identifier of the font that couldn't be loaded
+    pub path: String,
+    /// What went wrong
+    pub message: String,
+}
+
+impl FontLoadError {
+    /// Loading `path` failed because of `message`
+    pub fn new(path: impl Into<String>, message: impl std::fmt::Display) -> Self {
+        Self {
+            path: path.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FontLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Can't load {}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for FontLoadError {}
+
+/// Provides the data of custom fonts, given the path stored in [`CustomFontPaths`]
+///
+/// What the path means is up to the source. It can be a file path, a URL, or the name of an
+/// embedded asset. Implemented for closures taking the path.
+pub trait FontSource {
+    /// Load the font data at `path_or_id`
+    fn load(&self, path_or_id: &str) -> Result<Vec<u8>, FontLoadError>;
+}
+
+impl<F: Fn(&str) -> Result<Vec<u8>, FontLoadError>> FontSource for F {
+    fn load(&self, path_or_id: &str) -> Result<Vec<u8>, FontLoadError> {
+        self(path_or_id)
+    }
+}
+
+/// Reads fonts from the file system
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsFontSource;
+
+#[cfg(feature = "fs")]
+impl FontSource for FsFontSource {
+    fn load(&self, path_or_id: &str) -> Result<Vec<u8>, FontLoadError> {
+        std::fs::read(path_or_id).map_err(|e| FontLoadError::new(path_or_id, e))
+    }
+}
+
+/// Like [`load_custom_fonts`](crate::load_custom_fonts), but loads the fonts from `source`
+pub fn load_custom_fonts_from(
+    source: &dyn FontSource,
+    custom: &CustomFontPaths,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+) -> Result<(), FontLoadError> {
+    for (k, v) in custom
+        .iter()
+        .filter(|(_, v)| !crate::catalog::is_bundled(v))
+    {
+        font_data.insert(k.to_owned(), Arc::new(load_custom_font(Some(source), v)?));
+    }
+    Ok(())
+}
+
+/// Like [`load_custom_fonts_verified`](crate::load_custom_fonts_verified), but loads the fonts
+/// from `source`
+pub fn load_custom_fonts_verified_from(
+    source: &dyn FontSource,
+    custom: &CustomFontPaths,
+    hashes: &CustomFontHashes,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+) -> Result<Vec<HashMismatch>, FontLoadError> {
+    crate::load_verified(custom, hashes, font_data, |path| {
+        load_custom_font(Some(source), path)
+    })
+}
+
+/// Load the custom font recorded as `path` (see [`split_custom_font_path`]) from `source`,
+/// or from the file system if there is no source, converting it if needed
+pub(crate) fn load_custom_font(
+    source: Option<&dyn FontSource>,
+    path: &str,
+) -> Result<FontData, FontLoadError> {
+    let (file, index) = split_custom_font_path(path);
+    let data = load(source, file)?;
+    let data =
+        crate::format::ensure_loadable(data, index).map_err(|e| FontLoadError::new(path, e))?;
+    Ok(FontData {
+        index,
+        ..FontData::from_owned(data)
+    })
+}
+
+/// Load `path` from `source`, or from the file system if there is no source
+pub(crate) fn load(source: Option<&dyn FontSource>, path: &str) -> Result<Vec<u8>, FontLoadError> {
+    match source {
+        Some(source) => source.load(path),
+        #[cfg(feature = "fs")]
+        None => FsFontSource.load(path),
+        #[cfg(not(feature = "fs"))]
+        None => Err(FontLoadError::new(
+            path,
+            "No font source is set, and the fs feature is disabled",
+        )),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::{load_custom_fonts_from, FontLoadError},
+        crate::{custom_font_path, split_custom_font_path, CustomFontPaths},
+        egui::FontDefinitions,
+        std::collections::BTreeMap,
+    };
+
+    /// A font collection of `fonts`, with their table offsets moved to where they end up
+    pub(crate) fn collection(fonts: &[&[u8]]) -> Vec<u8> {
+        let mut out = b"ttcf\0\x01\0\0".to_vec();
+        out.extend((fonts.len() as u32).to_be_bytes());
+        let mut offset = out.len() + fonts.len() * 4;
+        for font in fonts {
+            out.extend((offset as u32).to_be_bytes());
+            offset += font.len().div_ceil(4) * 4;
+        }
+        for font in fonts {
+            let start = out.len();
+            out.extend(*font);
+            out.resize(out.len().div_ceil(4) * 4, 0);
+            let tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+            for i in 0..tables {
+                let pos = start + 12 + i * 16 + 8;
+                let moved =
+                    u32::from_be_bytes(out[pos..pos + 4].try_into().unwrap()) + start as u32;
+                out[pos..pos + 4].copy_from_slice(&moved.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn paths_with_face_index() {
+        assert_eq!(custom_font_path("fonts/a.ttf", 0), "fonts/a.ttf");
+        assert_eq!(custom_font_path("fonts/a.ttc", 2), "fonts/a.ttc#2");
+        for (path, index) in [("a.ttc", 0), ("a.ttc", 3), ("we#ird/a.ttc", 1)] {
+            assert_eq!(
+                split_custom_font_path(&custom_font_path(path, index)),
+                (path, index)
+            );
+        }
+        assert_eq!(split_custom_font_path("a#b.ttf"), ("a#b.ttf", 0));
+        assert_eq!(split_custom_font_path("a.ttf#"), ("a.ttf#", 0));
+    }
+
+    #[test]
+    fn loads_recorded_face() {
+        let defs = FontDefinitions::default();
+        let ttc = collection(&[
+            &defs.font_data["Hack"].font,
+            &defs.font_data["Ubuntu-Light"].font,
+        ]);
+        let source = |path: &str| match path {
+            "fonts.ttc" => Ok(ttc.clone()),
+            _ => Err(FontLoadError::new(path, "Not found")),
+        };
+        let mut custom = CustomFontPaths::default();
+        custom.insert("Text".into(), custom_font_path("fonts.ttc", 1));
+        let mut fonts = BTreeMap::new();
+        load_custom_fonts_from(&source, &custom, &mut fonts).unwrap();
+        let font = &fonts["Text"];
+        assert_eq!(font.index, 1);
+        let face = ttf_parser::Face::parse(&font.font, font.index).unwrap();
+        let family = face
+            .names()
+            .into_iter()
+            .find(|n| n.name_id == ttf_parser::name_id::FAMILY)
+            .and_then(|n| n.to_string());
+        assert_eq!(family.as_deref(), Some("Ubuntu Light"));
+        custom.insert("Text".into(), custom_font_path("fonts.ttc", 2));
+        assert!(load_custom_fonts_from(&source, &custom, &mut fonts).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Runtime cost of the applied font configuration
+
+use {
+    crate::{check_applicable, ApplyError},
+    egui::{epaint::text::Fonts, FontDefinitions},
+    std::{collections::HashSet, time::Duration},
+};
+
+/// Statistics of the font atlas of an egui context
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasStats {
+    /// Size of the atlas texture in texels
+    pub size: [usize; 2],
+    /// Fraction of the atlas that is occupied by rasterized glyphs, between 0 and 1
+    pub fill_ratio: f32,
+}
+
+impl AtlasStats {
+    /// Query the statistics of the font atlas `ctx` is using
+    ///
+    /// Glyphs are rasterized on demand, so occupancy grows as more text is shown.
+    pub fn of(ctx: &egui::Context) -> Self {
+        ctx.fonts(|f| Self {
+            size: f.font_image_size(),
+            fill_ratio: f.font_atlas_fill_ratio(),
+        })
+    }
+}
+
+/// Show how long the last apply took, along with the current atlas statistics
+pub(crate) fn apply_stats_ui(ui: &mut egui::Ui, set_fonts_time: Option<Duration>) {
+    let stats = AtlasStats::of(ui.ctx());
+    ui.horizontal_wrapped(|ui| {
+        match set_fonts_time {
+            Some(time) => ui.label(format!("set_fonts took {:.2} ms", time.as_secs_f64() * 1e3)),
+            None => ui.label("set_fonts time unavailable"),
+        };
+        ui.label(format!(
+            "Atlas {}×{}, {:.0}% used",
+            stats.size[0],
+            stats.size[1],
+            stats.fill_ratio * 100.0
+        ))
+        .on_hover_text("Glyphs are rasterized on demand, so usage grows as more text is shown");
+    });
+}
+
+/// What applying font definitions would do, see [`dry_run_apply`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyReport {
+    /// Why [`apply`](crate::apply) would refuse the definitions, if it would
+    pub error: Option<ApplyError>,
+    /// Fonts that can't be parsed, which would make egui panic when they're used
+    pub unparseable: Vec<String>,
+    /// Fonts that aren't in any family, which take up memory for nothing
+    pub unused: Vec<String>,
+    /// Total size of the font files the context would hold, in bytes
+    pub font_bytes: usize,
+    /// Estimated atlas after showing printable ASCII in every default text style,
+    /// or `None` if the definitions can't be applied
+    pub atlas: Option<AtlasStats>,
+}
+
+impl ApplyReport {
+    /// Whether the definitions can be applied safely
+    pub fn can_apply(&self) -> bool {
+        self.error.is_none() && self.unparseable.is_empty()
+    }
+    /// Estimated size of the atlas texture in bytes, with 4 bytes per texel
+    pub fn atlas_bytes(&self) -> Option<usize> {
+        self.atlas.map(|atlas| atlas.size[0] * atlas.size[1] * 4)
+    }
+}
+
+/// Check what applying `font_defs` would do, without touching any egui context
+///
+/// Useful for gating the real apply behind a confirmation of your own. The atlas estimate
+/// assumes one pixel per point and the default text styles.
+pub fn dry_run_apply(font_defs: &FontDefinitions) -> ApplyReport {
+    let error = check_applicable(font_defs).err();
+    let unparseable: Vec<_> = font_defs
+        .font_data
+        .iter()
+        .filter(|(_, font)| ttf_parser::Face::parse(&font.font, font.index).is_err())
+        .map(|(name, _)| name.clone())
+        .collect();
+    let unused = font_defs
+        .font_data
+        .keys()
+        .filter(|name| !font_defs.families.values().flatten().any(|n| n == *name))
+        .cloned()
+        .collect();
+    let mut seen = HashSet::new();
+    let font_bytes = font_defs
+        .font_data
+        .values()
+        .filter(|font| seen.insert(font.font.as_ptr()))
+        .map(|font| font.font.len())
+        .sum();
+    let atlas = (error.is_none() && unparseable.is_empty()).then(|| {
+        let fonts = Fonts::new(1.0, 8192, font_defs.clone());
+        let ascii: String = (' '..='~').collect();
+        for font_id in egui::Style::default().text_styles.values() {
+            if fonts.families().contains(&font_id.family) {
+                fonts.layout_no_wrap(ascii.clone(), font_id.clone(), egui::Color32::WHITE);
+            }
+        }
+        AtlasStats {
+            size: fonts.font_image_size(),
+            fill_ratio: fonts.font_atlas_fill_ratio(),
+        }
+    });
+    ApplyReport {
+        error,
+        unparseable,
+        unused,
+        font_bytes,
+        atlas,
+    }
+}
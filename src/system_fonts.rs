@@ -0,0 +1,261 @@
+//! Enumerating the fonts installed on the system
+
+use {
+    crate::{info::name_string, is_font_file, Spawner},
+    std::{
+        io::{Read, Seek, SeekFrom},
+        path::{Path, PathBuf},
+        sync::mpsc,
+        time::Duration,
+    },
+};
+
+/// A font face installed on the system, see [`system_fonts`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemFont {
+    /// Family name, e.g. `Noto Sans CJK JP`
+    pub family: String,
+    /// Style within the family, e.g. `Bold`
+    pub style: String,
+    /// The font file
+    pub path: PathBuf,
+    /// Index of the face within the file, for font collections
+    pub index: u32,
+}
+
+impl SystemFont {
+    /// Family and style, e.g. `Noto Sans CJK JP Bold`
+    pub fn display_name(&self) -> String {
+        if self.style.is_empty() || self.style == "Regular" {
+            self.family.clone()
+        } else {
+            format!("{} {}", self.family, self.style)
+        }
+    }
+}
+
+/// The directories fonts are usually installed to on this platform
+pub fn system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        if let Some(windir) = std::env::var_os("WINDIR") {
+            dirs.push(Path::new(&windir).join("Fonts"));
+        }
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join("Microsoft/Windows/Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push("/System/Library/Fonts".into());
+        dirs.push("/Library/Fonts".into());
+        dirs.extend(home.map(|home| home.join("Library/Fonts")));
+    } else {
+        dirs.push("/usr/share/fonts".into());
+        dirs.push("/usr/local/share/fonts".into());
+        if let Some(home) = home {
+            dirs.push(home.join(".local/share/fonts"));
+            dirs.push(home.join(".fonts"));
+        }
+    }
+    dirs
+}
+
+/// Collect the font files under `dir`, not descending deeper than `depth` directories
+fn collect_files(dir: &Path, depth: u32, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                collect_files(&path, depth - 1, out);
+            }
+        } else if is_font_file(&path) {
+            out.push(path);
+        }
+    }
+}
+
+/// Read `len` bytes at `offset` of `file`, failing on lengths no font header or name table has
+fn read_at(file: &mut std::fs::File, offset: u32, len: usize) -> Option<Vec<u8>> {
+    const MAX_LEN: usize = 1 << 20;
+    if len > MAX_LEN {
+        return None;
+    }
+    file.seek(SeekFrom::Start(offset.into())).ok()?;
+    let mut buf = vec![0; len];
+    file.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Read a big endian `u16` or `u32` at `at`
+fn be<const N: usize>(data: &[u8], at: usize) -> Option<u32> {
+    let bytes: [u8; N] = data.get(at..at + N)?.try_into().ok()?;
+    Some(bytes.iter().fold(0, |n, &b| (n << 8) | u32::from(b)))
+}
+
+/// The `name` table of the face whose table directory is at `offset`
+fn name_table(file: &mut std::fs::File, offset: u32) -> Option<Vec<u8>> {
+    let header = read_at(file, offset, 12)?;
+    let tables = be::<2>(&header, 4)? as usize;
+    let records = read_at(file, offset.checked_add(12)?, tables * 16)?;
+    let record = records.chunks_exact(16).find(|r| r.starts_with(b"name"))?;
+    read_at(file, be::<4>(record, 8)?, be::<4>(record, 12)? as usize)
+}
+
+/// The faces in the font file at `path`
+///
+/// Only the table directories and `name` tables are read, not the whole file.
+fn faces_in(path: &Path) -> Vec<SystemFont> {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let Some(header) = read_at(&mut file, 0, 12) else {
+        return Vec::new();
+    };
+    let offsets = if header.starts_with(b"ttcf") {
+        let count = be::<4>(&header, 8).unwrap_or(0) as usize;
+        read_at(&mut file, 12, count.saturating_mul(4))
+            .map(|offsets| {
+                offsets
+                    .chunks_exact(4)
+                    .filter_map(|offset| be::<4>(offset, 0))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        vec![0]
+    };
+    offsets
+        .into_iter()
+        .zip(0..)
+        .filter_map(|(offset, index)| {
+            let data = name_table(&mut file, offset)?;
+            let names = ttf_parser::name::Table::parse(&data)?.names;
+            let family = name_string(names, ttf_parser::name_id::TYPOGRAPHIC_FAMILY)
+                .or_else(|| name_string(names, ttf_parser::name_id::FAMILY))?;
+            let style = name_string(names, ttf_parser::name_id::TYPOGRAPHIC_SUBFAMILY)
+                .or_else(|| name_string(names, ttf_parser::name_id::SUBFAMILY))
+                .unwrap_or_default();
+            Some(SystemFont {
+                family,
+                style,
+                path: path.to_owned(),
+                index,
+            })
+        })
+        .collect()
+}
+
+/// Find the fonts installed on the system, sorted by family and style
+///
+/// This looks into every font file in [`system_font_dirs`], so it can take a while.
+pub fn system_fonts() -> Vec<SystemFont> {
+    let mut files = Vec::new();
+    for dir in system_font_dirs() {
+        collect_files(&dir, 8, &mut files);
+    }
+    files.sort();
+    files.dedup();
+    let mut fonts: Vec<_> = files.iter().flat_map(|path| faces_in(path)).collect();
+    fonts.sort_by(|a, b| (&a.family, &a.style).cmp(&(&b.family, &b.style)));
+    fonts
+}
+
+/// Searchable list of the system fonts, for the add new font form
+#[derive(Default)]
+pub(crate) struct SystemFontPicker {
+    /// Enumerated in the background the first time the picker is shown
+    fonts: Option<Vec<SystemFont>>,
+    scan: Option<mpsc::Receiver<Vec<SystemFont>>>,
+    search: String,
+}
+
+impl SystemFontPicker {
+    /// Show the picker, enumerating the fonts on `spawner`. Returns the font the user picked.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, spawner: &Spawner) -> Option<SystemFont> {
+        if self.fonts.is_none() {
+            let scan = self
+                .scan
+                .get_or_insert_with(|| crate::loading::spawn(spawner, system_fonts));
+            match scan.try_recv() {
+                Ok(fonts) => self.fonts = Some(fonts),
+                Err(mpsc::TryRecvError::Empty) => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Looking for installed fonts…");
+                    });
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                    return None;
+                }
+                // The scan panicked
+                Err(mpsc::TryRecvError::Disconnected) => self.fonts = Some(Vec::new()),
+            }
+            self.scan = None;
+        }
+        let fonts = self.fonts.as_ref()?;
+        if fonts.is_empty() {
+            ui.weak("No system fonts found");
+            return None;
+        }
+        let re = ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+        crate::ui::a11y_text_label(&re, "Search system fonts", &self.search);
+        let search = self.search.to_lowercase();
+        let mut picked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("system_fonts")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for font in fonts
+                    .iter()
+                    .filter(|f| f.display_name().to_lowercase().contains(&search))
+                {
+                    let re = ui
+                        .button(font.display_name())
+                        .on_hover_text(font.path.display().to_string());
+                    if re.clicked() {
+                        picked = Some(font.clone());
+                    }
+                }
+            });
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::faces_in,
+        crate::{source::tests::collection, FontInfo},
+        egui::FontDefinitions,
+    };
+
+    #[test]
+    fn reads_faces_of_collections() {
+        let defs = FontDefinitions::default();
+        let fonts = [
+            &*defs.font_data["Hack"].font,
+            &defs.font_data["Ubuntu-Light"].font,
+        ];
+        let path = std::env::temp_dir().join("egui-fontcfg-test-faces.ttc");
+        std::fs::write(&path, collection(&fonts)).unwrap();
+        let faces = faces_in(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(faces.len(), 2);
+        for (face, (data, index)) in faces.iter().zip(fonts.iter().zip(0..)) {
+            let info = FontInfo::from_bytes(data, 0).unwrap();
+            assert_eq!(Some(&face.family), info.family.as_ref());
+            assert_eq!(face.index, index);
+        }
+    }
+
+    #[test]
+    fn skips_malformed_files() {
+        let path = std::env::temp_dir().join("egui-fontcfg-test-malformed.ttc");
+        std::fs::write(&path, b"ttcf\0\x01\0\0\xff\xff\xff\xff\0\0\0\x10").unwrap();
+        let faces = faces_in(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(faces.is_empty());
+    }
+}
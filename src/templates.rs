@@ -0,0 +1,212 @@
+//! Starting points for new named families
+
+use {
+    crate::FontConfigMut,
+    egui::{FontDefinitions, FontFamily},
+};
+
+/// What a new family is initialized with by [`new_family`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyTemplate {
+    /// The fonts of the proportional family
+    CopyOfProportional,
+    /// The fonts of the monospace family
+    CopyOfMonospace,
+    /// No fonts. Text using the family panics until fonts are added to it.
+    Empty,
+    /// The given font, falling back to the fonts of the proportional family
+    /// for characters it doesn't cover
+    PrimaryWithFallback(String),
+}
+
+impl FamilyTemplate {
+    /// The fonts a family created from this template starts with
+    pub fn fonts(&self, font_defs: &FontDefinitions) -> Vec<String> {
+        let family_fonts = |family| font_defs.families.get(&family).cloned().unwrap_or_default();
+        match self {
+            Self::CopyOfProportional => family_fonts(FontFamily::Proportional),
+            Self::CopyOfMonospace => family_fonts(FontFamily::Monospace),
+            Self::Empty => Vec::new(),
+            Self::PrimaryWithFallback(primary) => {
+                let mut fonts = family_fonts(FontFamily::Proportional);
+                fonts.retain(|f| f != primary);
+                fonts.insert(0, primary.clone());
+                fonts
+            }
+        }
+    }
+    fn label(&self) -> &'static str {
+        match self {
+            Self::CopyOfProportional => "Copy of Proportional",
+            Self::CopyOfMonospace => "Copy of Monospace",
+            Self::Empty => "Empty",
+            Self::PrimaryWithFallback(_) => "Font with fallback",
+        }
+    }
+}
+
+/// Add the named family `name`, initialized from `template`
+///
+/// Returns `false` without changing anything if the family already exists.
+pub fn new_family(font_defs: &mut FontDefinitions, name: &str, template: &FamilyTemplate) -> bool {
+    let family = FontFamily::Name(name.into());
+    if font_defs.families.contains_key(&family) {
+        return false;
+    }
+    let fonts = template.fonts(font_defs);
+    font_defs.families.insert(family, fonts);
+    true
+}
+
+/// Reason renaming a family failed, see [`rename_family`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyRenameError {
+    /// Only named families can be renamed, not the built-in ones
+    NotNamed,
+    /// There is no such family
+    Missing(FontFamily),
+    /// The new name is empty
+    EmptyName,
+    /// A family with the new name already exists
+    Conflict(String),
+}
+
+impl std::fmt::Display for FamilyRenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotNamed => write!(f, "Only named families can be renamed"),
+            Self::Missing(family) => write!(f, "There is no {family} family"),
+            Self::EmptyName => write!(f, "Provide a name"),
+            Self::Conflict(name) => write!(f, "A family called {name} already exists"),
+        }
+    }
+}
+
+impl std::error::Error for FamilyRenameError {}
+
+/// Rename the named family `family` to `new_name`, keeping its fonts
+///
+/// Renaming a family to its current name does nothing. Remember that the application looks up
+/// named families by name, so it won't find a renamed family unless it's told about the new name.
+pub fn rename_family(
+    font_defs: &mut FontDefinitions,
+    family: &FontFamily,
+    new_name: &str,
+) -> Result<(), FamilyRenameError> {
+    let FontFamily::Name(old_name) = family else {
+        return Err(FamilyRenameError::NotNamed);
+    };
+    if !font_defs.families.contains_key(family) {
+        return Err(FamilyRenameError::Missing(family.clone()));
+    }
+    if new_name.is_empty() {
+        return Err(FamilyRenameError::EmptyName);
+    }
+    if **old_name == *new_name {
+        return Ok(());
+    }
+    let new_family = FontFamily::Name(new_name.into());
+    if font_defs.families.contains_key(&new_family) {
+        return Err(FamilyRenameError::Conflict(new_name.to_owned()));
+    }
+    if let Some(fonts) = font_defs.families.remove(family) {
+        font_defs.families.insert(new_family, fonts);
+    }
+    Ok(())
+}
+
+/// Form for creating a named family from a template
+pub(crate) struct NewFamilyForm {
+    name: String,
+    template: FamilyTemplate,
+}
+
+impl Default for NewFamilyForm {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            template: FamilyTemplate::CopyOfProportional,
+        }
+    }
+}
+
+impl NewFamilyForm {
+    /// Show the form. Returns the error message if creating the family failed.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+    ) -> Option<String> {
+        let re = ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Family name"));
+        crate::ui::a11y_text_label(&re, "Name of new family", &self.name);
+        let primary = match &self.template {
+            FamilyTemplate::PrimaryWithFallback(name) => name.clone(),
+            _ => font_defs
+                .font_data
+                .keys()
+                .next()
+                .cloned()
+                .unwrap_or_default(),
+        };
+        ui.horizontal_wrapped(|ui| {
+            for template in [
+                FamilyTemplate::CopyOfProportional,
+                FamilyTemplate::CopyOfMonospace,
+                FamilyTemplate::PrimaryWithFallback(primary.clone()),
+                FamilyTemplate::Empty,
+            ] {
+                let selected =
+                    std::mem::discriminant(&template) == std::mem::discriminant(&self.template);
+                let label = template.label();
+                if ui.selectable_label(selected, label).clicked() {
+                    self.template = template;
+                }
+            }
+        });
+        if let FamilyTemplate::PrimaryWithFallback(primary) = &mut self.template {
+            egui::ComboBox::from_label("Primary font")
+                .selected_text(primary.as_str())
+                .show_ui(ui, |ui| {
+                    for name in font_defs.font_data.keys() {
+                        ui.selectable_value(primary, name.clone(), name.as_str());
+                    }
+                });
+        }
+        let fonts = self.template.fonts(font_defs);
+        if fonts.is_empty() {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Text using an empty family panics until fonts are added to it",
+            );
+        } else {
+            ui.weak(fonts.join(", "));
+        }
+        let name = self.name.trim();
+        let problem = if name.is_empty() {
+            Some("Provide a name")
+        } else if font_defs
+            .families
+            .contains_key(&FontFamily::Name(name.into()))
+        {
+            Some("A family with this name already exists")
+        } else if self.template == FamilyTemplate::PrimaryWithFallback(String::new()) {
+            Some("Pick a primary font")
+        } else {
+            None
+        };
+        let mut re = ui.add_enabled(problem.is_none(), egui::Button::new("Create family"));
+        if let Some(problem) = problem {
+            re = re.on_disabled_hover_text(problem);
+        }
+        if !re.clicked() {
+            return None;
+        }
+        match FontConfigMut::new(font_defs, None).add_family(name, &self.template) {
+            Ok(_) => {
+                self.name.clear();
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        }
+    }
+}
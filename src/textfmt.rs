@@ -0,0 +1,223 @@
+//! Building blocks of the line based text formats the crate reads and writes
+//!
+//! Each line is a keyword followed by tab separated fields. Tabs, newlines and backslashes
+//! inside fields are escaped with backslashes. Empty lines and lines starting with `#` are
+//! ignored.
+
+use crate::{FamilyId, Tweaks};
+
+/// Error from parsing one of the crate's text formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line the error is on
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub(crate) fn escape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub(crate) fn unescape(field: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(c) => return Err(format!("Unknown escape sequence \\{c}")),
+            None => return Err("Unfinished escape sequence".into()),
+        }
+    }
+    Ok(out)
+}
+
+/// Write a line from a keyword and fields, escaping the fields
+pub(crate) fn push_line<'a>(
+    out: &mut String,
+    keyword: &str,
+    fields: impl IntoIterator<Item = &'a str>,
+) {
+    out.push_str(keyword);
+    for field in fields {
+        out.push('\t');
+        out.push_str(&escape(field));
+    }
+    out.push('\n');
+}
+
+/// A meaningful line: its 1-based line number, keyword and unescaped fields
+pub(crate) type Line = (usize, String, Vec<String>);
+
+/// The meaningful lines of `text`
+pub(crate) fn lines(text: &str) -> impl Iterator<Item = Result<Line, ParseError>> + '_ {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(line_no, line)| {
+            let mut parts = line.split('\t');
+            let keyword = parts.next().unwrap_or_default().to_owned();
+            let fields = parts
+                .map(unescape)
+                .collect::<Result<_, _>>()
+                .map_err(|message| ParseError {
+                    line: line_no,
+                    message,
+                })?;
+            Ok((line_no, keyword, fields))
+        })
+}
+
+/// A format whose first meaningful line is a header naming it and its version
+pub(crate) struct Format {
+    /// The header line
+    pub(crate) header: &'static str,
+    /// What the format holds, for error messages
+    pub(crate) what: &'static str,
+}
+
+impl Format {
+    /// The start of a text in this format
+    pub(crate) fn start(&self) -> String {
+        format!("{}\n", self.header)
+    }
+    /// The meaningful lines of `text` after the header, failing if it doesn't start with it
+    pub(crate) fn body<'a>(
+        &self,
+        text: &'a str,
+    ) -> Result<impl Iterator<Item = Result<Line, ParseError>> + 'a, ParseError> {
+        let mut lines = lines(text);
+        let (line, keyword, fields) = lines.next().unwrap_or(Ok((1, String::new(), Vec::new())))?;
+        if keyword != self.header || !fields.is_empty() {
+            return Err(ParseError {
+                line,
+                message: format!("Not an egui-fontcfg {}", self.what),
+            });
+        }
+        Ok(lines)
+    }
+}
+
+pub(crate) fn family_to_field(family: &FamilyId) -> String {
+    match family {
+        FamilyId::Proportional => "proportional".into(),
+        FamilyId::Monospace => "monospace".into(),
+        FamilyId::Named(name) => format!("named:{name}"),
+    }
+}
+
+pub(crate) fn family_from_field(field: &str) -> Result<FamilyId, String> {
+    match field {
+        "proportional" => Ok(FamilyId::Proportional),
+        "monospace" => Ok(FamilyId::Monospace),
+        _ => field
+            .strip_prefix("named:")
+            .map(|name| FamilyId::Named(name.into()))
+            .ok_or_else(|| format!("Unknown family {field:?}")),
+    }
+}
+
+pub(crate) fn tweak_to_fields(tweak: &Tweaks) -> [String; 4] {
+    [
+        tweak.scale.to_string(),
+        tweak.y_offset_factor.to_string(),
+        tweak.y_offset.to_string(),
+        tweak.baseline_offset_factor.to_string(),
+    ]
+}
+
+pub(crate) fn tweak_from_fields(fields: &[String]) -> Result<Tweaks, String> {
+    let [scale, y_offset_factor, y_offset, baseline_offset_factor] = fields else {
+        return Err("Expected 4 tweak values".into());
+    };
+    let parse = |s: &String| {
+        s.parse::<f32>()
+            .map_err(|_| format!("{s:?} is not a number"))
+    };
+    Ok(Tweaks {
+        scale: parse(scale)?,
+        y_offset_factor: parse(y_offset_factor)?,
+        y_offset: parse(y_offset)?,
+        baseline_offset_factor: parse(baseline_offset_factor)?,
+    })
+}
+
+pub(crate) fn index_from_field(field: &str) -> Result<u32, String> {
+    field
+        .parse()
+        .map_err(|_| format!("{field:?} is not a valid face index"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, lines, unescape, Format, ParseError};
+
+    const FORMAT: Format = Format {
+        header: "egui-fontcfg test 1",
+        what: "test",
+    };
+
+    #[test]
+    fn escape_round_trip() {
+        let field = "a\tb\nc\\d\re";
+        assert!(!escape(field).contains(['\t', '\n']));
+        assert_eq!(unescape(&escape(field)).unwrap(), field);
+        assert!(unescape("\\x").is_err());
+        assert!(unescape("\\").is_err());
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let text = "# comment\n\nfont\ta\\tb\n";
+        let lines: Vec<_> = lines(text).collect::<Result<_, _>>().unwrap();
+        assert_eq!(lines, [(3, "font".into(), vec!["a\tb".into()])]);
+    }
+
+    #[test]
+    fn header() {
+        let text = format!("{}font\ta\n", FORMAT.start());
+        let body: Vec<_> = FORMAT.body(&text).unwrap().collect();
+        assert_eq!(body, [Ok((2, "font".into(), vec!["a".into()]))]);
+        let not_it = ParseError {
+            line: 2,
+            message: "Not an egui-fontcfg test".into(),
+        };
+        assert_eq!(FORMAT.body("\negui-fontcfg other 1\n").err(), Some(not_it));
+        assert_eq!(FORMAT.body("").err().map(|e| e.line), Some(1));
+        // Bad escapes are reported on their own line
+        let text = format!("{}font\ta\\q\n", FORMAT.start());
+        assert_eq!(
+            FORMAT.body(&text).unwrap().last(),
+            Some(Err(ParseError {
+                line: 2,
+                message: "Unknown escape sequence \\q".into(),
+            }))
+        );
+    }
+}
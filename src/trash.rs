@@ -0,0 +1,190 @@
+//! Fonts removed during the session, kept around so they can be restored
+
+#[cfg(feature = "compression")]
+use std::sync::mpsc;
+use {
+    crate::CustomFontPaths,
+    egui::{FontData, FontDefinitions},
+    std::sync::Arc,
+};
+
+struct TrashedFont {
+    /// Tells the font apart from others of the same name, for finished compressions
+    #[cfg(feature = "compression")]
+    id: u64,
+    name: String,
+    data: TrashedData,
+    path: Option<String>,
+    /// Whether its data is being compressed in the background
+    #[cfg(feature = "compression")]
+    compressing: bool,
+}
+
+enum TrashedData {
+    Loaded(Arc<FontData>),
+    /// Data that nothing else referred to anymore, compressed to save memory
+    #[cfg(feature = "compression")]
+    Compressed {
+        data: Vec<u8>,
+        index: u32,
+        tweak: egui::FontTweak,
+    },
+}
+
+impl TrashedData {
+    fn restore(self) -> Result<Arc<FontData>, String> {
+        match self {
+            Self::Loaded(data) => Ok(data),
+            #[cfg(feature = "compression")]
+            Self::Compressed { data, index, tweak } => Ok(Arc::new(FontData {
+                index,
+                tweak,
+                ..FontData::from_owned(crate::decompress_font_data(&data)?)
+            })),
+        }
+    }
+}
+
+/// Compressed data of the trashed font with this id
+#[cfg(feature = "compression")]
+type Compressed = (u64, Vec<u8>);
+
+/// How many removed fonts are kept, the oldest ones are dropped first
+///
+/// With live apply, the trash is never cleared by applying, so it needs a limit.
+const MAX_FONTS: usize = 32;
+
+/// Removed fonts, most recently removed last
+#[derive(Default)]
+pub(crate) struct Trash {
+    fonts: Vec<TrashedFont>,
+    #[cfg(feature = "compression")]
+    next_id: u64,
+    #[cfg(feature = "compression")]
+    compressed: Option<(mpsc::Sender<Compressed>, mpsc::Receiver<Compressed>)>,
+}
+
+impl Trash {
+    pub(crate) fn push(&mut self, name: String, data: Arc<FontData>, path: Option<String>) {
+        #[cfg(feature = "compression")]
+        {
+            self.next_id += 1;
+        }
+        self.fonts.push(TrashedFont {
+            #[cfg(feature = "compression")]
+            id: self.next_id,
+            name,
+            data: TrashedData::Loaded(data),
+            path,
+            #[cfg(feature = "compression")]
+            compressing: false,
+        });
+        if self.fonts.len() > MAX_FONTS {
+            self.fonts.remove(0);
+        }
+    }
+    /// Compress the data of fonts that only the trash still refers to on `spawner`, and
+    /// take the data compressed since the last call
+    ///
+    /// Fonts compiled into the application are left alone, as they don't take up memory.
+    #[cfg(feature = "compression")]
+    pub(crate) fn compact(&mut self, spawner: &crate::Spawner) {
+        let (sender, receiver) = self.compressed.get_or_insert_with(mpsc::channel);
+        while let Ok((id, compressed)) = receiver.try_recv() {
+            // The font may have been restored or dropped in the meantime
+            let Some(font) = self.fonts.iter_mut().find(|f| f.id == id) else {
+                continue;
+            };
+            let TrashedData::Loaded(data) = &font.data else {
+                continue;
+            };
+            font.data = TrashedData::Compressed {
+                data: compressed,
+                index: data.index,
+                tweak: data.tweak,
+            };
+        }
+        for font in &mut self.fonts {
+            let TrashedData::Loaded(data) = &font.data else {
+                continue;
+            };
+            if font.compressing
+                || Arc::strong_count(data) > 1
+                || matches!(data.font, std::borrow::Cow::Borrowed(_))
+            {
+                continue;
+            }
+            font.compressing = true;
+            let (id, data, sender) = (font.id, data.clone(), sender.clone());
+            spawner(Box::new(move || {
+                let _ = sender.send((id, crate::compress_font_data(&data.font)));
+            }));
+        }
+    }
+    pub(crate) fn clear(&mut self) {
+        self.fonts.clear();
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.fonts.len()
+    }
+    /// Show the removed fonts with restore buttons. Returns the error message if restoring failed.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> Option<String> {
+        let mut restore = None;
+        for (idx, font) in self.fonts.iter().enumerate().rev() {
+            ui.horizontal(|ui| {
+                ui.label(&font.name);
+                if ui.button("Restore").clicked() {
+                    restore = Some(idx);
+                }
+            });
+        }
+        if ui.button("Empty trash").clicked() {
+            self.clear();
+        }
+        let idx = restore?;
+        if font_defs.font_data.contains_key(&self.fonts[idx].name) {
+            return Some(format!(
+                "Can't restore {}: a font with that name exists",
+                self.fonts[idx].name
+            ));
+        }
+        let font = self.fonts.remove(idx);
+        let data = match font.data.restore() {
+            Ok(data) => data,
+            Err(e) => return Some(format!("Can't restore {}: {e}", font.name)),
+        };
+        if let (Some(custom), Some(path)) = (custom, font.path) {
+            custom.insert(font.name.clone(), path);
+        }
+        font_defs.font_data.insert(font.name, data);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Trash, MAX_FONTS},
+        egui::FontData,
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn drops_the_oldest_fonts() {
+        let mut trash = Trash::default();
+        let data = Arc::new(FontData::from_static(b""));
+        for i in 0..MAX_FONTS + 2 {
+            trash.push(format!("font-{i}"), data.clone(), None);
+        }
+        assert_eq!(trash.len(), MAX_FONTS);
+        assert_eq!(trash.fonts[0].name, "font-2");
+    }
+}
@@ -0,0 +1,92 @@
+//! Editing [`egui::FontTweak`]s
+
+use {
+    egui::{FontDefinitions, FontFamily},
+    std::sync::Arc,
+};
+
+/// A relative adjustment applied to the tweaks of several fonts at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TweakAdjustment {
+    /// Factor the scale of each font is multiplied with
+    pub scale_factor: f32,
+    /// Added to the `y_offset_factor` of each font
+    pub y_offset_factor_delta: f32,
+    /// Leave the first (primary) font of the family alone, and only adjust the fallbacks
+    pub skip_primary: bool,
+}
+
+impl Default for TweakAdjustment {
+    fn default() -> Self {
+        Self {
+            scale_factor: 1.0,
+            y_offset_factor_delta: 0.0,
+            skip_primary: false,
+        }
+    }
+}
+
+/// Apply `adjustment` to the tweaks of all fonts in `family`
+///
+/// Tweaks belong to the font data, so fonts that are also part of other families are affected
+/// there as well. Each font is only adjusted once, even if it appears in the family multiple times.
+pub fn adjust_family_tweaks(
+    font_defs: &mut FontDefinitions,
+    family: &FontFamily,
+    adjustment: TweakAdjustment,
+) {
+    let Some(fonts) = font_defs.families.get(family) else {
+        return;
+    };
+    let skip = usize::from(adjustment.skip_primary);
+    let mut done = Vec::new();
+    for name in fonts.iter().skip(skip) {
+        if done.contains(name) {
+            continue;
+        }
+        if let Some(font) = font_defs.font_data.get_mut(name) {
+            let tweak = &mut Arc::make_mut(font).tweak;
+            tweak.scale *= adjustment.scale_factor;
+            tweak.y_offset_factor += adjustment.y_offset_factor_delta;
+        }
+        done.push(name.clone());
+    }
+}
+
+/// State of the bulk tweak form of a family
+pub(crate) struct BulkTweakForm {
+    pub(crate) family: FontFamily,
+    adjustment: TweakAdjustment,
+}
+
+impl BulkTweakForm {
+    pub(crate) fn new(family: FontFamily) -> Self {
+        Self {
+            family,
+            adjustment: TweakAdjustment::default(),
+        }
+    }
+    /// Show the form. Returns the adjustment to apply, if the user requested it.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) -> Option<TweakAdjustment> {
+        let adj = &mut self.adjustment;
+        ui.horizontal(|ui| {
+            ui.label("Scale ×");
+            ui.add(
+                egui::DragValue::new(&mut adj.scale_factor)
+                    .speed(0.01)
+                    .range(0.1..=10.0),
+            );
+            ui.label("Y offset +");
+            ui.add(
+                egui::DragValue::new(&mut adj.y_offset_factor_delta)
+                    .speed(0.005)
+                    .range(-1.0..=1.0),
+            );
+        });
+        ui.checkbox(&mut adj.skip_primary, "Only adjust fallback fonts");
+        ui.button("Adjust all fonts in family")
+            .on_hover_text("Fonts are shared, so families using the same fonts are affected too")
+            .clicked()
+            .then_some(*adj)
+    }
+}
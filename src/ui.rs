@@ -0,0 +1,3288 @@
+//! The font configuration dialog
+
+use {
+    crate::{
+        adjust_family_tweaks, adopt, apply_fallback_exclusions, apply_ref, calibrate, catalog,
+        check_applicable, config_diff, confirm, coverage, custom_font_path, details, diagnose,
+        diff_font_defs, find_duplicate_font, find_renamed_builtin_fonts, font_usage, format,
+        glyph_diff, health, help, info, is_font_file, journal, live_preview, loading, metrics,
+        monitors, namespace, namespaced, notify, preset_conflicts, presets, preview,
+        reload_font_data, remap_renamed_builtin_fonts,
+        rename::{self, take_renamed},
+        resolve, resolve_preset_conflicts, sandbox, sanitize_font_name, source,
+        split_custom_font_path, stats, templates, trash, tweaks, undo, usage, validate, variants,
+        zoom, BundledFont, ConfirmationPolicy, ConflictResolution, CustomFontBytes,
+        CustomFontPaths, DefsFingerprint, FallbackExclusions, FamilyTemplate, FontCfgIssue,
+        FontConfig, FontConfigMut, FontFilePicker, FontInfo, FontLoadQueue, FontNameError,
+        FontPresets, FontResolution, FontSource, FontStatus, FontUploader, HelpTopic,
+        InsertPosition, JournalEntry, Monitor, Notification, PresetHotkeys, Pushed, RenameError,
+        ValidationIssue, Validator,
+    },
+    egui::{
+        collapsing_header::{paint_default_icon, CollapsingState},
+        FontDefinitions, FontFamily,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::{mpsc, Arc},
+        time::Duration,
+    },
+};
+
+#[cfg(feature = "fs")]
+use crate::ConfigLock;
+#[cfg(feature = "system-fonts")]
+use crate::{system_fonts, SystemFont};
+
+/// A loaded font about to be added, see [`FontCfgUi::insert_font`]
+struct NewFont {
+    name: String,
+    data: egui::FontData,
+    /// What to record in the [`CustomFontPaths`]. Fonts without one count as uploaded.
+    path: Option<String>,
+    /// Families to add the font to
+    families: BTreeSet<FontFamily>,
+    position: InsertPosition,
+    /// Replace the font of the same name, as the user asked to
+    replace: bool,
+}
+
+/// A new font with the same contents as the already loaded font `existing`
+struct PendingDuplicate {
+    font: NewFont,
+    existing: String,
+}
+
+/// Drag and drop payload of an entry of a family
+struct FamilyEntryDrag {
+    family: FontFamily,
+    /// Position of the entry in the family
+    index: usize,
+}
+
+/// Where the add new font form takes the new font from
+#[derive(Default, Clone, Copy, PartialEq)]
+enum AddSource {
+    #[default]
+    File,
+    /// The [bundled fonts](FontCfgUi::bundled_font)
+    Bundled,
+    #[cfg(feature = "system-fonts")]
+    System,
+}
+
+/// The state of the font configuration ui
+#[derive(Default)]
+pub struct FontCfgUi {
+    name_buf: String,
+    path_buf: String,
+    /// New fonts whose contents are already loaded, waiting for the user to decide what to do
+    duplicates: Vec<PendingDuplicate>,
+    err_msg: String,
+    announce_err: bool,
+    add_new: bool,
+    apply_requested: bool,
+    /// See [`Self::live_apply`]
+    live_apply: bool,
+    /// When the fonts last changed without being applied yet, with live apply on
+    live_apply_changed_at: Option<f64>,
+    /// The fonts live apply saw during the last [`Self::show`], to notice changes
+    live_apply_seen: Option<DefsFingerprint>,
+    text_focused: bool,
+    focus_name_field: bool,
+    layout_mode: LayoutMode,
+    two_pane: bool,
+    show_priorities: bool,
+    high_contrast: bool,
+    gamepad_nav: bool,
+    normalize_name: Option<fn(&str) -> String>,
+    add_form_placement: AddFormPlacement,
+    confirmation: ConfirmationPolicy,
+    pending_confirm: Option<confirm::PendingAction>,
+    /// Font whose file is being replaced, and the path typed in for the new file
+    replacing: Option<(String, String)>,
+    /// Only fonts with this status are listed
+    status_filter: Option<FontStatus>,
+    trash: trash::Trash,
+    help: help::HelpTexts,
+    validators: Vec<Validator>,
+    /// Results of the checks of the fonts, updated at the start of [`Self::show`]
+    checks: validate::Checks,
+    journal: Vec<JournalEntry>,
+    /// Whether to collect events, see [`Self::collect_events`]
+    collect_events: bool,
+    /// Events not yet taken with [`Self::take_events`]
+    events: Vec<FontDefsUiMsg>,
+    exclusions: FallbackExclusions,
+    /// The configuration changes are exported relative to
+    baseline: Option<FontDefinitions>,
+    diff_form: config_diff::DiffForm,
+    catalog: Vec<BundledFont>,
+    glyph_diffs: glyph_diff::GlyphDiffCache,
+    live_preview: live_preview::LivePreview,
+    notifier: notify::Notifier,
+    /// Namespace that fonts added through the ui are put in
+    user_namespace: Option<String>,
+    namespace_filter: Option<String>,
+    file_picker: Option<Box<dyn FontFilePicker + Send>>,
+    uploader: Option<Box<dyn FontUploader + Send>>,
+    /// Fonts added from data that has no path to record, see [`Self::uploaded_font_bytes`]
+    uploaded: BTreeSet<String>,
+    /// Metadata of the font at the path in the add new font form, and that path
+    new_font_info: Option<(String, Option<FontInfo>)>,
+    /// The file at the path in the add new font form being read by the load queue
+    new_font_info_read: Option<mpsc::Receiver<std::io::Result<Vec<u8>>>>,
+    load_queue: Option<FontLoadQueue>,
+    font_source: Option<loading::SharedFontSource>,
+    #[cfg(feature = "fs")]
+    config_lock: Option<ConfigLock>,
+    monitor_previews: monitors::MonitorPreviews,
+    /// Why the apply button is disabled, if it is
+    apply_disabled: Option<String>,
+    history: undo::History,
+    /// Show the fonts in pages of this many instead of all at once
+    fonts_per_page: Option<usize>,
+    font_page: usize,
+    /// The fonts as they were when the dialog was opened, for reverting to
+    opened_with: Option<undo::State>,
+    /// The fonts as they were last applied or saved, see [`Self::is_dirty`]
+    clean: Option<DefsFingerprint>,
+    dirty: bool,
+    restore_requested: Option<confirm::Restore>,
+    add_source: AddSource,
+    /// Families the add new font form also adds the font to
+    quick_add_to: BTreeSet<FontFamily>,
+    insert_position: InsertPosition,
+    #[cfg(feature = "system-fonts")]
+    system_picker: system_fonts::SystemFontPicker,
+    diagnose_form: diagnose::DiagnoseForm,
+    variant_form: variants::VariantForm,
+    batch_rename: rename::BatchRenameForm,
+    new_family_form: templates::NewFamilyForm,
+    adopting: Option<adopt::AdoptForm>,
+    /// How long `set_fonts` took on the last apply, if there was one
+    last_apply: Option<Option<Duration>>,
+    presets: FontPresets,
+    presets_form: presets::PresetsForm,
+    details: details::DetailsState,
+    bulk_tweak: Option<tweaks::BulkTweakForm>,
+    calibration: calibrate::Calibration,
+    /// Renders previews with the definitions being edited
+    pending_preview: preview::PreviewFonts,
+    coverage: coverage::CoverageCache,
+    preview_texts: BTreeMap<FontFamily, String>,
+    editing_preview_text: Option<FontFamily>,
+    /// The named family being renamed, and the name being typed
+    renaming_family: Option<(FontFamily, String)>,
+    /// The font being renamed, and the identifier being typed
+    renaming_font: Option<(String, String)>,
+    /// Largest size of the ui, see [`Self::max_size`]
+    max_size: Option<egui::Vec2>,
+    /// Text the font list and family entries are narrowed down to
+    list_filter: String,
+    /// Whether to scroll to the first match of the filter, after it changed
+    scroll_to_match: bool,
+    /// The order families are shown in, see [`Self::family_order`]
+    family_order: Vec<FontFamily>,
+    /// Where fonts were loaded from, see [`Self::font_resolutions`]
+    resolutions: BTreeMap<String, FontResolution>,
+}
+
+/// Where [`FontCfgUi`] shows the form for adding new fonts
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddFormPlacement {
+    /// Right below the `Fonts` heading
+    #[default]
+    Inline,
+    /// In a popup below the `+` button
+    Popup,
+    /// In a collapsible section below the list of fonts
+    Collapsible,
+}
+
+/// How [`FontCfgUi`] lays out its controls
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// Compact layout, suited for mouse and keyboard
+    #[default]
+    Compact,
+    /// Large hit targets and more spacing, suited for touch screens
+    ///
+    /// Rows can also be deleted by swiping them to the left.
+    Touch,
+}
+
+/// Severity of a status message shown by the ui
+#[derive(Clone, Copy)]
+pub(crate) enum Severity {
+    Warning,
+    Error,
+}
+
+/// Show a status message. In high contrast mode it gets an icon and a strong background.
+pub(crate) fn status_label(
+    ui: &mut egui::Ui,
+    high_contrast: bool,
+    severity: Severity,
+    text: &str,
+) -> egui::Response {
+    if !high_contrast {
+        return ui.label(egui::RichText::new(text).color(egui::Color32::DARK_RED));
+    }
+    let (icon, fg, bg) = match severity {
+        Severity::Warning => ("⚠", egui::Color32::BLACK, egui::Color32::YELLOW),
+        Severity::Error => (
+            "❌",
+            egui::Color32::WHITE,
+            egui::Color32::from_rgb(160, 0, 0),
+        ),
+    };
+    egui::Frame::none()
+        .fill(bg)
+        .inner_margin(4.0)
+        .rounding(2.0)
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(format!("{icon} {text}"))
+                    .color(fg)
+                    .strong(),
+            )
+        })
+        .inner
+}
+
+/// How far a row has to be swiped to the left to delete it, in points
+const SWIPE_DELETE_DISTANCE: f32 = 120.0;
+
+/// The font source set with [`FontCfgUi::font_source`], if any
+fn as_source(source: &Option<loading::SharedFontSource>) -> Option<&dyn FontSource> {
+    source.as_deref().map(|source| source as _)
+}
+
+/// Whether `name` contains the filter text, ignoring case. Everything matches an empty filter.
+fn matches_list_filter(filter: &str, name: &str) -> bool {
+    filter.is_empty() || name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// Show a warning sign that explains a problem on hover
+fn issue_icon(ui: &mut egui::Ui, text: &str) {
+    let warn_color = ui.visuals().warn_fg_color;
+    ui.label(egui::RichText::new("⚠").color(warn_color))
+        .on_hover_text(text);
+}
+
+/// Message returned by [`FontCfgUi::show`]
+///
+/// `show` only returns the most important message of the frame. To react to every edit, see
+/// [`FontCfgUi::take_events`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontDefsUiMsg {
+    /// No event happened
+    None,
+    /// A save was requested
+    SaveRequest,
+    /// The back action was used with [`FontCfgUi::gamepad_navigation`] enabled
+    BackRequest,
+    /// A preset was applied with one of the [`PresetHotkeys`] of a [`FontCfgWindow`], or picked
+    /// in [`FontCfgUi::show_kiosk`]
+    ///
+    /// The font definitions and custom font paths were replaced with the preset's. Fonts of
+    /// named families the application uses, which the preset doesn't have, are kept (see
+    /// [`preset_conflicts`]).
+    PresetSwitched {
+        /// Name of the applied preset
+        name: String,
+    },
+    /// The user tried to close the [`FontCfgWindow`] while [`FontCfgUi::is_dirty`]
+    ///
+    /// The window stays open and asks the user to confirm. It closes if they do.
+    CloseRequestedWithUnsavedChanges,
+    /// The fonts were reverted to what they were when the dialog was opened
+    ///
+    /// The custom font paths were restored too, if they were passed to [`FontCfgUi::show`].
+    Reverted,
+    /// The fonts were reset to [`FontDefinitions::default`]
+    ///
+    /// The custom font paths passed to [`FontCfgUi::show`] were cleared. Clear any other
+    /// custom font paths you keep, along with things like [`CustomFontHashes`](crate::CustomFontHashes).
+    ResetToDefaults,
+    /// A font added with [`FontCfgUi::async_loading`] finished loading, and was added
+    FontLoaded {
+        /// Identifier of the font
+        name: String,
+    },
+    /// The fonts were pushed to the egui context this frame
+    ///
+    /// The new fonts take effect at the start of the next frame, which is a good time for
+    /// any expensive relayout work. If a save was requested in the same frame,
+    /// [`Self::SaveRequest`] is reported instead.
+    Applied {
+        /// How long [`egui::Context::set_fonts`] took, if it could be measured
+        set_fonts_time: Option<Duration>,
+    },
+    /// A font was added. Only reported by [`FontCfgUi::take_events`].
+    FontAdded {
+        /// Identifier of the font
+        name: String,
+    },
+    /// A font was removed. Only reported by [`FontCfgUi::take_events`].
+    FontRemoved {
+        /// Identifier of the font
+        name: String,
+    },
+    /// The data, face index or tweak of a font changed. Only reported by
+    /// [`FontCfgUi::take_events`].
+    FontChanged {
+        /// Identifier of the font
+        name: String,
+    },
+    /// A family was added, removed, or its fonts changed. Only reported by
+    /// [`FontCfgUi::take_events`].
+    FamilyChanged {
+        /// The family
+        family: FontFamily,
+    },
+}
+
+/// Give a widget an accessible name that says more than its visible text
+pub(crate) fn a11y_label(re: &egui::Response, typ: egui::WidgetType, label: &str) {
+    re.widget_info(|| egui::WidgetInfo::labeled(typ, re.enabled(), label));
+}
+
+/// Give a text edit an accessible name, as well as its current value
+///
+/// This also notes whether the text edit has focus, for [`FontCfgUi::has_text_focus`], so
+/// every text edit of the ui goes through here.
+pub(crate) fn a11y_text_label(re: &egui::Response, label: &str, text: &str) {
+    re.widget_info(|| {
+        let mut info = egui::WidgetInfo::text_edit(re.enabled(), text, text);
+        info.label = Some(label.to_owned());
+        info
+    });
+    if re.has_focus() {
+        re.ctx.data_mut(|d| d.insert_temp(text_focus_id(), true));
+    }
+}
+
+/// Where [`a11y_text_label`] notes that a text edit has focus, until [`FontCfgUi::show`]
+/// takes it
+fn text_focus_id() -> egui::Id {
+    egui::Id::new("egui_fontcfg_text_focus")
+}
+
+/// Lets the user delete a row by swiping it to the left. Returns whether it was swiped away.
+fn swiped_away(ui: &egui::Ui, row: &egui::Response, id: egui::Id) -> bool {
+    let re = ui.interact(row.rect, id, egui::Sense::drag());
+    let offset = ui.data_mut(|d| {
+        let offset = d.get_temp_mut_or_default::<f32>(id);
+        *offset += re.drag_delta().x;
+        *offset
+    });
+    if re.dragged() && offset < 0.0 {
+        let alpha = (-offset / SWIPE_DELETE_DISTANCE).min(1.0);
+        ui.painter().rect_filled(
+            row.rect,
+            2.0,
+            egui::Color32::DARK_RED.gamma_multiply(alpha * 0.5),
+        );
+    }
+    if re.drag_stopped() {
+        ui.data_mut(|d| d.remove::<f32>(id));
+        return offset <= -SWIPE_DELETE_DISTANCE;
+    }
+    false
+}
+
+impl FontCfgUi {
+    /// Set the layout mode of the ui
+    pub fn layout_mode(mut self, mode: LayoutMode) -> Self {
+        self.layout_mode = mode;
+        self
+    }
+    /// Limit the size of the ui
+    ///
+    /// The lists scroll once they don't fit. By default, the ui is 300 points wide (400 in
+    /// [`LayoutMode::Touch`]), and as tall as there is room for.
+    pub fn max_size(mut self, size: impl Into<egui::Vec2>) -> Self {
+        self.max_size = Some(size.into());
+        self
+    }
+    /// Apply the fonts automatically whenever they change, without the user pressing `Apply`
+    ///
+    /// Applying waits until the fonts stopped changing for a moment, as it rebuilds the font
+    /// atlas. Fonts that can't be applied are left alone until they're fixed. The user can
+    /// toggle this next to the `Apply` button.
+    pub fn live_apply(mut self, enabled: bool) -> Self {
+        self.live_apply = enabled;
+        self
+    }
+    /// Set where fonts added with the add new font form go in the families picked there
+    ///
+    /// The user can change this in the form.
+    pub fn insert_position(mut self, position: InsertPosition) -> Self {
+        self.insert_position = position;
+        self
+    }
+    /// Set where the add new font form is shown
+    pub fn add_form_placement(mut self, placement: AddFormPlacement) -> Self {
+        self.add_form_placement = placement;
+        self
+    }
+    /// Show errors and warnings with icons and strong contrast backgrounds instead of colored text
+    pub fn high_contrast(mut self, enabled: bool) -> Self {
+        self.high_contrast = enabled;
+        self
+    }
+    /// Set a Unicode normalization function (e.g. NFC) applied to identifiers of added fonts
+    ///
+    /// See [`sanitize_font_name`].
+    pub fn name_normalizer(mut self, normalize: fn(&str) -> String) -> Self {
+        self.normalize_name = Some(normalize);
+        self
+    }
+    /// Make the ui operable with only directional input plus accept and back actions
+    ///
+    /// This is meant for gamepads, with the host mapping the d-pad to the arrow keys,
+    /// accept to Enter and back to Escape.
+    /// When nothing has focus, directional input focuses the first control of the ui.
+    /// Back closes the add new font form, or reports [`FontDefsUiMsg::BackRequest`].
+    pub fn gamepad_navigation(mut self, enabled: bool) -> Self {
+        self.gamepad_nav = enabled;
+        self
+    }
+    /// Lay out the ui in two panes, with the lists of fonts and families on the left, and the
+    /// preview and inspection sections on the right, separated by a resizable splitter
+    ///
+    /// This suits larger settings windows. The ui fills the available space in this mode.
+    pub fn two_pane(mut self, enabled: bool) -> Self {
+        self.two_pane = enabled;
+        self
+    }
+    /// Show an editable priority number next to each font of a family
+    ///
+    /// Priorities map onto the order of the family, 1 being the font that is tried first.
+    /// Changing a priority moves the font to that position.
+    pub fn show_priorities(mut self, enabled: bool) -> Self {
+        self.show_priorities = enabled;
+        self
+    }
+    /// Set the configuration the application ships with, enabling export and import of only
+    /// the changes the user made to it (see [`FontConfigDiff`](crate::FontConfigDiff))
+    pub fn baseline(mut self, baseline: FontDefinitions) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+    /// Offer a font compiled into the application in a "Bundled" tab of the add new font form
+    ///
+    /// Added bundled fonts are recorded in the custom font paths by name, see
+    /// [`BundledFont::custom_path`]. Restore them with [`load_bundled_fonts`](crate::load_bundled_fonts).
+    pub fn bundled_font(mut self, font: BundledFont) -> Self {
+        self.catalog.push(font);
+        self
+    }
+    /// Show where each font was loaded from, as reported by [`load_fonts_by_priority`](crate::load_fonts_by_priority)
+    pub fn font_resolutions(
+        mut self,
+        resolutions: impl IntoIterator<Item = FontResolution>,
+    ) -> Self {
+        self.resolutions = resolutions
+            .into_iter()
+            .map(|resolution| (resolution.name.clone(), resolution))
+            .collect();
+        self
+    }
+    /// Show the families in this order, e.g. as persisted from [`Self::family_display_order`]
+    ///
+    /// Families that aren't listed are shown after the listed ones, in the order of
+    /// [`FontDefinitions::families`]. The user can rearrange them in the dialog.
+    pub fn family_order(mut self, order: Vec<FontFamily>) -> Self {
+        self.family_order = order;
+        self
+    }
+    /// The order the families of `font_defs` are shown in, as arranged by the user
+    ///
+    /// Persist it to restore the arrangement with [`Self::family_order`], or store it in a
+    /// [`FontConfigSnapshot`](crate::FontConfigSnapshot) with [`FontConfigSnapshot::order_families`](crate::FontConfigSnapshot::order_families).
+    pub fn family_display_order(&self, font_defs: &FontDefinitions) -> Vec<FontFamily> {
+        let listed = self
+            .family_order
+            .iter()
+            .filter(|family| font_defs.families.contains_key(family));
+        let rest = font_defs
+            .families
+            .keys()
+            .filter(|family| !self.family_order.contains(family));
+        listed.chain(rest).cloned().collect()
+    }
+    /// Set which destructive actions the user has to confirm
+    pub fn confirmation_policy(mut self, policy: ConfirmationPolicy) -> Self {
+        self.confirmation = policy;
+        self
+    }
+    /// Offer the fonts of `source` (e.g. from a theme crate) for import
+    ///
+    /// The ui lists them, letting the user pick which ones to import along with their family
+    /// entries. See [`import_fonts`](crate::import_fonts) for doing this without user interaction.
+    pub fn adopt_fonts(&mut self, source: &FontDefinitions) {
+        self.adopting = Some(adopt::AdoptForm::new(source.clone()));
+    }
+    /// The presets the user can switch between
+    pub fn presets(&self) -> &FontPresets {
+        &self.presets
+    }
+    /// Mutable access to the presets, e.g. for restoring persisted ones
+    pub fn presets_mut(&mut self) -> &mut FontPresets {
+        &mut self.presets
+    }
+    /// Replace the help text shown for `topic`. An empty text hides the help icon.
+    pub fn help_text(mut self, topic: HelpTopic, text: impl Into<String>) -> Self {
+        self.help.set(topic, text.into());
+        self
+    }
+    /// Put the fonts the user adds into `namespace`, e.g. `user`, so they can be told apart from
+    /// the fonts the app registers
+    ///
+    /// See [`split_namespace`](crate::split_namespace) and [`merge_namespace`](crate::merge_namespace) for persisting them separately.
+    pub fn user_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.user_namespace = Some(namespace.into());
+        self
+    }
+    /// Show a "Browse…" button next to the path of a new font, which picks the file with `picker`
+    pub fn file_picker(mut self, picker: impl FontFilePicker + Send + 'static) -> Self {
+        self.file_picker = Some(Box::new(picker));
+        self
+    }
+    /// Show an "Upload…" button in the add new font form, which adds fonts uploaded with `uploader`
+    ///
+    /// Meant for the web, where fonts can't be loaded from paths. Uploaded fonts aren't
+    /// recorded in [`CustomFontPaths`]. To persist them, save their data from
+    /// [`Self::uploaded_font_bytes`] with [`encode_custom_font_bytes`](crate::encode_custom_font_bytes).
+    pub fn font_uploader(mut self, uploader: impl FontUploader + Send + 'static) -> Self {
+        self.uploader = Some(Box::new(uploader));
+        self
+    }
+    /// Treat the fonts called `names` as uploaded, e.g. after restoring them with
+    /// [`load_custom_font_bytes`](crate::load_custom_font_bytes)
+    pub fn uploaded_fonts(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.uploaded.extend(names);
+        self
+    }
+    /// The data of the fonts that were uploaded or dropped without a path, for persisting it
+    ///
+    /// These fonts have no [`CustomFontPaths`] entry, as there is no path to load them back from.
+    pub fn uploaded_font_bytes(&self, font_defs: &FontDefinitions) -> CustomFontBytes {
+        self.uploaded
+            .iter()
+            .filter_map(|name| Some((name.clone(), font_defs.font_data.get(name)?.font.to_vec())))
+            .collect()
+    }
+    /// Load the custom fonts the user adds, replaces or reloads from `source`
+    ///
+    /// By default, they are read from the file system. On the web, provide a source that
+    /// fetches the fonts or looks them up in embedded assets. To load the fonts recorded in
+    /// [`CustomFontPaths`] from the same source, use [`load_custom_fonts_from`](crate::load_custom_fonts_from).
+    /// A [`FontLoadQueue::default`] set with [`Self::async_loading`] reads from it as well.
+    pub fn font_source(mut self, source: impl FontSource + Send + Sync + 'static) -> Self {
+        let source: loading::SharedFontSource = Arc::new(source);
+        if let Some(queue) = &mut self.load_queue {
+            queue.set_default_source(source.clone());
+        }
+        self.font_source = Some(source);
+        self
+    }
+    /// Lock the file the application saves the font configuration to at `config_path`
+    ///
+    /// If another instance of the application is already editing it, the user is warned next
+    /// to the save button that saving may overwrite the other instance's changes. The lock is
+    /// released when the ui is dropped. See [`ConfigLock`].
+    #[cfg(feature = "fs")]
+    pub fn config_lock(mut self, config_path: impl AsRef<std::path::Path>) -> Self {
+        self.config_lock = Some(ConfigLock::new(config_path));
+        self
+    }
+    /// Read the files of fonts added with the add new font form in the background with `queue`
+    ///
+    /// The fonts are added once they're read, which is reported with
+    /// [`FontDefsUiMsg::FontLoaded`], for each of them in [`Self::take_events`]. Without this,
+    /// files are read right away, which can make the ui stutter when adding large fonts from
+    /// slow disks. The files are read on new threads, unless the queue has a
+    /// [`FontLoadQueue::spawner`] or an async reader.
+    pub fn async_loading(mut self, mut queue: FontLoadQueue) -> Self {
+        if let Some(source) = &self.font_source {
+            queue.set_default_source(source.clone());
+        }
+        self.load_queue = Some(queue);
+        self
+    }
+    /// Preview the fonts at the scale of each of `monitors`, see [`Self::set_monitors`]
+    pub fn monitors(mut self, monitors: impl IntoIterator<Item = Monitor>) -> Self {
+        self.set_monitors(monitors);
+        self
+    }
+    /// Preview the fonts at the scale of each of `monitors`
+    ///
+    /// Users with monitors of different DPI can then pick fonts and sizes that read well on
+    /// all of them. Call this again when monitors are connected or disconnected.
+    pub fn set_monitors(&mut self, monitors: impl IntoIterator<Item = Monitor>) {
+        self.monitor_previews.monitors = monitors.into_iter().collect();
+    }
+    /// Show the font list in pages of `per_page` fonts, with controls for switching pages
+    ///
+    /// Laying out fewer rows per frame helps on slow targets with many fonts.
+    pub fn paginate_fonts(mut self, per_page: usize) -> Self {
+        self.fonts_per_page = Some(per_page.max(1));
+        self
+    }
+    /// Hand errors, applies and preset switches to `sink` instead of showing errors in the ui
+    ///
+    /// Meant for hosts that show notifications globally, e.g. as toasts.
+    /// Repeats of a notification are dropped, see [`Self::notification_interval`].
+    pub fn notifications(mut self, sink: impl FnMut(Notification) + Send + 'static) -> Self {
+        self.notifier.set_sink(Box::new(sink));
+        self
+    }
+    /// Drop notifications identical to one emitted less than `secs` seconds ago (default 2)
+    pub fn notification_interval(mut self, secs: f64) -> Self {
+        self.notifier.interval = secs;
+        self
+    }
+    /// Add a check that runs alongside the built-in ones, shown in the diagnostics section
+    ///
+    /// Useful for app specific requirements, like a family having to include a brand font.
+    pub fn validator(
+        mut self,
+        validator: impl Fn(&FontDefinitions) -> Vec<ValidationIssue> + Send + 'static,
+    ) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+    /// The changes the user made to the font definitions, oldest first
+    pub fn journal(&self) -> &[JournalEntry] {
+        &self.journal
+    }
+    /// Take the recorded changes, leaving the journal empty
+    ///
+    /// Hosts syncing changes elsewhere should call this regularly, as the journal grows
+    /// with every edit.
+    pub fn take_journal(&mut self) -> Vec<JournalEntry> {
+        std::mem::take(&mut self.journal)
+    }
+    /// Take everything that happened since the last call, oldest first
+    ///
+    /// Unlike the message [`Self::show`] returns, this has an event for each edit
+    /// ([`FontDefsUiMsg::FontAdded`], [`FontDefsUiMsg::FamilyChanged`], ...) besides the
+    /// applies, saves, reverts and so on, so hosts can e.g. re-save their configuration or
+    /// sync it elsewhere as it changes. Events are only collected after enabling
+    /// [`Self::collect_events`].
+    pub fn take_events(&mut self) -> Vec<FontDefsUiMsg> {
+        std::mem::take(&mut self.events)
+    }
+    /// Collect the events returned by [`Self::take_events`]
+    ///
+    /// Off by default, as the events pile up unless they're taken. Call
+    /// [`Self::take_events`] every frame if you turn this on. Repeated changes of the same
+    /// font or family, like dragging a tweak slider, are reported as one event.
+    pub fn collect_events(mut self, enabled: bool) -> Self {
+        self.collect_events = enabled;
+        self
+    }
+    /// Add `event` to the ones collected for [`Self::take_events`], if they're collected
+    fn push_event(&mut self, event: FontDefsUiMsg) {
+        if !self.collect_events {
+            return;
+        }
+        let repeated = matches!(
+            event,
+            FontDefsUiMsg::FontChanged { .. } | FontDefsUiMsg::FamilyChanged { .. }
+        ) && self.events.last() == Some(&event);
+        if !repeated {
+            self.events.push(event);
+        }
+    }
+    /// The code point ranges the user excluded fonts from being used for
+    ///
+    /// Persist these along with your font configuration, and use [`apply_fallback_exclusions`]
+    /// when applying the configuration yourself. The previews don't reflect the exclusions.
+    pub fn fallback_exclusions(&self) -> &FallbackExclusions {
+        &self.exclusions
+    }
+    /// Mutable access to the fallback exclusions, e.g. for restoring persisted ones
+    pub fn fallback_exclusions_mut(&mut self) -> &mut FallbackExclusions {
+        &mut self.exclusions
+    }
+    /// Request the font definitions to be applied the next time [`Self::show`] is called
+    ///
+    /// This goes through the same path as clicking the `Apply` button.
+    pub fn request_apply(&mut self) {
+        self.apply_requested = true;
+    }
+    /// Undo the last edit made through the ui, the next time [`Self::show`] is called
+    ///
+    /// Meant for binding to a keyboard shortcut. Does nothing if there is nothing to undo.
+    pub fn undo(&mut self) {
+        self.history.request(false);
+    }
+    /// Redo the last undone edit, the next time [`Self::show`] is called
+    pub fn redo(&mut self) {
+        self.history.request(true);
+    }
+    /// Make the next [`Self::show`] remember the fonts as the ones `Revert` goes back to
+    ///
+    /// Call this when showing the dialog again after hiding it. [`FontCfgWindow`] does this
+    /// whenever the window is opened.
+    pub fn set_revert_point(&mut self) {
+        self.opened_with = None;
+    }
+    /// Whether the fonts differ from the ones last applied or saved with the dialog's buttons
+    ///
+    /// Up to date as of the last [`Self::show`]. The fonts count as saved when the dialog is
+    /// first shown.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+    /// Remember `font_defs` as saved, e.g. after saving them without the dialog's save button
+    pub fn mark_saved(&mut self, font_defs: &FontDefinitions) {
+        self.clean = Some(DefsFingerprint::new(font_defs));
+        self.dirty = false;
+    }
+    /// Whether there is an edit that [`Self::undo`] would undo
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+    /// Whether there is an undone edit that [`Self::redo`] would redo
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+    /// Enable or disable the `Apply` button, e.g. during an operation that must not be
+    /// disturbed by a font change. `reason` is shown when hovering the disabled button.
+    ///
+    /// [`Self::request_apply`] still works while the button is disabled.
+    pub fn set_apply_enabled(&mut self, enabled: bool, reason: impl Into<String>) {
+        self.apply_disabled = (!enabled).then(|| reason.into());
+    }
+    /// The preview texts the user set for each family
+    ///
+    /// Families without an entry use a default text. Persist this along with your font
+    /// configuration to keep previews relevant to each family's purpose.
+    pub fn preview_texts(&self) -> &BTreeMap<FontFamily, String> {
+        &self.preview_texts
+    }
+    /// Mutable access to the preview texts, e.g. for restoring persisted ones
+    pub fn preview_texts_mut(&mut self) -> &mut BTreeMap<FontFamily, String> {
+        &mut self.preview_texts
+    }
+    /// Whether the add new font form is open
+    pub fn is_adding_new(&self) -> bool {
+        self.add_new
+    }
+    /// The error message currently shown, if any
+    pub fn last_error(&self) -> Option<&str> {
+        (!self.err_msg.is_empty()).then_some(self.err_msg.as_str())
+    }
+    /// The identifier typed into the add new font form so far
+    pub fn pending_font_name(&self) -> &str {
+        &self.name_buf
+    }
+    /// The path typed into the add new font form so far
+    pub fn pending_font_path(&self) -> &str {
+        &self.path_buf
+    }
+    /// Whether one of the text fields of the ui had keyboard focus during the last [`Self::show`]
+    ///
+    /// Useful for suppressing global hotkeys while the user is typing.
+    pub fn has_text_focus(&self) -> bool {
+        self.text_focused
+    }
+    /// Show the add new font form
+    fn add_form_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        back: &mut bool,
+    ) {
+        let tabs = !self.catalog.is_empty() || cfg!(feature = "system-fonts");
+        if tabs {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.add_source, AddSource::File, "File");
+                if !self.catalog.is_empty() {
+                    ui.selectable_value(&mut self.add_source, AddSource::Bundled, "Bundled");
+                }
+                #[cfg(feature = "system-fonts")]
+                ui.selectable_value(&mut self.add_source, AddSource::System, "System");
+            });
+        }
+        if self.add_source == AddSource::Bundled && self.catalog.is_empty() {
+            self.add_source = AddSource::File;
+        }
+        self.quick_add_ui(ui, font_defs);
+        if self.add_source != AddSource::File {
+            match self.add_source {
+                AddSource::Bundled => {
+                    let name_for =
+                        |name: &str| self.font_name_for(name).unwrap_or_else(|_| name.to_owned());
+                    let picked = catalog::catalog_ui(ui, &self.catalog, font_defs, name_for);
+                    if let Some(font) = picked.cloned() {
+                        if let Err(e) = self.add_bundled_font(font_defs, custom, &font) {
+                            self.set_error(e);
+                        }
+                    }
+                }
+                #[cfg(feature = "system-fonts")]
+                AddSource::System => {
+                    let spawner = self.spawner();
+                    if let Some(font) = self.system_picker.ui(ui, &spawner) {
+                        if let Err(e) = self.add_system_font(font_defs, custom, font) {
+                            self.set_error(e);
+                        }
+                    }
+                }
+                AddSource::File => {}
+            }
+            if *back {
+                self.cancel_add_form();
+                *back = false;
+            }
+            return;
+        }
+        let name_re = ui.add(
+            egui::TextEdit::singleline(&mut self.name_buf).hint_text("Identifier for new font"),
+        );
+        if std::mem::take(&mut self.focus_name_field) {
+            name_re.request_focus();
+        }
+        a11y_text_label(&name_re, "Identifier for new font", &self.name_buf);
+        if let (Some(_), Ok(name)) = (&self.user_namespace, self.new_font_name()) {
+            ui.weak(format!("Added as {name}"));
+        }
+        let path_re = ui
+            .horizontal(|ui| {
+                let re = ui.add(
+                    egui::TextEdit::singleline(&mut self.path_buf).hint_text("Path to new font"),
+                );
+                let picked = match &mut self.file_picker {
+                    Some(picker) => {
+                        let re = ui.button("Browse…");
+                        a11y_label(&re, egui::WidgetType::Button, "Browse for new font");
+                        if re.clicked() {
+                            picker.pick_font_file()
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                };
+                if let Some(path) = picked {
+                    if self.name_buf.trim().is_empty() {
+                        if let Some(stem) = path.file_stem() {
+                            self.name_buf = stem.to_string_lossy().into_owned();
+                        }
+                    }
+                    self.path_buf = path.to_string_lossy().into_owned();
+                }
+                if let Some(uploader) = &mut self.uploader {
+                    let re = ui
+                        .button("⬆ Upload…")
+                        .on_hover_text("Upload font files, adding them right away");
+                    a11y_label(&re, egui::WidgetType::Button, "Upload fonts");
+                    if re.clicked() {
+                        uploader.start_upload();
+                    }
+                }
+                re
+            })
+            .inner;
+        a11y_text_label(&path_re, "Path to new font", &self.path_buf);
+        self.new_font_info_ui(ui);
+        self.text_focused |= name_re.has_focus() || path_re.has_focus();
+        let left_field = name_re.lost_focus() || path_re.lost_focus();
+        let (enter, escape) = ui.input(|i| {
+            (
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if (left_field || *back) && escape {
+            self.cancel_add_form();
+            *back = false;
+            return;
+        }
+        let problem = self.add_form_problem();
+        let mut add_re = ui.add_enabled(problem.is_none(), egui::Button::new("Add new font"));
+        if let Some(problem) = &problem {
+            add_re = add_re.on_disabled_hover_text(problem);
+        }
+        if problem.is_some() && left_field && enter {
+            // Keep the user in the form so they can fix the problem
+            if name_re.lost_focus() {
+                name_re.request_focus();
+            } else {
+                path_re.request_focus();
+            }
+        }
+        if add_re.clicked() || (problem.is_none() && left_field && enter) {
+            let duplicate = self
+                .new_font_name()
+                .ok()
+                .filter(|name| font_defs.font_data.contains_key(name));
+            match duplicate {
+                Some(name) if self.confirmation.overwrite_duplicate => {
+                    self.pending_confirm = Some(confirm::PendingAction::OverwriteFont(name));
+                }
+                _ => {
+                    if let Err(e) = self.add_font(font_defs, custom) {
+                        self.set_error(e);
+                    }
+                }
+            }
+        }
+    }
+    /// Offer to use the loaded fonts with the same contents as new fonts, instead of loading
+    /// them again
+    fn duplicates_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) {
+        let Some(duplicate) = self.duplicates.first() else {
+            return;
+        };
+        // The existing font was removed since, so there's nothing to choose from
+        if !font_defs.font_data.contains_key(&duplicate.existing) {
+            let duplicate = self.duplicates.remove(0);
+            self.add_duplicate(font_defs, custom, duplicate.font);
+            return;
+        }
+        let (name, existing) = (&duplicate.font.name, &duplicate.existing);
+        let text = format!("{name} is already loaded as {existing}");
+        let mut answer = None;
+        ui.horizontal_wrapped(|ui| {
+            status_label(ui, self.high_contrast, Severity::Warning, &text);
+            let hover = if duplicate.font.families.is_empty() {
+                format!("Don't add {name}, and use {existing} instead")
+            } else {
+                format!("Add {existing} to the picked families instead")
+            };
+            let re = ui.button("Use existing").on_hover_text(hover);
+            a11y_label(
+                &re,
+                egui::WidgetType::Button,
+                &format!("Use existing font {existing}"),
+            );
+            if re.clicked() {
+                answer = Some(Some(true));
+            }
+            if ui
+                .button("Add anyway")
+                .on_hover_text("Load the file again as another font")
+                .clicked()
+            {
+                answer = Some(Some(false));
+            }
+            if ui.button("Cancel").clicked() {
+                answer = Some(None);
+            }
+            if self.duplicates.len() > 1 {
+                ui.weak(format!("{} more", self.duplicates.len() - 1));
+            }
+        });
+        let Some(answer) = answer else {
+            return;
+        };
+        let PendingDuplicate { font, existing } = self.duplicates.remove(0);
+        match answer {
+            Some(true) => {
+                let mut config = FontConfigMut::new(font_defs, custom);
+                if let Err(e) = config.add_to_families(&font.families, &existing, font.position) {
+                    self.set_error(e.to_string());
+                }
+            }
+            Some(false) => self.add_duplicate(font_defs, custom, font),
+            None => {}
+        }
+    }
+    /// Add `font` even though its contents are already loaded, unless its name was taken since
+    fn add_duplicate(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        font: NewFont,
+    ) {
+        if let Err(e) = self.insert_font(font_defs, custom, font, false) {
+            self.set_error(e);
+        }
+    }
+    /// Add `font` to `font_defs` and its families, recording where it came from
+    ///
+    /// With `check_duplicate`, a font whose contents are already loaded under another identifier
+    /// isn't added, and the user is asked what to do instead. Returns whether it was added.
+    fn insert_font(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        font: NewFont,
+        check_duplicate: bool,
+    ) -> Result<bool, String> {
+        if check_duplicate {
+            let existing =
+                find_duplicate_font(font_defs, &font.data.font).filter(|n| *n != font.name);
+            if let Some(existing) = existing {
+                let existing = existing.to_owned();
+                self.duplicates.push(PendingDuplicate { font, existing });
+                return Ok(false);
+            }
+        }
+        let NewFont {
+            name,
+            data,
+            path,
+            families,
+            position,
+            replace,
+        } = font;
+        let uploaded = path.is_none();
+        let mut config = FontConfigMut::new(font_defs, custom);
+        let name = if replace {
+            config.replace_font(&name, data, path).map(|(name, _)| name)
+        } else {
+            config.insert_font(&name, data, path)
+        }
+        .map_err(|e| e.to_string())?;
+        config
+            .add_to_families(&families, &name, position)
+            .map_err(|e| e.to_string())?;
+        // Without a path, there's nothing load_custom_fonts could load the font back from
+        if uploaded {
+            self.uploaded.insert(name);
+        } else {
+            self.uploaded.remove(&name);
+        }
+        Ok(true)
+    }
+    /// Register the style variants entered into the variant form, like fonts added one by one
+    ///
+    /// The fonts go into the user namespace and are checked for duplicate contents. If any of
+    /// their fonts or families already exists, nothing is added.
+    fn add_style_variants(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+        request: variants::VariantRequest,
+    ) -> Result<(), String> {
+        let font_base = self
+            .font_name_for(&request.base)
+            .map_err(|e| e.to_string())?;
+        let files: Vec<_> = request
+            .files
+            .iter()
+            .map(|(style, path)| (*style, path.as_str()))
+            .collect();
+        let loaded = variants::load_style_variants(
+            &FontConfigMut::new(font_defs, custom.as_deref_mut()),
+            as_source(&self.font_source),
+            (&request.base, &font_base),
+            &files,
+            request.create_families,
+        )
+        .map_err(|e| e.to_string())?;
+        for variant in loaded {
+            let mut families = BTreeSet::new();
+            if let Some(family) = &variant.family {
+                let family = FontConfigMut::new(font_defs, custom.as_deref_mut())
+                    .add_family(family, &FamilyTemplate::CopyOfProportional)
+                    .map_err(|e| e.to_string())?;
+                families.insert(family);
+            }
+            let font = NewFont {
+                name: variant.name,
+                data: variant.data,
+                path: Some(variant.path),
+                families,
+                position: InsertPosition::Front,
+                replace: false,
+            };
+            self.insert_font(font_defs, custom.as_deref_mut(), font, true)?;
+        }
+        Ok(())
+    }
+    /// Add the font described by the add new font form
+    ///
+    /// A font whose contents are already loaded under another identifier isn't added, and the
+    /// user is asked what to do instead. A font of the same name is replaced, as the form only
+    /// gets here once the user confirmed that, or if confirming it is turned off.
+    fn add_font(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> Result<(), String> {
+        let name = self.new_font_name().map_err(|e| e.to_string())?;
+        let replace = font_defs.font_data.contains_key(&name);
+        if let Some(queue) = &mut self.load_queue {
+            queue.load(loading::PendingFont {
+                name,
+                path: std::mem::take(&mut self.path_buf),
+                families: self.quick_add_to.clone(),
+                position: self.insert_position,
+                replace,
+            });
+            self.name_buf.clear();
+            self.add_new = false;
+            return Ok(());
+        }
+        let data = source::load_custom_font(as_source(&self.font_source), &self.path_buf)
+            .map_err(|e| e.message)?;
+        let font = NewFont {
+            name,
+            data,
+            path: Some(self.path_buf.clone()),
+            families: self.quick_add_to.clone(),
+            position: self.insert_position,
+            replace,
+        };
+        // The form stays as it is if the font is rejected, so the user can correct it
+        self.insert_font(font_defs, custom, font, true)?;
+        self.name_buf.clear();
+        self.path_buf.clear();
+        self.add_new = false;
+        Ok(())
+    }
+    /// Add `font` from the catalog, into the user namespace like any other new font
+    fn add_bundled_font(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        font: &BundledFont,
+    ) -> Result<(), String> {
+        let name = self.font_name_for(&font.name).map_err(|e| e.to_string())?;
+        let font = NewFont {
+            name,
+            data: egui::FontData::from_static(font.data),
+            path: Some(font.custom_path()),
+            families: self.quick_add_to.clone(),
+            position: self.insert_position,
+            replace: false,
+        };
+        self.insert_font(font_defs, custom, font, true)?;
+        self.add_new = false;
+        Ok(())
+    }
+    /// Add a font picked from the system fonts, named after its family and style
+    #[cfg(feature = "system-fonts")]
+    fn add_system_font(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        font: SystemFont,
+    ) -> Result<(), String> {
+        let name = self
+            .font_name_for(&font.display_name())
+            .map_err(|e| e.to_string())?;
+        // Picking only read the font's names, so adding it checks the rest
+        let data = std::fs::read(&font.path).map_err(|e| e.to_string())?;
+        let font = NewFont {
+            name,
+            data: egui::FontData {
+                index: font.index,
+                ..egui::FontData::from_owned(data)
+            },
+            path: Some(custom_font_path(&font.path.to_string_lossy(), font.index)),
+            families: self.quick_add_to.clone(),
+            position: self.insert_position,
+            replace: false,
+        };
+        self.insert_font(font_defs, custom, font, true)?;
+        self.add_new = false;
+        Ok(())
+    }
+    /// Show the choice of families to also add new fonts to, and where in them
+    fn quick_add_ui(&mut self, ui: &mut egui::Ui, font_defs: &FontDefinitions) {
+        self.quick_add_to
+            .retain(|family| font_defs.families.contains_key(family));
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Also add to");
+            for family in font_defs.families.keys() {
+                let mut checked = self.quick_add_to.contains(family);
+                let re = ui.checkbox(&mut checked, family.to_string());
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Checkbox,
+                    &format!("Also add new fonts to {family} family"),
+                );
+                if re.changed() {
+                    if checked {
+                        self.quick_add_to.insert(family.clone());
+                    } else {
+                        self.quick_add_to.remove(family);
+                    }
+                }
+            }
+        });
+        if !self.quick_add_to.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label("as");
+                ui.selectable_value(&mut self.insert_position, InsertPosition::Front, "First")
+                    .on_hover_text("Use the font wherever it covers a character");
+                ui.selectable_value(&mut self.insert_position, InsertPosition::Back, "Fallback")
+                    .on_hover_text("Use the font for characters the other fonts don't cover");
+            });
+        }
+    }
+    /// Add the fonts whose files finished loading in the background
+    fn finish_loading(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+        msg: &mut FontDefsUiMsg,
+    ) {
+        let Some(queue) = &mut self.load_queue else {
+            return;
+        };
+        for loaded in queue.poll() {
+            let font = loaded.pending;
+            let index = split_custom_font_path(&font.path).1;
+            let data = loaded
+                .result
+                .map_err(|e| e.to_string())
+                .and_then(|data| format::ensure_loadable(data, index));
+            let data = match data {
+                Ok(data) => data,
+                Err(e) => {
+                    self.set_error(format!("Failed to load {}: {e}", font.name));
+                    continue;
+                }
+            };
+            let name = font.name.clone();
+            let font = NewFont {
+                name: font.name,
+                data: egui::FontData {
+                    index,
+                    ..egui::FontData::from_owned(data)
+                },
+                path: Some(font.path),
+                families: font.families,
+                position: font.position,
+                replace: font.replace,
+            };
+            match self.insert_font(font_defs, custom.as_deref_mut(), font, true) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    self.set_error(e);
+                    continue;
+                }
+            }
+            // Several fonts can finish in the same frame, and only one can be returned
+            self.push_event(FontDefsUiMsg::FontLoaded { name: name.clone() });
+            if matches!(msg, FontDefsUiMsg::None) {
+                *msg = FontDefsUiMsg::FontLoaded { name };
+            }
+        }
+    }
+    /// Add the font files dropped onto the window, named after the files
+    fn add_dropped_files(
+        &mut self,
+        files: Vec<egui::DroppedFile>,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+    ) {
+        for file in files {
+            if let Err(e) = self.add_dropped_file(font_defs, custom.as_deref_mut(), file) {
+                self.set_error(e);
+            }
+        }
+    }
+    fn add_dropped_file(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        file: egui::DroppedFile,
+    ) -> Result<(), String> {
+        // Native backends only set the path, web backends only the name
+        let file_name = match &file.path {
+            Some(path) => path.to_string_lossy().into_owned(),
+            None => file.name,
+        };
+        let file_path = std::path::Path::new(&file_name);
+        if !is_font_file(file_path) {
+            return Err(format!("{file_name} isn't a TrueType or OpenType font"));
+        }
+        let stem = file_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let name = self.font_name_for(&stem).map_err(|e| e.to_string())?;
+        let data = match (file.bytes, &file.path) {
+            (Some(bytes), _) => bytes.to_vec(),
+            (None, Some(path)) => {
+                source::load(as_source(&self.font_source), &path.to_string_lossy())
+                    .map_err(|e| format!("Can't read {file_name}: {}", e.message))?
+            }
+            (None, None) => return Err(format!("The contents of {file_name} weren't provided")),
+        };
+        let data = format::ensure_loadable(data, 0).map_err(|e| format!("{file_name}: {e}"))?;
+        let font = NewFont {
+            name,
+            data: egui::FontData::from_owned(data),
+            path: file.path.is_some().then_some(file_name),
+            families: BTreeSet::new(),
+            position: self.insert_position,
+            replace: false,
+        };
+        self.insert_font(font_defs, custom, font, true)?;
+        Ok(())
+    }
+    /// Show the metadata of the font at the path in the add new font form, offering to use its
+    /// name as the identifier
+    fn new_font_info_ui(&mut self, ui: &mut egui::Ui) {
+        if self
+            .new_font_info
+            .as_ref()
+            .is_none_or(|(path, _)| *path != self.path_buf)
+        {
+            let path = std::path::Path::new(&self.path_buf);
+            let mut info = None;
+            self.new_font_info_read = None;
+            // Only read paths that look like fonts, so typing a path doesn't read every prefix
+            if is_font_file(path) {
+                match &self.load_queue {
+                    Some(queue) => self.new_font_info_read = Some(queue.read(&self.path_buf)),
+                    None if self.font_source.is_some() || path.is_file() => {
+                        info = source::load(as_source(&self.font_source), &self.path_buf)
+                            .ok()
+                            .and_then(|data| FontInfo::from_bytes(&data, 0));
+                    }
+                    None => {}
+                }
+            }
+            self.found_new_font_info(info);
+        }
+        if let Some(read) = &self.new_font_info_read {
+            match read.try_recv() {
+                Ok(result) => {
+                    self.new_font_info_read = None;
+                    let info = result.ok().and_then(|data| FontInfo::from_bytes(&data, 0));
+                    self.found_new_font_info(info);
+                }
+                Err(mpsc::TryRecvError::Empty) => {
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                }
+                Err(mpsc::TryRecvError::Disconnected) => self.new_font_info_read = None,
+            }
+        }
+        let Some((_, Some(info))) = &self.new_font_info else {
+            return;
+        };
+        egui::Grid::new("new_font_info")
+            .num_columns(2)
+            .show(ui, |ui| info.grid_rows_ui(ui));
+        if let Some(name) = info.display_name() {
+            if self.name_buf != name
+                && ui
+                    .button(format!("Use \"{name}\" as identifier"))
+                    .on_hover_text("Use the name stored in the font")
+                    .clicked()
+            {
+                self.name_buf = name;
+            }
+        }
+    }
+    /// Record the metadata of the font at the path in the add new font form
+    fn found_new_font_info(&mut self, info: Option<FontInfo>) {
+        // Default to the name stored in the font, unless the user typed an identifier
+        let path = std::path::Path::new(&self.path_buf);
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy());
+        let untouched = self.name_buf.trim().is_empty() || stem.as_deref() == Some(&self.name_buf);
+        if let (true, Some(name)) = (untouched, info.as_ref().and_then(FontInfo::display_name)) {
+            self.name_buf = name;
+        }
+        self.new_font_info = Some((self.path_buf.clone(), info));
+    }
+    /// Add the fonts that finished uploading
+    fn finish_uploads(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) {
+        let Some(uploader) = &mut self.uploader else {
+            return;
+        };
+        let files = uploader
+            .poll_uploads()
+            .into_iter()
+            .map(|font| egui::DroppedFile {
+                name: font.file_name,
+                bytes: Some(font.data.into()),
+                ..Default::default()
+            })
+            .collect();
+        self.add_dropped_files(files, font_defs, custom);
+    }
+    /// The identifier the font of the add new font form would be added under
+    fn new_font_name(&self) -> Result<String, FontNameError> {
+        self.font_name_for(&self.name_buf)
+    }
+    /// The identifier a new font named `name` by the user would be added under
+    fn font_name_for(&self, name: &str) -> Result<String, FontNameError> {
+        let name = sanitize_font_name(name, self.normalize_name)?;
+        Ok(match &self.user_namespace {
+            Some(ns) => namespaced(ns, &name),
+            None => name,
+        })
+    }
+    /// Close the add new font form, discarding what was typed into it
+    fn cancel_add_form(&mut self) {
+        self.add_new = false;
+        self.name_buf.clear();
+        self.path_buf.clear();
+    }
+    /// Why the add new font form can't be submitted in its current state, if it can't
+    fn add_form_problem(&self) -> Option<String> {
+        match self.new_font_name() {
+            Err(e) => return Some(e.to_string()),
+            Ok(name)
+                if self
+                    .load_queue
+                    .as_ref()
+                    .is_some_and(|q| q.is_pending(&name)) =>
+            {
+                return Some(format!("{name} is still being loaded"));
+            }
+            Ok(_) => {}
+        }
+        if self.path_buf.trim().is_empty() {
+            return Some("Path to the font file can't be empty".into());
+        }
+        // Other sources may not take file paths
+        if self.font_source.is_none() && !std::path::Path::new(&self.path_buf).is_file() {
+            return Some("No file exists at the given path".into());
+        }
+        None
+    }
+    /// Ask for confirmation of the pending action, and perform it if the user confirms
+    fn confirm_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) {
+        let Some(action) = &self.pending_confirm else {
+            return;
+        };
+        let Some(confirmed) = confirm::confirm_ui(ui, self.high_contrast, action) else {
+            return;
+        };
+        let Some(action) = self.pending_confirm.take() else {
+            return;
+        };
+        if !confirmed {
+            return;
+        }
+        match action {
+            confirm::PendingAction::RemoveFont(name) => {
+                // The family entries stay, so restoring the font puts it back in its families
+                let mut config = FontConfigMut::new(font_defs, custom);
+                if let Ok((data, path)) = config.take_font(&name) {
+                    self.uploaded.remove(&name);
+                    self.trash.push(name, data, path);
+                }
+            }
+            confirm::PendingAction::RemoveFamily(family) => {
+                // It may have been removed since the confirmation was requested
+                let _ = FontConfigMut::new(font_defs, custom).remove_family(&family);
+            }
+            confirm::PendingAction::Restore(restore) => {
+                self.restore_requested = Some(restore);
+            }
+            // Asked by the window itself
+            confirm::PendingAction::CloseWindow => {}
+            confirm::PendingAction::OverwriteFont(name) => {
+                // The form may have been edited since the confirmation was requested
+                if self.new_font_name().as_ref() == Ok(&name) {
+                    if let Err(e) = self.add_font(font_defs, custom) {
+                        self.set_error(e);
+                    }
+                }
+            }
+        }
+    }
+    /// Show controls for switching between `pages` pages of fonts
+    fn pager_ui(&mut self, ui: &mut egui::Ui, pages: usize) {
+        let pages = pages.max(1);
+        self.font_page = self.font_page.min(pages - 1);
+        if pages == 1 {
+            return;
+        }
+        ui.horizontal(|ui| {
+            let re = ui.add_enabled(self.font_page > 0, egui::Button::new("◀"));
+            a11y_label(&re, egui::WidgetType::Button, "Previous page of fonts");
+            if re.clicked() {
+                self.font_page -= 1;
+            }
+            ui.label(format!("Page {} of {pages}", self.font_page + 1));
+            let re = ui.add_enabled(self.font_page + 1 < pages, egui::Button::new("▶"));
+            a11y_label(&re, egui::WidgetType::Button, "Next page of fonts");
+            if re.clicked() {
+                self.font_page += 1;
+            }
+        });
+    }
+    /// Show an error, and announce it to assistive technology the next time it's shown
+    fn set_error(&mut self, msg: String) {
+        if self.notifier.is_active() {
+            self.notifier.push(Notification::Error(msg));
+            return;
+        }
+        self.err_msg = msg;
+        self.announce_err = true;
+    }
+    /// Whether live apply should apply the fonts now, as they stopped changing
+    fn live_apply_due(&mut self, ctx: &egui::Context, font_defs: &FontDefinitions) -> bool {
+        /// How long the fonts have to stay unchanged before they're applied, in seconds
+        const DELAY: f64 = 0.3;
+        if !self.live_apply {
+            self.live_apply_changed_at = None;
+            self.live_apply_seen = None;
+            return false;
+        }
+        let now = ctx.input(|i| i.time);
+        // Edits made by the application count too, not just the ones made in the ui
+        let seen = self.live_apply_seen.as_ref();
+        if !seen.is_some_and(|seen| seen.matches(font_defs)) {
+            if seen.is_some() {
+                self.live_apply_changed_at = Some(now);
+            }
+            self.live_apply_seen = Some(DefsFingerprint::new(font_defs));
+        }
+        let Some(changed_at) = self.live_apply_changed_at else {
+            return false;
+        };
+        // Keep the change pending, to apply it once applying is allowed again
+        if self.apply_disabled.is_some() {
+            return false;
+        }
+        let waited = now - changed_at;
+        if waited < DELAY || ctx.input(|i| i.pointer.any_down()) {
+            ctx.request_repaint_after(Duration::from_secs_f64((DELAY - waited).max(0.05)));
+            return false;
+        }
+        self.live_apply_changed_at = None;
+        check_applicable(font_defs).is_ok()
+    }
+    /// Rename the font `old` to `new` along with the references to it, and the state kept about it
+    fn rename_font(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        old: String,
+        new: String,
+    ) {
+        if old == new {
+            self.renaming_font = None;
+            return;
+        }
+        let renames = BTreeMap::from([(old, new)]);
+        match self.rename_fonts(font_defs, custom, &renames) {
+            Ok(()) => self.renaming_font = None,
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+    /// Rename fonts according to `renames` with [`rename_fonts`], moving the fallback
+    /// exclusions, resolutions and details state kept about them to their new identifiers
+    fn rename_fonts(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+        renames: &BTreeMap<String, String>,
+    ) -> Result<(), RenameError> {
+        FontConfigMut::new(font_defs, custom).rename_fonts(renames)?;
+        for (new, ranges) in take_renamed(renames, |old| self.exclusions.remove(old)) {
+            self.exclusions.insert(new.clone(), ranges);
+        }
+        for (new, mut resolution) in take_renamed(renames, |old| self.resolutions.remove(old)) {
+            resolution.name.clone_from(new);
+            self.resolutions.insert(new.clone(), resolution);
+        }
+        for (new, ()) in take_renamed(renames, |old| self.uploaded.remove(old).then_some(())) {
+            self.uploaded.insert(new.clone());
+        }
+        self.details.rename(renames);
+        Ok(())
+    }
+    /// Show the current error until the user dismisses it
+    fn error_ui(&mut self, ui: &mut egui::Ui) {
+        if self.err_msg.is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            let re = status_label(ui, self.high_contrast, Severity::Error, &self.err_msg);
+            if std::mem::take(&mut self.announce_err) {
+                // Screen readers speak value change events, which makes this act like a live region
+                re.output_event(egui::output::OutputEvent::ValueChanged(
+                    egui::WidgetInfo::labeled(
+                        egui::WidgetType::Label,
+                        true,
+                        format!("Error: {}", self.err_msg),
+                    ),
+                ));
+            }
+            let re = ui.button("🗙").on_hover_text("Dismiss");
+            a11y_label(&re, egui::WidgetType::Button, "Dismiss error");
+            if re.clicked() {
+                self.err_msg.clear();
+            }
+        });
+    }
+    /// Show the lists of fonts and families
+    fn lists_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+        focus_first: bool,
+        back: &mut bool,
+    ) {
+        ui.horizontal(|ui| {
+            let re = ui.add(
+                egui::TextEdit::singleline(&mut self.list_filter)
+                    .hint_text("🔍 Filter fonts and families"),
+            );
+            a11y_text_label(&re, "Filter fonts and families", &self.list_filter);
+            self.text_focused |= re.has_focus();
+            if re.changed() {
+                self.scroll_to_match = !self.list_filter.is_empty();
+            }
+            if !self.list_filter.is_empty() {
+                let re = ui.button("✖").on_hover_text("Clear");
+                a11y_label(&re, egui::WidgetType::Button, "Clear filter");
+                if re.clicked() {
+                    self.list_filter.clear();
+                }
+            }
+        });
+        let filter = self.list_filter.trim().to_owned();
+        let mut fonts_section = CollapsingState::load_with_default_open(
+            ui.ctx(),
+            ui.make_persistent_id("fonts_section"),
+            true,
+        );
+        let plus_re = ui
+            .horizontal(|ui| {
+                fonts_section.show_toggle_button(ui, paint_default_icon);
+                ui.heading("Fonts");
+                let re = ui.button("+").on_hover_text("Add new font");
+                a11y_label(&re, egui::WidgetType::Button, "Open add new font form");
+                self.help.icon(ui, HelpTopic::Fonts);
+                if focus_first {
+                    re.request_focus();
+                }
+                if re.clicked() {
+                    self.add_new = true;
+                    self.focus_name_field = true;
+                    fonts_section.set_open(true);
+                }
+                re
+            })
+            .inner;
+        fonts_section.store(ui.ctx());
+        match self.add_form_placement {
+            AddFormPlacement::Inline => {
+                if self.add_new {
+                    self.add_form_ui(ui, font_defs, custom.as_deref_mut(), back);
+                }
+            }
+            AddFormPlacement::Popup => {
+                let popup_id = ui.make_persistent_id("add_font_popup");
+                if self.add_new {
+                    ui.memory_mut(|m| m.open_popup(popup_id));
+                }
+                egui::popup_below_widget(
+                    ui,
+                    popup_id,
+                    &plus_re,
+                    egui::PopupCloseBehavior::CloseOnClickOutside,
+                    |ui| {
+                        ui.set_min_width(250.0);
+                        self.add_form_ui(ui, font_defs, custom.as_deref_mut(), back);
+                    },
+                );
+                let popup_open = ui.memory(|m| m.is_popup_open(popup_id));
+                if self.add_new && !popup_open {
+                    self.add_new = false;
+                } else if !self.add_new && popup_open {
+                    ui.memory_mut(|m| m.close_popup());
+                }
+            }
+            AddFormPlacement::Collapsible => {}
+        }
+        self.error_ui(ui);
+        self.duplicates_ui(ui, font_defs, custom.as_deref_mut());
+        self.confirm_ui(ui, font_defs, custom.as_deref_mut());
+        if let Some(form) = &mut self.adopting {
+            let (done, skipped) = ui
+                .group(|ui| {
+                    ui.strong("Import fonts");
+                    form.ui(ui, font_defs)
+                })
+                .inner;
+            if done {
+                self.adopting = None;
+            }
+            if !skipped.is_empty() {
+                self.set_error(format!(
+                    "Skipped fonts whose names are taken by different fonts: {}",
+                    skipped.join(", ")
+                ));
+            }
+        }
+        if fonts_section.is_open() {
+            self.fonts_ui(ui, font_defs, custom, back, &filter);
+        }
+        ui.separator();
+        let mut families_section = CollapsingState::load_with_default_open(
+            ui.ctx(),
+            ui.make_persistent_id("families_section"),
+            true,
+        );
+        ui.horizontal(|ui| {
+            families_section.show_toggle_button(ui, paint_default_icon);
+            ui.heading("Families");
+            self.help.icon(ui, HelpTopic::Families);
+        });
+        families_section.store(ui.ctx());
+        if families_section.is_open() {
+            self.families_ui(ui, font_defs, &filter);
+        }
+    }
+    /// Show the list of fonts, and the forms for managing them
+    fn fonts_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+        back: &mut bool,
+        filter: &str,
+    ) {
+        let touch = self.layout_mode == LayoutMode::Touch;
+        let checks = self.checks.results();
+        let statuses = &checks.statuses;
+        health::dashboard_ui(ui, statuses, &mut self.status_filter);
+        namespace::filter_ui(ui, font_defs, &mut self.namespace_filter);
+        let delete_pressed = ui.input(|i| i.key_pressed(egui::Key::Delete));
+        let cfg_issues = &checks.cfg_issues;
+        let mut reload_err = None;
+        let mut rename = None;
+        // Fonts passing the filters so far, for pagination
+        let mut shown = 0;
+        font_defs.font_data.retain(|name, font| {
+            if self
+                .status_filter
+                .is_some_and(|filter| statuses.get(name) != Some(&filter))
+                || !namespace::matches_filter(self.namespace_filter.as_deref(), name)
+                || !matches_list_filter(filter, name)
+            {
+                return true;
+            }
+            shown += 1;
+            if let Some(per_page) = self.fonts_per_page {
+                if (shown - 1) / per_page != self.font_page {
+                    return true;
+                }
+            }
+            let mut remove = false;
+            let row = ui.horizontal(|ui| {
+                let mut label_focused = false;
+                if let Some((_, new_name)) = self.renaming_font.as_mut().filter(|(n, _)| n == name)
+                {
+                    let re = ui.add(egui::TextEdit::singleline(new_name).desired_width(150.0));
+                    a11y_text_label(&re, &format!("New identifier of font {name}"), new_name);
+                    self.text_focused |= re.has_focus();
+                    if re.lost_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            rename = Some((name.clone(), new_name.trim().to_owned()));
+                        } else {
+                            self.renaming_font = None;
+                        }
+                    } else if !re.has_focus() {
+                        re.request_focus();
+                    }
+                } else {
+                    let label_re = ui
+                        .add(egui::Label::new(name).sense(egui::Sense::focusable_noninteractive()));
+                    label_focused = label_re.has_focus();
+                }
+                let renaming = self.renaming_font.as_ref().is_some_and(|(n, _)| n == name);
+                let re = ui.selectable_label(renaming, "✏").on_hover_text("Rename");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Rename font {name}"),
+                );
+                if re.clicked() {
+                    self.renaming_font = (!renaming).then(|| (name.clone(), name.clone()));
+                }
+                info::class_badges_ui(ui, font);
+                if cfg_issues.contains(&FontCfgIssue::UnassignedFont(name.clone())) {
+                    issue_icon(ui, "Not in any family, so it is never used");
+                }
+                let expanded = self.details.is_expanded(name);
+                let re = ui
+                    .selectable_label(expanded, "ℹ")
+                    .on_hover_text("Show details");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Show details of font {name}"),
+                );
+                if re.clicked() {
+                    self.details.toggle(name);
+                }
+                let re = ui.button("…").on_hover_text("Replace file…");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Replace file of font {name}"),
+                );
+                if re.clicked() {
+                    let path = custom
+                        .as_ref()
+                        .and_then(|custom| custom.get(name))
+                        .cloned()
+                        .unwrap_or_default();
+                    self.replacing = Some((name.clone(), path));
+                }
+                let re = ui.button("-");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Remove font {name}"),
+                );
+                if re.clicked() || (delete_pressed && (label_focused || re.has_focus())) {
+                    remove = true;
+                }
+            });
+            if let Some((_, path)) = self.replacing.as_mut().filter(|(n, _)| n == name) {
+                let mut done = false;
+                ui.horizontal(|ui| {
+                    let re = ui.add(
+                        egui::TextEdit::singleline(path).hint_text("Path to replacement file"),
+                    );
+                    a11y_text_label(&re, &format!("Path to replacement file for {name}"), path);
+                    self.text_focused |= re.has_focus();
+                    if ui.button("Replace").clicked() {
+                        match reload_font_data(as_source(&self.font_source), font, path) {
+                            Ok(new) => {
+                                *font = new;
+                                if let Some(custom) = &mut custom {
+                                    custom.insert(name.clone(), path.clone());
+                                }
+                                done = true;
+                            }
+                            Err(e) => {
+                                reload_err =
+                                    Some(format!("Failed to replace {name}: {}", e.message));
+                            }
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        done = true;
+                    }
+                });
+                if done {
+                    self.replacing = None;
+                }
+            } else if let Some(path) = custom.as_mut().and_then(|custom| custom.get_mut(name)) {
+                ui.horizontal(|ui| {
+                    let re = ui.add(egui::TextEdit::singleline(path).hint_text("Path to font"));
+                    a11y_text_label(&re, &format!("Path to font {name}"), path);
+                    self.text_focused |= re.has_focus();
+                    let re = ui
+                        .button("⟳")
+                        .on_hover_text("Reload the font from this path");
+                    a11y_label(
+                        &re,
+                        egui::WidgetType::Button,
+                        &format!("Reload font {name}"),
+                    );
+                    if re.clicked() {
+                        match reload_font_data(as_source(&self.font_source), font, path) {
+                            Ok(new) => *font = new,
+                            Err(e) => {
+                                reload_err =
+                                    Some(format!("Failed to reload {name}: {}", e.message));
+                            }
+                        }
+                    }
+                });
+            }
+            if let Some(resolution) = self.resolutions.get(name) {
+                let path = custom.as_ref().and_then(|custom| custom.get(name));
+                resolve::resolution_ui(ui, resolution, path.map(String::as_str));
+            }
+            if self.details.is_expanded(name) {
+                let index = font.index;
+                ui.indent(("details", name), |ui| {
+                    details::font_details_ui(
+                        ui,
+                        name,
+                        font,
+                        &mut self.details,
+                        &mut self.exclusions,
+                    );
+                });
+                // Keep the face index when the font is loaded from its path again
+                let path = custom.as_mut().and_then(|custom| custom.get_mut(name));
+                if let (Some(path), true) = (path, font.index != index) {
+                    *path = custom_font_path(split_custom_font_path(path).0, font.index);
+                }
+            }
+            if !filter.is_empty() && std::mem::take(&mut self.scroll_to_match) {
+                row.response.scroll_to_me(Some(egui::Align::Center));
+            }
+            if touch && swiped_away(ui, &row.response, egui::Id::new(("swipe_font", name))) {
+                remove = true;
+            }
+            if remove && self.confirmation.remove_font {
+                self.pending_confirm = Some(confirm::PendingAction::RemoveFont(name.clone()));
+                remove = false;
+            }
+            if remove {
+                let path = custom.as_mut().and_then(|custom| custom.remove(name));
+                self.trash.push(name.clone(), font.clone(), path);
+            }
+            !remove
+        });
+        if let Some(e) = reload_err {
+            self.set_error(e);
+        }
+        if let Some((old, new)) = rename {
+            self.rename_font(font_defs, custom.as_deref_mut(), old, new);
+        }
+        if let Some(per_page) = self.fonts_per_page {
+            self.pager_ui(ui, shown.div_ceil(per_page));
+        }
+        if let Some(queue) = &self.load_queue {
+            for font in queue.pending() {
+                ui.horizontal(|ui| {
+                    ui.label(&font.name);
+                    ui.spinner();
+                })
+                .response
+                .on_hover_text(format!("Loading {}", font.path));
+            }
+        }
+        if !self.trash.is_empty() {
+            let restore_err = egui::CollapsingHeader::new(format!("Trash ({})", self.trash.len()))
+                .id_salt("trash")
+                .show(ui, |ui| self.trash.ui(ui, font_defs, custom.as_deref_mut()))
+                .body_returned
+                .flatten();
+            if let Some(e) = restore_err {
+                self.set_error(e);
+            }
+        }
+        if self.add_form_placement == AddFormPlacement::Collapsible {
+            let re = egui::CollapsingHeader::new("Add new font")
+                .open(Some(self.add_new))
+                .show(ui, |ui| {
+                    self.add_form_ui(ui, font_defs, custom.as_deref_mut(), back);
+                });
+            if re.header_response.clicked() {
+                self.add_new = !self.add_new;
+                self.focus_name_field = self.add_new;
+            }
+        }
+        let variants = egui::CollapsingHeader::new("Add style variants")
+            .show(ui, |ui| self.variant_form.ui(ui, self.normalize_name))
+            .body_returned
+            .flatten();
+        if let Some(request) = variants {
+            match self.add_style_variants(font_defs, custom.as_deref_mut(), request) {
+                Ok(()) => self.variant_form.clear(),
+                Err(e) => self.set_error(e),
+            }
+        }
+        let renames = egui::CollapsingHeader::new("Batch rename")
+            .show(ui, |ui| self.batch_rename.ui(ui, font_defs))
+            .body_returned
+            .flatten();
+        let renamed = renames.map(|renames| self.rename_fonts(font_defs, custom, &renames?));
+        match renamed {
+            Some(Ok(())) => self.batch_rename = Default::default(),
+            Some(Err(e)) => self.set_error(e.to_string()),
+            None => {}
+        }
+    }
+    /// Show the families with their fonts
+    fn families_ui(&mut self, ui: &mut egui::Ui, font_defs: &mut FontDefinitions, filter: &str) {
+        let touch = self.layout_mode == LayoutMode::Touch;
+        let delete_pressed = ui.input(|i| i.key_pressed(egui::Key::Delete));
+        let renamed = find_renamed_builtin_fonts(font_defs);
+        if !renamed.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                status_label(
+                    ui,
+                    self.high_contrast,
+                    Severity::Warning,
+                    &format!("{} entries refer to renamed built-in fonts", renamed.len()),
+                )
+                .on_hover_text(
+                    renamed
+                        .iter()
+                        .map(|r| format!("{}: {} → {}", r.family, r.old_name, r.new_name))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+                let re = ui.button("Remap");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    "Remap renamed built-in fonts",
+                );
+                if re.clicked() {
+                    remap_renamed_builtin_fonts(font_defs);
+                }
+            });
+        }
+        preview::ppp_toggle_ui(ui, &mut self.pending_preview);
+        let preview_ok = self.pending_preview.prepare(ui.ctx(), font_defs).is_ok();
+        let mut push_new_to = None;
+        let mut bulk_adjust = None;
+        let mut rename = None;
+        self.coverage.prune(&font_defs.font_data);
+        let font_names: BTreeSet<String> = font_defs.font_data.keys().cloned().collect();
+        let font_names = &font_names;
+        let checks = self.checks.results();
+        let cfg_issues = &checks.cfg_issues;
+        let order = self.family_display_order(font_defs);
+        let mut removed_families = Vec::new();
+        let mut move_family = None;
+        for (family_idx, family) in order.iter().enumerate() {
+            let Some(fonts) = font_defs.families.get_mut(family) else {
+                continue;
+            };
+            // All entries of a family are shown if its name matches
+            let family_matches = matches_list_filter(filter, &family.to_string());
+            if !family_matches && !fonts.iter().any(|f| matches_list_filter(filter, f)) {
+                continue;
+            }
+            let mut retain = true;
+            ui.horizontal(|ui| {
+                ui.label(family.to_string());
+                let re = ui
+                    .add_enabled(family_idx > 0, egui::Button::new("⏶"))
+                    .on_hover_text("Show this family further up");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Move {family} family up"),
+                );
+                if re.clicked() {
+                    move_family = Some((family_idx, family_idx - 1));
+                }
+                let re = ui
+                    .add_enabled(family_idx + 1 < order.len(), egui::Button::new("⏷"))
+                    .on_hover_text("Show this family further down");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Move {family} family down"),
+                );
+                if re.clicked() {
+                    move_family = Some((family_idx, family_idx + 1));
+                }
+                if let FontFamily::Name(name) = family {
+                    let renaming = self
+                        .renaming_family
+                        .as_ref()
+                        .is_some_and(|(f, _)| f == family);
+                    let re = ui.selectable_label(renaming, "✏").on_hover_text("Rename");
+                    a11y_label(
+                        &re,
+                        egui::WidgetType::Button,
+                        &format!("Rename {family} family"),
+                    );
+                    if re.clicked() {
+                        self.renaming_family =
+                            (!renaming).then(|| (family.clone(), name.to_string()));
+                    }
+                }
+                if fonts.is_empty() {
+                    issue_icon(
+                        ui,
+                        "Text using this family can't be rendered until it has fonts",
+                    );
+                }
+                let re = ui.button("+");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Add font to {family} family"),
+                );
+                if re.clicked() {
+                    push_new_to = Some(family.clone());
+                }
+                self.help.icon(ui, HelpTopic::FallbackOrder);
+                let tweaking = self
+                    .bulk_tweak
+                    .as_ref()
+                    .is_some_and(|f| f.family == *family);
+                let re = ui
+                    .selectable_label(tweaking, "⚙")
+                    .on_hover_text("Adjust the tweaks of all fonts in the family");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Adjust tweaks of {family} family"),
+                );
+                if re.clicked() {
+                    self.bulk_tweak =
+                        (!tweaking).then(|| tweaks::BulkTweakForm::new(family.clone()));
+                }
+                let re = ui.button("-");
+                a11y_label(
+                    &re,
+                    egui::WidgetType::Button,
+                    &format!("Remove {family} family"),
+                );
+                if re.clicked() || (delete_pressed && re.has_focus()) {
+                    if self.confirmation.remove_family {
+                        self.pending_confirm =
+                            Some(confirm::PendingAction::RemoveFamily(family.clone()));
+                    } else {
+                        retain = false;
+                    }
+                }
+            });
+            if let Some((_, new_name)) = self.renaming_family.as_mut().filter(|(f, _)| f == family)
+            {
+                let re = ui.text_edit_singleline(new_name);
+                a11y_text_label(&re, &format!("New name of {family} family"), new_name);
+                self.text_focused |= re.has_focus();
+                if re.lost_focus() {
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        rename = Some((family.clone(), new_name.trim().to_owned()));
+                    } else {
+                        self.renaming_family = None;
+                    }
+                } else if !re.has_focus() {
+                    re.request_focus();
+                }
+            }
+            coverage::family_coverage_ui(ui, &mut self.coverage, &font_defs.font_data, fonts);
+            let picked_sample = if preview_ok {
+                let text = self
+                    .preview_texts
+                    .get(family)
+                    .map_or(preview::default_sample_text(family), |s| s.as_str());
+                ui.horizontal(|ui| {
+                    let re = preview::family_sample_ui(ui, &mut self.pending_preview, family, text)
+                        .on_hover_text("Click to change the preview text");
+                    if re.clicked() {
+                        self.editing_preview_text = Some(family.clone());
+                    }
+                    preview::sample_presets_menu(ui)
+                })
+                .inner
+            } else {
+                None
+            };
+            if let Some(text) = picked_sample {
+                self.preview_texts.insert(family.clone(), text.to_owned());
+            }
+            if self.editing_preview_text.as_ref() == Some(family) {
+                let text = self
+                    .preview_texts
+                    .entry(family.clone())
+                    .or_insert_with(|| preview::default_sample_text(family).to_owned());
+                let re = ui.text_edit_singleline(text);
+                a11y_text_label(&re, &format!("Preview text of {family} family"), text);
+                self.text_focused |= re.has_focus();
+                if re.lost_focus() {
+                    self.editing_preview_text = None;
+                } else {
+                    re.request_focus();
+                }
+            }
+            if let Some(form) = self.bulk_tweak.as_mut().filter(|f| f.family == *family) {
+                ui.indent(("bulk_tweak", family), |ui| {
+                    self.help.icon(ui, HelpTopic::Tweaks);
+                    if let Some(adjustment) = form.ui(ui) {
+                        bulk_adjust = Some((family.clone(), adjustment));
+                    }
+                });
+            }
+            let mut idx = 0;
+            let len = fonts.len();
+            let mut moved = None;
+            fonts.retain_mut(|font_name| {
+                let mut retain = true;
+                idx += 1;
+                if !family_matches && !matches_list_filter(filter, font_name) {
+                    return true;
+                }
+                let row = ui.horizontal(|ui| {
+                    let drag = FamilyEntryDrag {
+                        family: family.clone(),
+                        index: idx - 1,
+                    };
+                    ui.dnd_drag_source(
+                        egui::Id::new(("family_entry_drag", &*family, idx)),
+                        drag,
+                        |ui| ui.label("☰"),
+                    )
+                    .response
+                    .on_hover_text("Drag to change the priority");
+                    if self.show_priorities {
+                        let mut priority = idx;
+                        ui.add(egui::DragValue::new(&mut priority).range(1..=len))
+                            .on_hover_text("Priority. Fonts with lower numbers are tried first.");
+                        if priority != idx {
+                            moved = Some((idx - 1, priority - 1));
+                        }
+                    }
+                    let selected = if font_name.is_empty() {
+                        "(no font)"
+                    } else {
+                        font_name.as_str()
+                    };
+                    let re = egui::ComboBox::from_id_salt(("family_entry", &*family, idx))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for name in font_names {
+                                ui.selectable_value(font_name, name.clone(), name.as_str());
+                            }
+                        })
+                        .response;
+                    a11y_label(
+                        &re,
+                        egui::WidgetType::ComboBox,
+                        &format!("Font {idx} of {family} family: {font_name}"),
+                    );
+                    if !font_names.contains(font_name) {
+                        let text = if font_name.is_empty() {
+                            "Pick a font".to_owned()
+                        } else {
+                            format!("There is no font called {font_name}")
+                        };
+                        issue_icon(ui, &text);
+                    } else if cfg_issues.contains(&FontCfgIssue::DuplicateEntry {
+                        family: family.clone(),
+                        name: font_name.clone(),
+                    }) {
+                        issue_icon(
+                            ui,
+                            "Listed more than once. Only the first entry has an effect.",
+                        );
+                    }
+                    let re = ui.button("-");
+                    a11y_label(
+                        &re,
+                        egui::WidgetType::Button,
+                        &format!("Remove {font_name} from {family} family"),
+                    );
+                    if re.clicked() || (delete_pressed && re.has_focus()) {
+                        retain = false;
+                    }
+                });
+                let payload = row
+                    .response
+                    .dnd_hover_payload::<FamilyEntryDrag>()
+                    .filter(|drag| drag.family == *family);
+                if let Some(drag) = payload {
+                    // Show where the dragged entry would end up
+                    let rect = row.response.rect;
+                    let y = if drag.index < idx - 1 {
+                        rect.bottom()
+                    } else {
+                        rect.top()
+                    };
+                    let stroke = ui.visuals().selection.stroke;
+                    ui.painter().hline(rect.x_range(), y, stroke);
+                }
+                let payload = row
+                    .response
+                    .dnd_release_payload::<FamilyEntryDrag>()
+                    .filter(|drag| drag.family == *family);
+                if let Some(drag) = payload {
+                    moved = Some((drag.index, idx - 1));
+                }
+                if !filter.is_empty() && std::mem::take(&mut self.scroll_to_match) {
+                    row.response.scroll_to_me(Some(egui::Align::Center));
+                }
+                let swipe_id = egui::Id::new(("swipe_family_entry", &*family, idx));
+                if touch && swiped_away(ui, &row.response, swipe_id) {
+                    retain = false;
+                }
+                retain
+            });
+            // Positions shift when entries are removed, so only move if none were
+            if let Some((from, to)) = moved.filter(|_| fonts.len() == len) {
+                let font = fonts.remove(from);
+                fonts.insert(to, font);
+            }
+            if !retain {
+                removed_families.push(family.clone());
+            }
+        }
+        let mut config = FontConfigMut::new(font_defs, None);
+        for family in &removed_families {
+            let _ = config.remove_family(family);
+        }
+        if let Some((from, to)) = move_family {
+            let mut order = order;
+            let family = order.remove(from);
+            order.insert(to, family);
+            self.family_order = order;
+        }
+        if let Some((family, adjustment)) = bulk_adjust {
+            adjust_family_tweaks(font_defs, &family, adjustment);
+        }
+        if let Some((family, new_name)) = rename {
+            match FontConfigMut::new(font_defs, None).rename_family(&family, &new_name) {
+                Ok(()) => {
+                    let new_family = FontFamily::Name(new_name.into());
+                    if let Some(text) = self.preview_texts.remove(&family) {
+                        self.preview_texts.insert(new_family.clone(), text);
+                    }
+                    if let Some(listed) = self.family_order.iter_mut().find(|f| **f == family) {
+                        *listed = new_family;
+                    }
+                    self.renaming_family = None;
+                }
+                Err(e) => self.set_error(e.to_string()),
+            }
+        }
+        if let Some(key) = push_new_to {
+            font_defs
+                .families
+                .get_mut(&key)
+                .unwrap()
+                .push(String::new());
+        }
+        let family_err = egui::CollapsingHeader::new("New family")
+            .show(ui, |ui| self.new_family_form.ui(ui, font_defs))
+            .body_returned
+            .flatten();
+        if let Some(e) = family_err {
+            self.set_error(e);
+        }
+    }
+    /// Show the sections for inspecting the font definitions
+    fn inspection_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+    ) {
+        let checks = self.checks.results();
+        let issues = &checks.issues;
+        egui::CollapsingHeader::new(format!("Diagnostics ({})", issues.len()))
+            .id_salt("diagnostics")
+            .show(ui, |ui| {
+                validate::issues_ui(ui, self.high_contrast, issues);
+            });
+        egui::CollapsingHeader::new("Live preview")
+            .default_open(true)
+            .show(ui, |ui| {
+                self.live_preview
+                    .ui(ui, &mut self.pending_preview, font_defs);
+            });
+        let action = egui::CollapsingHeader::new("Why is this character a box?")
+            .id_salt("diagnose_missing_glyphs")
+            .show(ui, |ui| self.diagnose_form.ui(ui, font_defs))
+            .body_returned
+            .flatten();
+        match action {
+            Some(diagnose::DiagnoseAction::AddToFamilies(name, families)) => {
+                let mut config = FontConfigMut::new(font_defs, None);
+                if let Err(e) = config.add_to_families(&families, &name, InsertPosition::Back) {
+                    self.set_error(e.to_string());
+                }
+            }
+            #[cfg(feature = "system-fonts")]
+            Some(diagnose::DiagnoseAction::AddSystemFont(font)) => {
+                if let Err(e) = self.add_system_font(font_defs, custom.as_deref_mut(), font) {
+                    self.set_error(e);
+                }
+            }
+            #[cfg(feature = "system-fonts")]
+            Some(diagnose::DiagnoseAction::SearchSystemFonts(missing)) => {
+                let spawner = self.spawner();
+                self.diagnose_form.search_system_fonts(missing, &spawner);
+            }
+            None => {}
+        }
+        egui::CollapsingHeader::new("UI preview").show(ui, |ui| {
+            match self.pending_preview.prepare(ui.ctx(), font_defs) {
+                Ok(()) => sandbox::mock_ui(ui, &mut self.pending_preview),
+                Err(e) => {
+                    status_label(ui, self.high_contrast, Severity::Error, &e);
+                }
+            }
+        });
+        egui::CollapsingHeader::new("Monitors").show(ui, |ui| {
+            self.monitor_previews.ui(ui, font_defs);
+        });
+        egui::CollapsingHeader::new("Glyphs changed by apply").show(ui, |ui| {
+            glyph_diff::glyph_diff_ui(ui, &mut self.glyph_diffs, font_defs);
+        });
+        egui::CollapsingHeader::new("Licenses").show(ui, |ui| {
+            info::license_ui(ui, font_defs);
+        });
+        egui::CollapsingHeader::new("Line heights").show(ui, |ui| {
+            preview::line_height_ui(ui, &mut self.pending_preview, font_defs);
+        });
+        egui::CollapsingHeader::new("Usage").show(ui, |ui| {
+            usage::usage_ui(ui, font_defs);
+        });
+        egui::CollapsingHeader::new("Zoom and text size").show(ui, |ui| {
+            zoom::zoom_ui(ui, self.high_contrast);
+        });
+        egui::CollapsingHeader::new("Baseline consistency").show(ui, |ui| {
+            metrics::report_ui(ui, font_defs);
+        });
+        egui::CollapsingHeader::new("Calibrate baselines").show(ui, |ui| {
+            self.calibration.ui(ui, font_defs);
+        });
+        if let Some(baseline) = &self.baseline {
+            let import_err = egui::CollapsingHeader::new("Changes from defaults")
+                .show(ui, |ui| {
+                    let source = as_source(&self.font_source);
+                    let custom = custom.as_deref_mut();
+                    self.diff_form.ui(ui, source, baseline, font_defs, custom)
+                })
+                .body_returned
+                .flatten();
+            if let Some(e) = import_err {
+                self.set_error(e);
+            }
+        }
+        let active = self.presets.active().map(str::to_owned);
+        let preset_err = egui::CollapsingHeader::new("Presets")
+            .show(ui, |ui| {
+                self.presets_form
+                    .ui(ui, &mut self.presets, font_defs, custom)
+            })
+            .body_returned
+            .flatten();
+        if let Some(e) = preset_err {
+            self.set_error(e);
+        }
+        if let Some(name) = self.presets.active() {
+            if active.as_deref() != Some(name) {
+                self.notifier
+                    .push(Notification::PresetSwitched(name.to_owned()));
+            }
+        }
+    }
+    /// Warning that another instance holds the config lock, if it does
+    #[cfg(feature = "fs")]
+    fn config_lock_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(lock) = &mut self.config_lock else {
+            return;
+        };
+        lock.refresh();
+        if lock.is_held() {
+            return;
+        }
+        let holder = match lock.holder() {
+            Some(pid) => format!("process {pid}"),
+            None => "another process".to_owned(),
+        };
+        status_label(
+            ui,
+            self.high_contrast,
+            Severity::Warning,
+            "⚠ Edited elsewhere",
+        )
+        .on_hover_text(format!(
+            "The font config is also being edited by {holder}. \
+             Saving may overwrite its changes, or it may overwrite yours."
+        ));
+        if ui
+            .small_button("Retry")
+            .on_hover_text("Check again whether the other instance is still editing")
+            .clicked()
+        {
+            lock.retry();
+        }
+    }
+    /// Show the apply and save buttons
+    fn footer_ui(&mut self, ui: &mut egui::Ui, msg: &mut FontDefsUiMsg) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            let mut re = ui
+                .add_enabled(self.apply_disabled.is_none(), egui::Button::new("✅ Apply"))
+                .on_hover_text("Apply the font config to the current egui context");
+            if let Some(reason) = &self.apply_disabled {
+                re = re.on_disabled_hover_text(reason);
+            }
+            a11y_label(&re, egui::WidgetType::Button, "Apply");
+            if re.clicked() {
+                self.apply_requested = true;
+            }
+            let mut re = ui
+                .add_enabled(
+                    self.apply_disabled.is_none(),
+                    egui::Checkbox::new(&mut self.live_apply, "Live"),
+                )
+                .on_hover_text("Apply automatically after every change");
+            if let Some(reason) = &self.apply_disabled {
+                re = re.on_disabled_hover_text(reason);
+            }
+            a11y_label(&re, egui::WidgetType::Checkbox, "Live apply");
+            if re.changed() && self.live_apply {
+                // Apply what was changed before it was turned on
+                self.live_apply_changed_at = Some(ui.input(|i| i.time));
+            }
+            let re = ui
+                .button("💾 Save")
+                .on_hover_text("Save the custom font paths");
+            a11y_label(&re, egui::WidgetType::Button, "Save");
+            if re.clicked() {
+                *msg = FontDefsUiMsg::SaveRequest;
+                self.trash.clear();
+            }
+            #[cfg(feature = "fs")]
+            self.config_lock_ui(ui);
+            for (restore, label, hover) in [
+                (
+                    confirm::Restore::Revert,
+                    "Revert",
+                    "Restore the fonts the dialog was opened with",
+                ),
+                (
+                    confirm::Restore::Reset,
+                    "Reset to defaults",
+                    "Restore egui's default fonts",
+                ),
+            ] {
+                if ui.button(label).on_hover_text(hover).clicked() {
+                    if self.confirmation.reset {
+                        self.pending_confirm = Some(confirm::PendingAction::Restore(restore));
+                    } else {
+                        self.restore_requested = Some(restore);
+                    }
+                }
+            }
+            let re = ui.add_enabled(self.history.can_undo(), egui::Button::new("⟲ Undo"));
+            a11y_label(&re, egui::WidgetType::Button, "Undo");
+            if re.clicked() {
+                self.undo();
+            }
+            let re = ui.add_enabled(self.history.can_redo(), egui::Button::new("⟳ Redo"));
+            a11y_label(&re, egui::WidgetType::Button, "Redo");
+            if re.clicked() {
+                self.redo();
+            }
+        });
+        if let Some(set_fonts_time) = self.last_apply {
+            stats::apply_stats_ui(ui, set_fonts_time);
+        }
+    }
+    /// Show the font definitions ui
+    ///
+    /// # Arguments
+    ///
+    /// - `ui`: The [`egui::Ui`] to show the ui on
+    /// - `font_defs`: The [`egui::FontDefinitions`] to edit
+    /// - `custom`: An optional [`CustomFontPaths`] to save custom font paths to
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> FontDefsUiMsg {
+        self.show_with_drops(ui, font_defs, custom, Vec::new())
+    }
+    /// Like [`Self::show`], also adding the font files in `dropped` as part of the frame's edits
+    fn show_with_drops(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+        dropped: Vec<egui::DroppedFile>,
+    ) -> FontDefsUiMsg {
+        let mut msg = FontDefsUiMsg::None;
+        self.history
+            .perform_request(font_defs, custom.as_deref_mut());
+        #[cfg(feature = "compression")]
+        self.trash.compact(&self.spawner());
+        // Cheap, as the font data is reference counted
+        let undo_before = undo::State::new(font_defs, custom.as_deref());
+        if self.opened_with.is_none() {
+            self.opened_with = Some(undo_before.clone());
+        }
+        self.add_dropped_files(dropped, font_defs, custom.as_deref_mut());
+        self.checks
+            .update(font_defs, custom.as_deref(), &self.validators);
+        if self.clean.is_none() {
+            self.clean = Some(DefsFingerprint::new(font_defs));
+        }
+        self.text_focused = false;
+        let touch = self.layout_mode == LayoutMode::Touch;
+        if touch {
+            let spacing = ui.spacing_mut();
+            spacing.interact_size.y = spacing.interact_size.y.max(40.0);
+            spacing.button_padding = egui::vec2(14.0, 10.0);
+            spacing.item_spacing = egui::vec2(12.0, 10.0);
+        }
+        let (focus_first, mut back) = if self.gamepad_nav {
+            ui.input(|i| {
+                let directional = [
+                    egui::Key::ArrowUp,
+                    egui::Key::ArrowDown,
+                    egui::Key::ArrowLeft,
+                    egui::Key::ArrowRight,
+                ]
+                .into_iter()
+                .any(|key| i.key_pressed(key));
+                (directional, i.key_pressed(egui::Key::Escape))
+            })
+        } else {
+            (false, false)
+        };
+        let focus_first = focus_first && ui.memory(|m| m.focused().is_none());
+        if let Some(size) = self.max_size {
+            ui.set_max_size(size);
+        }
+        if self.two_pane {
+            egui::TopBottomPanel::bottom(ui.id().with("fontcfg_footer"))
+                .show_inside(ui, |ui| self.footer_ui(ui, &mut msg));
+            egui::SidePanel::left(ui.id().with("fontcfg_lists"))
+                .resizable(true)
+                .default_width(if touch { 400.0 } else { 300.0 })
+                .show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_salt("fontcfg_lists_scroll")
+                        .show(ui, |ui| {
+                            self.lists_ui(
+                                ui,
+                                font_defs,
+                                custom.as_deref_mut(),
+                                focus_first,
+                                &mut back,
+                            );
+                        });
+                });
+            egui::CentralPanel::default().show_inside(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("fontcfg_inspection_scroll")
+                    .show(ui, |ui| {
+                        self.inspection_ui(ui, font_defs, custom.as_deref_mut())
+                    });
+            });
+        } else {
+            if self.max_size.is_none() {
+                ui.set_max_width(if touch { 400.0 } else { 300.0 });
+            }
+            // Leave room for the footer, which stays in view
+            let footer_height = ui.spacing().interact_size.y * 2.0 + ui.spacing().item_spacing.y;
+            egui::ScrollArea::vertical()
+                .id_salt("fontcfg_scroll")
+                .max_height((ui.available_height() - footer_height).max(footer_height))
+                .show(ui, |ui| {
+                    self.lists_ui(ui, font_defs, custom.as_deref_mut(), focus_first, &mut back);
+                    self.inspection_ui(ui, font_defs, custom.as_deref_mut());
+                });
+            self.footer_ui(ui, &mut msg);
+        }
+        self.finish_loading(font_defs, custom.as_deref_mut(), &mut msg);
+        self.finish_uploads(font_defs, custom.as_deref_mut());
+        if self
+            .load_queue
+            .as_ref()
+            .is_some_and(FontLoadQueue::is_loading)
+        {
+            // The spinners may be scrolled out of view, so make sure to poll again
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+        match self.restore_requested.take() {
+            Some(confirm::Restore::Revert) => {
+                if let Some(state) = self.opened_with.clone() {
+                    state.restore(font_defs, custom.as_deref_mut());
+                    msg = FontDefsUiMsg::Reverted;
+                }
+            }
+            Some(confirm::Restore::Reset) => {
+                *font_defs = FontDefinitions::default();
+                if let Some(custom) = custom.as_deref_mut() {
+                    custom.clear();
+                }
+                msg = FontDefsUiMsg::ResetToDefaults;
+            }
+            None => {}
+        }
+        if back {
+            msg = FontDefsUiMsg::BackRequest;
+        }
+        let live = self.live_apply_due(ui.ctx(), font_defs);
+        if std::mem::take(&mut self.apply_requested) || live {
+            let result = if self.exclusions.values().all(Vec::is_empty) {
+                apply_ref(ui.ctx(), font_defs)
+            } else {
+                apply_ref(
+                    ui.ctx(),
+                    &apply_fallback_exclusions(font_defs, &self.exclusions),
+                )
+            };
+            match result {
+                Ok(Some(Pushed { set_fonts_time })) => {
+                    self.last_apply = Some(set_fonts_time);
+                    self.clean = Some(DefsFingerprint::new(font_defs));
+                    // Live applies happen all the time, so keep offering to restore fonts
+                    // and don't notify about each one
+                    if !live {
+                        self.trash.clear();
+                        self.notifier.push(Notification::Applied);
+                    }
+                    if matches!(msg, FontDefsUiMsg::None) {
+                        msg = FontDefsUiMsg::Applied { set_fonts_time };
+                    }
+                }
+                Ok(None) => self.clean = Some(DefsFingerprint::new(font_defs)),
+                Err(e) => {
+                    self.set_error(e.to_string());
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
+        self.record_edits(undo_before.font_defs(), font_defs);
+        // Loaded fonts have their events pushed as they finish
+        if !matches!(msg, FontDefsUiMsg::None | FontDefsUiMsg::FontLoaded { .. }) {
+            self.push_event(msg.clone());
+        }
+        let dragging = ui.input(|i| i.pointer.any_down());
+        self.history
+            .record(undo_before, font_defs, custom.as_deref(), dragging);
+        if matches!(msg, FontDefsUiMsg::SaveRequest) {
+            self.clean = Some(DefsFingerprint::new(font_defs));
+        }
+        self.dirty = !self
+            .clean
+            .as_ref()
+            .is_some_and(|clean| clean.matches(font_defs));
+        self.text_focused |= ui
+            .ctx()
+            .data_mut(|d| d.remove_temp::<bool>(text_focus_id()))
+            .unwrap_or(false);
+        self.notifier.flush(ui.input(|i| i.time));
+        msg
+    }
+    /// Like [`Self::show`], for a configuration kept as a [`FontConfig`]
+    pub fn show_config(&mut self, ui: &mut egui::Ui, config: &mut FontConfig) -> FontDefsUiMsg {
+        self.show(ui, &mut config.font_defs, Some(&mut config.custom))
+    }
+    /// Show a locked-down version of the ui, for end users of a shipped application
+    ///
+    /// Only offers switching between the [presets](Self::presets) and scaling the text size.
+    /// Everything else is hidden, so the user can't end up with a broken configuration.
+    /// Fonts of named families the application uses, which a preset doesn't have, are kept
+    /// like with [`PresetHotkeys`].
+    pub fn show_kiosk(
+        &mut self,
+        ui: &mut egui::Ui,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> FontDefsUiMsg {
+        let mut msg = FontDefsUiMsg::None;
+        if self.presets.is_empty() {
+            ui.weak("No font presets to choose from");
+        } else {
+            ui.label("Fonts");
+            let active = self.presets.active().map(str::to_owned);
+            let mut picked = None;
+            ui.horizontal_wrapped(|ui| {
+                for name in self.presets.names() {
+                    let selected = active.as_deref() == Some(name);
+                    if ui.selectable_label(selected, name).clicked() && !selected {
+                        picked = Some(name.to_owned());
+                    }
+                }
+            });
+            if let Some(name) = picked {
+                msg = self.switch_preset(ui.ctx(), name, font_defs, custom);
+            }
+        }
+        let ctx = ui.ctx().clone();
+        let scale = zoom::text_scale(&ctx);
+        let mut percent = scale * 100.0;
+        let re = ui.add(
+            egui::Slider::new(&mut percent, 50.0..=200.0)
+                .text("Text size")
+                .suffix("%")
+                .fixed_decimals(0),
+        );
+        if re.changed() {
+            zoom::set_text_scale(&ctx, percent / 100.0);
+        }
+        self.error_ui(ui);
+        self.notifier.flush(ui.input(|i| i.time));
+        msg
+    }
+    /// Where background work runs: on the spawner of the [`FontLoadQueue`], if there is one
+    #[cfg(any(feature = "compression", feature = "system-fonts"))]
+    fn spawner(&self) -> crate::Spawner {
+        self.load_queue
+            .as_ref()
+            .map_or_else(loading::default_spawner, FontLoadQueue::spawner_handle)
+    }
+    /// Record the edits between `before` and `after` in the journal and as events
+    fn record_edits(&mut self, before: &FontDefinitions, after: &FontDefinitions) {
+        let changes = diff_font_defs(before, after);
+        journal::record(&mut self.journal, &changes);
+        for change in &changes {
+            self.push_event(change.to_msg());
+        }
+    }
+    /// Switch to the preset called `name`, keeping the fonts it would drop from named families
+    /// the application uses
+    fn switch_preset(
+        &mut self,
+        ctx: &egui::Context,
+        name: String,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) -> FontDefsUiMsg {
+        let Some(preset) = self.presets.get(&name) else {
+            return FontDefsUiMsg::None;
+        };
+        let usage = font_usage(ctx);
+        let resolutions: Vec<_> = preset_conflicts(font_defs, &preset.font_defs, &usage)
+            .into_iter()
+            .map(|c| (c, ConflictResolution::Keep))
+            .collect();
+        let no_paths = CustomFontPaths::default();
+        let resolved = resolve_preset_conflicts(
+            preset,
+            font_defs,
+            custom.as_deref().unwrap_or(&no_paths),
+            &resolutions,
+        );
+        match self.presets.apply_resolved(ctx, &name, &resolved.font_defs) {
+            Ok(_) => {
+                let before = std::mem::replace(font_defs, resolved.font_defs);
+                if let Some(custom) = custom {
+                    custom.clone_from(&resolved.custom);
+                }
+                self.record_edits(&before, font_defs);
+                self.notifier
+                    .push(Notification::PresetSwitched(name.clone()));
+                self.push_event(FontDefsUiMsg::PresetSwitched { name: name.clone() });
+                FontDefsUiMsg::PresetSwitched { name }
+            }
+            Err(e) => {
+                self.set_error(e.to_string());
+                FontDefsUiMsg::None
+            }
+        }
+    }
+}
+
+/// A convenience window wrapper around [`FontCfgUi`], to show it in a window
+#[derive(Default)]
+pub struct FontCfgWindow {
+    ui: FontCfgUi,
+    /// Whether the window should be open
+    pub open: bool,
+    /// Whether the window was open during the last [`Self::show`]
+    was_open: bool,
+    preset_hotkeys: PresetHotkeys,
+    /// Whether the user is being asked to confirm closing with unsaved changes
+    confirm_close: bool,
+    /// Where the window was during the last [`Self::show`], for taking the files dropped on it
+    rect: Option<egui::Rect>,
+}
+
+impl FontCfgWindow {
+    /// Create a window around an already configured [`FontCfgUi`]
+    pub fn new(ui: FontCfgUi) -> Self {
+        Self {
+            ui,
+            open: false,
+            was_open: false,
+            preset_hotkeys: PresetHotkeys::default(),
+            confirm_close: false,
+            rect: None,
+        }
+    }
+    /// Whether the pointer is over the window, as it was during the last frame
+    fn pointer_over(&self, input: &egui::InputState) -> bool {
+        let pos = input.pointer.latest_pos();
+        self.rect
+            .is_some_and(|rect| pos.is_some_and(|pos| rect.contains(pos)))
+    }
+    /// Take the font files dropped onto the window, leaving other drops to the application
+    fn take_font_drops(&self, ctx: &egui::Context) -> Vec<egui::DroppedFile> {
+        ctx.input_mut(|i| {
+            if !self.pointer_over(i) {
+                return Vec::new();
+            }
+            let (fonts, others) = std::mem::take(&mut i.raw.dropped_files)
+                .into_iter()
+                .partition(|file| {
+                    let path = file.path.as_deref();
+                    is_font_file(path.unwrap_or(std::path::Path::new(&file.name)))
+                });
+            i.raw.dropped_files = others;
+            fonts
+        })
+    }
+    /// Highlight the window while font files are dragged over it
+    fn file_drop_ui(&self, ctx: &egui::Context) {
+        let Some(rect) = self.rect else {
+            return;
+        };
+        let hovering = ctx.input(|i| {
+            // Web backends don't tell the path or name before the drop
+            let fonts = i
+                .raw
+                .hovered_files
+                .iter()
+                .any(|file| file.path.as_deref().is_none_or(is_font_file));
+            fonts && self.pointer_over(i)
+        });
+        if !hovering {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("font_file_drop"),
+        ));
+        let visuals = ctx.style().visuals.clone();
+        painter.rect_filled(
+            rect,
+            visuals.window_rounding,
+            visuals.selection.bg_fill.gamma_multiply(0.6),
+        );
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Drop to add the font",
+            egui::TextStyle::Heading.resolve(&ctx.style()),
+            visuals.strong_text_color(),
+        );
+    }
+    /// Set shortcuts for cycling through the [presets](FontCfgUi::presets)
+    ///
+    /// They work while the window is closed too, but not while the user is typing into it.
+    pub fn preset_hotkeys(mut self, hotkeys: PresetHotkeys) -> Self {
+        self.preset_hotkeys = hotkeys;
+        self
+    }
+    /// Access the wrapped [`FontCfgUi`]
+    pub fn ui_mut(&mut self) -> &mut FontCfgUi {
+        &mut self.ui
+    }
+    /// Show the font defs ui window
+    ///
+    /// Font files dropped onto the window are added, named after the file. Other dropped files,
+    /// and files dropped elsewhere, are left to the application. While the window is open,
+    /// Ctrl+Z undoes and Ctrl+Shift+Z or Ctrl+Y redoes, unless a text field has focus.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        font_defs: &mut FontDefinitions,
+        mut custom: Option<&mut CustomFontPaths>,
+    ) -> FontDefsUiMsg {
+        let mut msg = FontDefsUiMsg::None;
+        let forward = if self.ui.has_text_focus() {
+            None
+        } else {
+            self.preset_hotkeys.pressed(ctx)
+        };
+        let target = forward.and_then(|forward| self.ui.presets.neighbor(forward));
+        if let Some(name) = target.map(str::to_owned) {
+            // There's no time to ask while switching with a hotkey, so keep what would be
+            // dropped, which is what the app can keep rendering with
+            msg = self
+                .ui
+                .switch_preset(ctx, name, font_defs, custom.as_deref_mut());
+        }
+        if self.open && !self.was_open {
+            self.ui.set_revert_point();
+        }
+        if self.open && !self.ui.has_text_focus() {
+            if let Some(redo) = undo::pressed_shortcut(ctx) {
+                self.ui.history.request(redo);
+            }
+        }
+        let title = if self.ui.is_dirty() {
+            "Font definitions (modified)"
+        } else {
+            "Font definitions"
+        };
+        let mut open = self.open;
+        let mut close_confirmed = false;
+        let window = egui::Window::new(title)
+            // Keep the window in place when the title changes
+            .id(egui::Id::new("Font definitions"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.confirm_close {
+                    let action = confirm::PendingAction::CloseWindow;
+                    if let Some(confirmed) = confirm::confirm_ui(ui, self.ui.high_contrast, &action)
+                    {
+                        close_confirmed = confirmed;
+                        self.confirm_close = false;
+                    }
+                }
+                let dropped = self.take_font_drops(ui.ctx());
+                let ui_msg = self.ui.show_with_drops(ui, font_defs, custom, dropped);
+                if !matches!(ui_msg, FontDefsUiMsg::None) {
+                    msg = ui_msg;
+                }
+            });
+        self.rect = window.map(|window| window.response.rect);
+        self.file_drop_ui(ctx);
+        let close_requested = !open || matches!(msg, FontDefsUiMsg::BackRequest);
+        if close_confirmed || (close_requested && !self.ui.is_dirty()) {
+            self.open = false;
+        } else if close_requested && self.open {
+            self.confirm_close = true;
+            msg = FontDefsUiMsg::CloseRequestedWithUnsavedChanges;
+            self.ui.push_event(msg.clone());
+        }
+        if !self.open {
+            self.confirm_close = false;
+        }
+        self.was_open = self.open;
+        // The window may be closed, in which case the wrapped ui didn't flush
+        self.ui.notifier.flush(ctx.input(|i| i.time));
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FontCfgUi, FontCfgWindow},
+        crate::{CustomFontPaths, FontDefsUiMsg, FontLoadError, FontLoadQueue},
+        egui::FontDefinitions,
+        std::collections::BTreeMap,
+    };
+
+    #[test]
+    fn send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<FontCfgUi>();
+        assert_send::<FontCfgWindow>();
+    }
+
+    #[test]
+    fn rename_keeps_font_state() {
+        let mut ui = FontCfgUi::default();
+        let mut defs = FontDefinitions::default();
+        ui.fallback_exclusions_mut()
+            .insert("Hack".into(), vec![0x41..=0x5a]);
+        ui.details.toggle("Hack");
+        let renames = BTreeMap::from([
+            ("Hack".into(), "Code".into()),
+            ("Ubuntu-Light".into(), "Text".into()),
+        ]);
+        ui.rename_fonts(&mut defs, None, &renames).unwrap();
+        assert_eq!(ui.fallback_exclusions()["Code"], [0x41..=0x5a]);
+        assert!(!ui.fallback_exclusions().contains_key("Hack"));
+        assert!(ui.details.is_expanded("Code"));
+        assert!(!ui.details.is_expanded("Hack"));
+        assert!(!ui.details.is_expanded("Text"));
+    }
+
+    #[test]
+    fn live_apply_waits_while_apply_is_disabled() {
+        let ctx = egui::Context::default();
+        let defs = FontDefinitions::default();
+        let mut ui = FontCfgUi {
+            live_apply: true,
+            live_apply_changed_at: Some(-1.0),
+            ..Default::default()
+        };
+        ui.set_apply_enabled(false, "Busy");
+        assert!(!ui.live_apply_due(&ctx, &defs));
+        ui.set_apply_enabled(true, "");
+        assert!(ui.live_apply_due(&ctx, &defs));
+    }
+
+    #[test]
+    fn duplicates_are_held_back_on_every_path() {
+        let hack = FontDefinitions::default().font_data["Hack"].font.to_vec();
+        let reader_data = hack.clone();
+        let queue =
+            FontLoadQueue::with_reader(move |_| Ok(reader_data.clone())).spawner(|task| task());
+        let mut ui = FontCfgUi::default().async_loading(queue);
+        let mut defs = FontDefinitions::default();
+        let mut custom = CustomFontPaths::default();
+        ui.name_buf = "Queued".into();
+        ui.path_buf = "queued.ttf".into();
+        ui.add_font(&mut defs, Some(&mut custom)).unwrap();
+        ui.finish_loading(&mut defs, Some(&mut custom), &mut FontDefsUiMsg::None);
+        let file = egui::DroppedFile {
+            name: "Dropped.ttf".into(),
+            bytes: Some(hack.into()),
+            ..Default::default()
+        };
+        ui.add_dropped_files(vec![file], &mut defs, Some(&mut custom));
+        let held: Vec<_> = ui
+            .duplicates
+            .iter()
+            .map(|d| (&*d.font.name, &*d.existing))
+            .collect();
+        assert_eq!(held, [("Queued", "Hack"), ("Dropped", "Hack")]);
+        assert!(!defs.font_data.contains_key("Queued"));
+        assert!(!defs.font_data.contains_key("Dropped"));
+        assert!(custom.is_empty());
+    }
+
+    #[test]
+    fn window_takes_only_font_drops_over_it() {
+        let ctx = egui::Context::default();
+        let mut window = FontCfgWindow {
+            open: true,
+            ..Default::default()
+        };
+        let mut defs = FontDefinitions::default();
+        let data = defs.font_data.remove("Hack").unwrap().font.to_vec();
+        let drop = |name: &str| egui::DroppedFile {
+            name: name.into(),
+            bytes: Some(data.clone().into()),
+            ..Default::default()
+        };
+        let mut frame = |window: &mut FontCfgWindow, input| {
+            let _ = ctx.run(input, |ctx| {
+                window.show(ctx, &mut defs, None);
+            });
+        };
+        frame(&mut window, egui::RawInput::default());
+        let rect = window.rect.unwrap();
+        frame(
+            &mut window,
+            egui::RawInput {
+                dropped_files: vec![drop("Outside.ttf")],
+                events: vec![egui::Event::PointerMoved(rect.max + egui::vec2(10.0, 10.0))],
+                ..Default::default()
+            },
+        );
+        frame(
+            &mut window,
+            egui::RawInput {
+                dropped_files: vec![drop("Dropped.ttf"), drop("notes.txt")],
+                events: vec![egui::Event::PointerMoved(rect.center())],
+                ..Default::default()
+            },
+        );
+        assert!(defs.font_data.contains_key("Dropped"));
+        assert!(!defs.font_data.contains_key("Outside"));
+        assert_eq!(ctx.input(|i| i.raw.dropped_files.len()), 1);
+        assert!(window.ui.can_undo());
+    }
+
+    #[test]
+    fn every_loaded_font_is_an_event() {
+        let mut defs = FontDefinitions::default();
+        let fonts = [
+            defs.font_data.remove("Hack"),
+            defs.font_data.remove("Ubuntu-Light"),
+        ];
+        let files: BTreeMap<_, _> = ["hack.ttf", "ubuntu.ttf"]
+            .into_iter()
+            .zip(fonts.map(|font| font.unwrap().font.to_vec()))
+            .collect();
+        let queue =
+            FontLoadQueue::with_reader(move |path| Ok(files[path].clone())).spawner(|task| task());
+        let mut ui = FontCfgUi::default()
+            .async_loading(queue)
+            .collect_events(true);
+        for (name, path) in [("A", "hack.ttf"), ("B", "ubuntu.ttf")] {
+            ui.name_buf = name.into();
+            ui.path_buf = path.into();
+            ui.add_font(&mut defs, None).unwrap();
+        }
+        let mut msg = FontDefsUiMsg::None;
+        ui.finish_loading(&mut defs, None, &mut msg);
+        assert_eq!(msg, FontDefsUiMsg::FontLoaded { name: "A".into() });
+        assert_eq!(
+            ui.take_events(),
+            [
+                FontDefsUiMsg::FontLoaded { name: "A".into() },
+                FontDefsUiMsg::FontLoaded { name: "B".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn default_queue_reads_from_the_font_source() {
+        let hack = FontDefinitions::default().font_data["Hack"].font.to_vec();
+        let source = move |path: &str| match path {
+            "asset:hack" => Ok(hack.clone()),
+            _ => Err(FontLoadError::new(path, "Not among the assets")),
+        };
+        let queue = || FontLoadQueue::default().spawner(|task| task());
+        // The queue picks up the source whichever is set first
+        for mut ui in [
+            FontCfgUi::default()
+                .font_source(source.clone())
+                .async_loading(queue()),
+            FontCfgUi::default()
+                .async_loading(queue())
+                .font_source(source.clone()),
+        ] {
+            // Without Hack, so adding it isn't a duplicate
+            let mut defs = FontDefinitions::default();
+            let hack = defs.font_data.remove("Hack").unwrap();
+            ui.name_buf = "Asset".into();
+            ui.path_buf = "asset:hack".into();
+            ui.add_font(&mut defs, None).unwrap();
+            ui.finish_loading(&mut defs, None, &mut FontDefsUiMsg::None);
+            assert_eq!(defs.font_data["Asset"].font, hack.font);
+        }
+    }
+
+    #[test]
+    fn events_are_opt_in_and_coalesced() {
+        let changed = |name: &str| FontDefsUiMsg::FontChanged { name: name.into() };
+        let mut ui = FontCfgUi::default();
+        ui.push_event(changed("A"));
+        assert!(ui.take_events().is_empty());
+        let mut ui = FontCfgUi::default().collect_events(true);
+        for name in ["A", "A", "B", "A", "A"] {
+            ui.push_event(changed(name));
+        }
+        ui.push_event(FontDefsUiMsg::SaveRequest);
+        ui.push_event(FontDefsUiMsg::SaveRequest);
+        assert_eq!(
+            ui.take_events(),
+            [
+                changed("A"),
+                changed("B"),
+                changed("A"),
+                FontDefsUiMsg::SaveRequest,
+                FontDefsUiMsg::SaveRequest,
+            ]
+        );
+    }
+
+    #[test]
+    fn uploads_have_no_custom_path() {
+        let mut ui = FontCfgUi::default();
+        let mut defs = FontDefinitions::default();
+        let mut custom = CustomFontPaths::default();
+        // Taken out, so the upload isn't held back as a duplicate
+        let data = defs.font_data.remove("Hack").unwrap().font.to_vec();
+        let file = egui::DroppedFile {
+            name: "Upload.ttf".into(),
+            bytes: Some(data.clone().into()),
+            ..Default::default()
+        };
+        ui.add_dropped_files(vec![file], &mut defs, Some(&mut custom));
+        assert!(custom.is_empty());
+        assert_eq!(ui.uploaded_font_bytes(&defs)["Upload"], data);
+        let renames = BTreeMap::from([("Upload".into(), "Renamed".into())]);
+        ui.rename_fonts(&mut defs, Some(&mut custom), &renames)
+            .unwrap();
+        assert_eq!(
+            ui.uploaded_font_bytes(&defs).keys().collect::<Vec<_>>(),
+            ["Renamed"]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Undoing and redoing edits made through the ui
+
+use {
+    crate::{CustomFontPaths, DefsFingerprint},
+    egui::{FontDefinitions, Key, KeyboardShortcut, Modifiers},
+};
+
+/// How many edits can be undone
+const MAX_DEPTH: usize = 100;
+
+const UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO: [KeyboardShortcut; 2] = [
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z),
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::Y),
+];
+
+/// Consume the pressed undo or redo shortcut. Returns `Some(true)` for redo, `Some(false)` for
+/// undo.
+pub(crate) fn pressed_shortcut(ctx: &egui::Context) -> Option<bool> {
+    ctx.input_mut(|i| {
+        // Ctrl+Z also matches Ctrl+Shift+Z, so redo goes first
+        if REDO.iter().any(|s| i.consume_shortcut(s)) {
+            Some(true)
+        } else if i.consume_shortcut(&UNDO) {
+            Some(false)
+        } else {
+            None
+        }
+    })
+}
+
+/// The edited state, which is cheap to clone as the font data is reference counted
+#[derive(Clone)]
+pub(crate) struct State {
+    font_defs: FontDefinitions,
+    custom: Option<CustomFontPaths>,
+}
+
+impl State {
+    pub(crate) fn new(font_defs: &FontDefinitions, custom: Option<&CustomFontPaths>) -> Self {
+        Self {
+            font_defs: font_defs.clone(),
+            custom: custom.cloned(),
+        }
+    }
+    /// The saved font definitions
+    pub(crate) fn font_defs(&self) -> &FontDefinitions {
+        &self.font_defs
+    }
+    /// Put the saved state back. Custom font paths are only restored if they were saved.
+    pub(crate) fn restore(
+        self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) {
+        *font_defs = self.font_defs;
+        if let (Some(custom), Some(saved)) = (custom, self.custom) {
+            *custom = saved;
+        }
+    }
+}
+
+/// Undo and redo stacks of the states before each edit
+#[derive(Default)]
+pub(crate) struct History {
+    undo: Vec<State>,
+    redo: Vec<State>,
+    /// Requested with [`Self::request`], performed during the next [`Self::perform_request`]
+    requested: Option<bool>,
+    /// Edits are merged into the previous one while a pointer drag is going on,
+    /// so dragging a slider can be undone in one step
+    coalescing: bool,
+}
+
+impl History {
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// Ask for the next [`Self::perform_request`] to undo (`false`) or redo (`true`)
+    pub(crate) fn request(&mut self, redo: bool) {
+        self.requested = Some(redo);
+    }
+    /// Undo or redo if it was requested
+    pub(crate) fn perform_request(
+        &mut self,
+        font_defs: &mut FontDefinitions,
+        custom: Option<&mut CustomFontPaths>,
+    ) {
+        let Some(redo) = self.requested.take() else {
+            return;
+        };
+        let (from, to) = if redo {
+            (&mut self.redo, &mut self.undo)
+        } else {
+            (&mut self.undo, &mut self.redo)
+        };
+        let Some(state) = from.pop() else {
+            return;
+        };
+        to.push(State::new(font_defs, custom.as_deref()));
+        state.restore(font_defs, custom);
+        self.coalescing = false;
+    }
+    /// Record an edit if the state changed from `before`
+    pub(crate) fn record(
+        &mut self,
+        before: State,
+        font_defs: &FontDefinitions,
+        custom: Option<&CustomFontPaths>,
+        dragging: bool,
+    ) {
+        let changed = !DefsFingerprint::new(&before.font_defs).matches(font_defs)
+            || before.custom.as_ref() != custom;
+        if !changed {
+            self.coalescing &= dragging;
+            return;
+        }
+        if !self.coalescing {
+            self.undo.push(before);
+            if self.undo.len() > MAX_DEPTH {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.coalescing = dragging;
+    }
+}
@@ -0,0 +1,163 @@
+//! Adding fonts by their contents rather than their path, for the web
+
+use {
+    crate::{textfmt, ParseError},
+    base64::engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig},
+    egui::FontData,
+    std::{
+        collections::{BTreeMap, HashMap},
+        sync::Arc,
+    },
+};
+
+/// A font file the user uploaded
+#[derive(Debug, Clone)]
+pub struct UploadedFont {
+    /// Name of the file, e.g. `Hack-Regular.ttf`
+    pub file_name: String,
+    /// Contents of the file
+    pub data: Vec<u8>,
+}
+
+/// Lets the user upload font files, see [`FontCfgUi::font_uploader`](crate::FontCfgUi::font_uploader)
+///
+/// In the browser, file contents can only be read asynchronously, so uploading is split in
+/// two: [`Self::start_upload`] opens the file dialog, and [`Self::poll_uploads`] returns the
+/// files once they're read. With `rfd`, this could look like:
+///
+/// ```ignore
+/// struct RfdUploader(std::sync::mpsc::Sender<UploadedFont>, std::sync::mpsc::Receiver<UploadedFont>, egui::Context);
+///
+/// impl FontUploader for RfdUploader {
+///     fn start_upload(&mut self) {
+///         let (sender, ctx) = (self.0.clone(), self.2.clone());
+///         wasm_bindgen_futures::spawn_local(async move {
+///             if let Some(file) = rfd::AsyncFileDialog::new().pick_file().await {
+///                 let data = file.read().await;
+///                 let _ = sender.send(UploadedFont { file_name: file.file_name(), data });
+///                 ctx.request_repaint();
+///             }
+///         });
+///     }
+///     fn poll_uploads(&mut self) -> Vec<UploadedFont> {
+///         self.1.try_iter().collect()
+///     }
+/// }
+/// ```
+pub trait FontUploader {
+    /// Let the user pick font files to upload
+    fn start_upload(&mut self);
+    /// The files that finished uploading since the last call
+    ///
+    /// Called every frame. Request a repaint when an upload finishes, or it will only show up
+    /// once something else makes egui repaint.
+    fn poll_uploads(&mut self) -> Vec<UploadedFont>;
+}
+
+/// The contents of custom fonts, keyed by font identifier like [`CustomFontPaths`](crate::CustomFontPaths)
+///
+/// For applications that can't load fonts back from their paths, e.g. on the web, where
+/// uploaded fonts have no path. See [`FontCfgUi::uploaded_font_bytes`](crate::FontCfgUi::uploaded_font_bytes).
+pub type CustomFontBytes = HashMap<String, Vec<u8>>;
+
+/// Collect the data of the custom fonts in `custom` from `font_data`, for persisting it
+///
+/// Fonts that aren't in `font_data` are skipped.
+pub fn custom_font_bytes(
+    custom: &crate::CustomFontPaths,
+    font_data: &BTreeMap<String, Arc<FontData>>,
+) -> CustomFontBytes {
+    custom
+        .keys()
+        .filter_map(|k| Some((k.clone(), font_data.get(k)?.font.to_vec())))
+        .collect()
+}
+
+/// Like [`load_custom_fonts`](crate::load_custom_fonts), but takes the data from `bytes`
+pub fn load_custom_font_bytes(
+    bytes: &CustomFontBytes,
+    font_data: &mut BTreeMap<String, Arc<FontData>>,
+) {
+    for (k, v) in bytes {
+        font_data.insert(k.to_owned(), Arc::new(FontData::from_owned(v.clone())));
+    }
+}
+
+/// Encode `bytes` as text, with the data in base64, e.g. for storing it in local storage
+///
+/// Restore it with [`decode_custom_font_bytes`].
+pub fn encode_custom_font_bytes(bytes: &CustomFontBytes) -> String {
+    let mut out = String::new();
+    // Sorted, so the same fonts always encode the same
+    let sorted: BTreeMap<_, _> = bytes.iter().collect();
+    for (name, data) in sorted {
+        textfmt::push_line(&mut out, "font", [name.as_str(), &base64_encode(data)]);
+    }
+    out
+}
+
+/// Like [`encode_custom_font_bytes`], but compresses the data first, see [`compress_font_data`](crate::compress_font_data)
+///
+/// [`decode_custom_font_bytes`] reads both.
+#[cfg(feature = "compression")]
+pub fn encode_custom_font_bytes_compressed(bytes: &CustomFontBytes) -> String {
+    let mut out = String::new();
+    let sorted: BTreeMap<_, _> = bytes.iter().collect();
+    for (name, data) in sorted {
+        let data = base64_encode(&crate::compress_font_data(data));
+        textfmt::push_line(&mut out, "compressed-font", [name.as_str(), &data]);
+    }
+    out
+}
+
+/// Decode the text made by [`encode_custom_font_bytes`]
+pub fn decode_custom_font_bytes(text: &str) -> Result<CustomFontBytes, ParseError> {
+    let mut bytes = CustomFontBytes::new();
+    for line in textfmt::lines(text) {
+        let (line, keyword, fields) = line?;
+        let error = |message: String| ParseError { line, message };
+        match (keyword.as_str(), fields.as_slice()) {
+            ("font", [name, data]) => {
+                let data = base64_decode(data).map_err(error)?;
+                bytes.insert(name.clone(), data);
+            }
+            ("compressed-font", [name, data]) => {
+                let data = base64_decode(data).map_err(error)?;
+                bytes.insert(name.clone(), decompress(name, &data).map_err(error)?);
+            }
+            ("font" | "compressed-font", _) => {
+                return Err(error("Expected a font name and its data".into()))
+            }
+            _ => return Err(error(format!("Unknown keyword {keyword}"))),
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(feature = "compression")]
+fn decompress(_name: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    crate::decompress_font_data(data)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(name: &str, _data: &[u8]) -> Result<Vec<u8>, String> {
+    Err(format!(
+        "The data of {name} is compressed, which needs the compression feature"
+    ))
+}
+
+/// Standard base64, also accepting data without padding
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+fn base64_encode(data: &[u8]) -> String {
+    BASE64.encode(data)
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(text)
+        .map_err(|e| format!("Invalid base64 data: {e}"))
+}
@@ -0,0 +1,135 @@
+//! Usage counts of families and text styles, reported by the host application
+
+use {
+    egui::{FontDefinitions, FontFamily, TextStyle},
+    std::collections::BTreeMap,
+};
+
+fn usage_id() -> egui::Id {
+    egui::Id::new("egui_fontcfg_usage")
+}
+
+/// How often families and text styles were used, as reported with [`report_family_use`]
+/// and [`report_text_style_use`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontUsage {
+    /// Uses of each family, including those through text styles
+    pub families: BTreeMap<FontFamily, u64>,
+    /// Uses of each text style
+    pub text_styles: BTreeMap<TextStyle, u64>,
+}
+
+impl FontUsage {
+    /// Total uses of the families `name` is in
+    pub fn font_uses(&self, font_defs: &FontDefinitions, name: &str) -> u64 {
+        font_defs
+            .families
+            .iter()
+            .filter(|(_, fonts)| fonts.iter().any(|f| f == name))
+            .filter_map(|(family, _)| self.families.get(family))
+            .sum()
+    }
+}
+
+/// Report that the application rendered text with `family` `count` times
+///
+/// Call this wherever text is shown, e.g. once per label per frame. The counts are shown
+/// in the dialog, so users can tell which families are unused and which fonts matter most.
+pub fn report_family_use(ctx: &egui::Context, family: &FontFamily, count: u64) {
+    ctx.data_mut(|d| {
+        let usage = d.get_temp_mut_or_default::<FontUsage>(usage_id());
+        *usage.families.entry(family.clone()).or_default() += count;
+    });
+}
+
+/// Report that the application rendered text with `style` `count` times
+///
+/// This also counts as a use of the family the style currently maps to.
+pub fn report_text_style_use(ctx: &egui::Context, style: &TextStyle, count: u64) {
+    let family = ctx
+        .style()
+        .text_styles
+        .get(style)
+        .map(|font_id| font_id.family.clone());
+    ctx.data_mut(|d| {
+        let usage = d.get_temp_mut_or_default::<FontUsage>(usage_id());
+        *usage.text_styles.entry(style.clone()).or_default() += count;
+        if let Some(family) = family {
+            *usage.families.entry(family).or_default() += count;
+        }
+    });
+}
+
+/// The usage reported so far
+pub fn font_usage(ctx: &egui::Context) -> FontUsage {
+    ctx.data(|d| d.get_temp::<FontUsage>(usage_id()))
+        .unwrap_or_default()
+}
+
+/// Forget the usage reported so far
+pub fn reset_font_usage(ctx: &egui::Context) {
+    ctx.data_mut(|d| d.remove::<FontUsage>(usage_id()));
+}
+
+/// Show the reported usage of families, text styles and fonts
+pub(crate) fn usage_ui(ui: &mut egui::Ui, font_defs: &FontDefinitions) {
+    let usage = font_usage(ui.ctx());
+    if usage == FontUsage::default() {
+        ui.weak("The application hasn't reported any usage");
+        return;
+    }
+    egui::Grid::new("family_usage")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Family");
+            ui.strong("Uses");
+            ui.end_row();
+            for family in font_defs.families.keys() {
+                ui.label(family.to_string());
+                match usage.families.get(family).copied().unwrap_or(0) {
+                    0 if matches!(family, FontFamily::Name(_)) => ui.weak("Unused, safe to remove"),
+                    0 => ui.weak("Unused, but required by egui"),
+                    n => ui.label(n.to_string()),
+                };
+                ui.end_row();
+            }
+        });
+    if !usage.text_styles.is_empty() {
+        egui::Grid::new("text_style_usage")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Text style");
+                ui.strong("Uses");
+                ui.end_row();
+                for (style, n) in &usage.text_styles {
+                    ui.label(style.to_string());
+                    ui.label(n.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+    let mut fonts: Vec<_> = font_defs
+        .font_data
+        .keys()
+        .map(|name| (usage.font_uses(font_defs, name), name))
+        .collect();
+    fonts.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    egui::Grid::new("font_usage")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Font");
+            ui.strong("Uses of its families");
+            ui.end_row();
+            for (n, name) in fonts {
+                ui.label(name);
+                ui.label(n.to_string());
+                ui.end_row();
+            }
+        });
+    if ui.button("Reset counts").clicked() {
+        reset_font_usage(ui.ctx());
+    }
+}
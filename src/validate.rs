@@ -0,0 +1,303 @@
+//! Checks of font definitions, built-in and host provided
+
+use {
+    crate::{
+        font_statuses,
+        ui::{status_label, Severity},
+        CustomFontPaths, DefsFingerprint, FontStatus,
+    },
+    egui::{FontDefinitions, FontFamily},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::Arc,
+    },
+};
+
+/// How serious a [`ValidationIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The configuration works, but is likely not what the user wants
+    Warning,
+    /// The configuration can't be applied, or is unacceptable to the host
+    Error,
+}
+
+/// A problem found in font definitions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// How serious the problem is
+    pub severity: IssueSeverity,
+    /// Description of the problem, shown to the user
+    pub message: String,
+}
+
+impl ValidationIssue {
+    /// A warning with the given message
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            message: message.into(),
+        }
+    }
+    /// An error with the given message
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            message: message.into(),
+        }
+    }
+}
+
+/// A structural problem in font definitions, found by [`validate`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FontCfgIssue {
+    /// A family refers to a font that isn't present in the font data
+    MissingFont {
+        /// The family containing the entry
+        family: FontFamily,
+        /// The name of the missing font
+        name: String,
+    },
+    /// A font isn't used by any family, so it is never rendered
+    UnassignedFont(String),
+    /// A font is listed more than once in a family. Only the first entry has an effect.
+    DuplicateEntry {
+        /// The family containing the entries
+        family: FontFamily,
+        /// The name of the font
+        name: String,
+    },
+    /// A family has no fonts, so text using it can't be rendered
+    EmptyFamily(FontFamily),
+}
+
+impl FontCfgIssue {
+    /// How serious the problem is. Missing fonts make egui panic, the rest are warnings.
+    pub fn severity(&self) -> IssueSeverity {
+        match self {
+            Self::MissingFont { .. } => IssueSeverity::Error,
+            Self::UnassignedFont(_) | Self::DuplicateEntry { .. } | Self::EmptyFamily(_) => {
+                IssueSeverity::Warning
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FontCfgIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFont { family, name } => {
+                write!(f, "Family {family} refers to missing font {name:?}")
+            }
+            Self::UnassignedFont(name) => write!(f, "Font {name:?} isn't in any family"),
+            Self::DuplicateEntry { family, name } => {
+                write!(f, "Family {family} lists font {name:?} more than once")
+            }
+            Self::EmptyFamily(family) => write!(f, "Family {family} has no fonts"),
+        }
+    }
+}
+
+impl From<FontCfgIssue> for ValidationIssue {
+    fn from(issue: FontCfgIssue) -> Self {
+        Self {
+            severity: issue.severity(),
+            message: issue.to_string(),
+        }
+    }
+}
+
+/// Find structural problems in `font_defs`, e.g. after loading a configuration from disk
+///
+/// Each problem is reported once, in the order of the families and fonts.
+pub fn validate(font_defs: &FontDefinitions) -> Vec<FontCfgIssue> {
+    let mut issues = Vec::new();
+    for (family, fonts) in &font_defs.families {
+        if fonts.is_empty() {
+            issues.push(FontCfgIssue::EmptyFamily(family.clone()));
+        }
+        let mut seen = BTreeSet::new();
+        let mut duplicates = BTreeSet::new();
+        for name in fonts {
+            if !seen.insert(name) {
+                if duplicates.insert(name) {
+                    issues.push(FontCfgIssue::DuplicateEntry {
+                        family: family.clone(),
+                        name: name.clone(),
+                    });
+                }
+            } else if !font_defs.font_data.contains_key(name) {
+                issues.push(FontCfgIssue::MissingFont {
+                    family: family.clone(),
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+    let assigned: BTreeSet<&String> = font_defs.families.values().flatten().collect();
+    for name in font_defs.font_data.keys() {
+        if !assigned.contains(name) {
+            issues.push(FontCfgIssue::UnassignedFont(name.clone()));
+        }
+    }
+    issues
+}
+
+/// A host provided check, see [`FontCfgUi::validator`](crate::FontCfgUi::validator)
+pub type Validator = Box<dyn Fn(&FontDefinitions) -> Vec<ValidationIssue> + Send>;
+
+/// The checks the crate always runs, given the result of [`validate`]
+fn builtin_issues(
+    font_defs: &FontDefinitions,
+    cfg_issues: &[FontCfgIssue],
+) -> Vec<ValidationIssue> {
+    let mut issues: Vec<ValidationIssue> = cfg_issues.iter().cloned().map(Into::into).collect();
+    for (name, font) in &font_defs.font_data {
+        if let Err(e) = ttf_parser::Face::parse(&font.font, font.index) {
+            issues.push(ValidationIssue::error(format!(
+                "Font {name:?} can't be parsed: {e}"
+            )));
+        }
+    }
+    issues
+}
+
+/// Run the built-in checks followed by `validators`
+pub(crate) fn run(font_defs: &FontDefinitions, validators: &[Validator]) -> Vec<ValidationIssue> {
+    run_with(font_defs, &validate(font_defs), validators)
+}
+
+fn run_with(
+    font_defs: &FontDefinitions,
+    cfg_issues: &[FontCfgIssue],
+    validators: &[Validator],
+) -> Vec<ValidationIssue> {
+    let mut issues = builtin_issues(font_defs, cfg_issues);
+    for validator in validators {
+        issues.extend(validator(font_defs));
+    }
+    issues
+}
+
+/// What the checks found in the font definitions, see [`Checks`]
+#[derive(Default)]
+pub(crate) struct CheckResults {
+    /// Structural problems, see [`validate`]
+    pub(crate) cfg_issues: BTreeSet<FontCfgIssue>,
+    /// The problems found by [`run`]
+    pub(crate) issues: Vec<ValidationIssue>,
+    pub(crate) statuses: BTreeMap<String, FontStatus>,
+}
+
+/// The checks of the font definitions being edited, rerun only when they change
+///
+/// Parsing every font and looking at every custom font file each frame would make large
+/// configurations sluggish.
+#[derive(Default)]
+pub(crate) struct Checks {
+    /// The definitions and paths the results are for
+    checked: Option<(DefsFingerprint, Option<CustomFontPaths>)>,
+    results: Arc<CheckResults>,
+}
+
+impl Checks {
+    /// Rerun the checks if `font_defs` or `custom` changed since they last ran
+    pub(crate) fn update(
+        &mut self,
+        font_defs: &FontDefinitions,
+        custom: Option<&CustomFontPaths>,
+        validators: &[Validator],
+    ) {
+        let fresh = self.checked.as_ref().is_some_and(|(fingerprint, paths)| {
+            fingerprint.matches(font_defs) && paths.as_ref() == custom
+        });
+        if !fresh {
+            let cfg_issues = validate(font_defs);
+            self.results = Arc::new(CheckResults {
+                issues: run_with(font_defs, &cfg_issues, validators),
+                cfg_issues: cfg_issues.into_iter().collect(),
+                statuses: font_statuses(font_defs, custom),
+            });
+            self.checked = Some((DefsFingerprint::new(font_defs), custom.cloned()));
+        }
+    }
+    /// The results of the last update
+    pub(crate) fn results(&self) -> Arc<CheckResults> {
+        self.results.clone()
+    }
+}
+
+/// List `issues`, or say that there are none
+pub(crate) fn issues_ui(ui: &mut egui::Ui, high_contrast: bool, issues: &[ValidationIssue]) {
+    if issues.is_empty() {
+        ui.label("No problems found");
+        return;
+    }
+    for issue in issues {
+        let severity = match issue.severity {
+            IssueSeverity::Warning => Severity::Warning,
+            IssueSeverity::Error => Severity::Error,
+        };
+        status_label(ui, high_contrast, severity, &issue.message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{validate, Checks, FontCfgIssue, Validator},
+        crate::{CustomFontPaths, FontStatus},
+        egui::{FontDefinitions, FontFamily},
+        std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    #[test]
+    fn finds_structural_problems() {
+        let mut defs = FontDefinitions::default();
+        let proportional = defs.families.get_mut(&FontFamily::Proportional).unwrap();
+        proportional.push("Missing".into());
+        proportional.push("Hack".into());
+        proportional.push("Hack".into());
+        defs.families
+            .insert(FontFamily::Name("Empty".into()), Vec::new());
+        defs.font_data
+            .insert("Unused".into(), defs.font_data["Hack"].clone());
+        let issues = validate(&defs);
+        assert!(issues.contains(&FontCfgIssue::MissingFont {
+            family: FontFamily::Proportional,
+            name: "Missing".into(),
+        }));
+        assert!(issues.contains(&FontCfgIssue::DuplicateEntry {
+            family: FontFamily::Proportional,
+            name: "Hack".into(),
+        }));
+        assert!(issues.contains(&FontCfgIssue::EmptyFamily(FontFamily::Name("Empty".into()))));
+        assert!(issues.contains(&FontCfgIssue::UnassignedFont("Unused".into())));
+        assert_eq!(issues.len(), 4);
+    }
+
+    #[test]
+    fn checks_rerun_only_on_changes() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let validators: Vec<Validator> = vec![Box::new(move |_: &FontDefinitions| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Vec::new()
+        })];
+        let mut checks = Checks::default();
+        let mut defs = FontDefinitions::default();
+        let mut custom = CustomFontPaths::default();
+        checks.update(&defs, Some(&custom), &validators);
+        checks.update(&defs, Some(&custom), &validators);
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        defs.families.remove(&FontFamily::Monospace);
+        checks.update(&defs, Some(&custom), &validators);
+        custom.insert("Hack".into(), "hack.ttf".into());
+        checks.update(&defs, Some(&custom), &validators);
+        assert_eq!(runs.load(Ordering::Relaxed), 3);
+        assert_eq!(checks.results().statuses["Hack"], FontStatus::MissingFile);
+    }
+}
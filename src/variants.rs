@@ -0,0 +1,391 @@
+//! Registering the style variants of a typeface in one go
+
+use {
+    crate::{
+        sanitize_font_name, ui::a11y_text_label, FamilyTemplate, FontConfigError, FontConfigMut,
+        FontLoadError, FontSource,
+    },
+    egui::{FontData, FontDefinitions, FontFamily},
+};
+
+/// A style variant of a typeface
+///
+/// egui has no notion of font weight or style, so each variant is registered as its own font,
+/// with an optional named family of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    /// Regular weight, upright
+    Regular,
+    /// Bold weight, upright
+    Bold,
+    /// Regular weight, italic
+    Italic,
+    /// Bold weight, italic
+    BoldItalic,
+}
+
+impl FontStyle {
+    /// All the style variants, in the order the ui lists them
+    pub const ALL: [Self; 4] = [Self::Regular, Self::Bold, Self::Italic, Self::BoldItalic];
+    /// Human readable name of the style
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Regular => "Regular",
+            Self::Bold => "Bold",
+            Self::Italic => "Italic",
+            Self::BoldItalic => "Bold Italic",
+        }
+    }
+    /// Pick the style for a numeric (CSS-like) weight, where 600 and above counts as bold
+    pub fn from_weight(weight: u16, italic: bool) -> Self {
+        match (weight >= 600, italic) {
+            (false, false) => Self::Regular,
+            (true, false) => Self::Bold,
+            (false, true) => Self::Italic,
+            (true, true) => Self::BoldItalic,
+        }
+    }
+    /// The style to use instead if this one isn't available
+    fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Regular => None,
+            Self::Bold | Self::Italic => Some(Self::Regular),
+            Self::BoldItalic => Some(Self::Bold),
+        }
+    }
+}
+
+/// The font identifier used for the `style` variant of `base`, e.g. `MyFont-BoldItalic`
+pub fn variant_font_name(base: &str, style: FontStyle) -> String {
+    format!("{base}-{}", style.label().replace(' ', ""))
+}
+
+/// The name of the family created for the `style` variant of `base`
+///
+/// The regular variant gets the base name (`MyFont`), the others append the style
+/// (`MyFont Bold`, `MyFont Italic`, `MyFont Bold Italic`).
+pub fn variant_family_name(base: &str, style: FontStyle) -> String {
+    match style {
+        FontStyle::Regular => base.to_owned(),
+        _ => format!("{base} {}", style.label()),
+    }
+}
+
+/// The family created for the `style` variant of `base`
+pub fn variant_family(base: &str, style: FontStyle) -> FontFamily {
+    FontFamily::Name(variant_family_name(base, style).into())
+}
+
+/// The family created for the bold variant of `base`
+pub fn bold_family_for(base: &str) -> FontFamily {
+    variant_family(base, FontStyle::Bold)
+}
+
+/// The family created for the italic variant of `base`
+pub fn italic_family_for(base: &str) -> FontFamily {
+    variant_family(base, FontStyle::Italic)
+}
+
+/// Maps weight and style requests onto the variant families of a typeface
+///
+/// Styles without a family fall back to the closest available one
+/// (bold italic to bold, bold and italic to regular).
+///
+/// ```
+/// # let font_defs = egui::FontDefinitions::default();
+/// let variants = egui_fontcfg::FontVariants::from_defs(&font_defs, "MyFont");
+/// let heading = variants.rich_text("Hello", 700, false, 24.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FontVariants {
+    base: String,
+    available: [bool; 4],
+}
+
+impl FontVariants {
+    /// Variants of `base`, assuming all of them have families
+    pub fn new(base: &str) -> Self {
+        Self {
+            base: base.to_owned(),
+            available: [true; 4],
+        }
+    }
+    /// Variants of `base`, using only the families present in `font_defs`
+    pub fn from_defs(font_defs: &FontDefinitions, base: &str) -> Self {
+        Self {
+            base: base.to_owned(),
+            available: FontStyle::ALL.map(|style| {
+                font_defs
+                    .families
+                    .contains_key(&variant_family(base, style))
+            }),
+        }
+    }
+    /// The family to use for `style`
+    ///
+    /// If no variant is available at all, this is [`FontFamily::Proportional`].
+    pub fn family(&self, style: FontStyle) -> FontFamily {
+        let mut style = Some(style);
+        while let Some(s) = style {
+            let idx = FontStyle::ALL.iter().position(|st| *st == s).unwrap();
+            if self.available[idx] {
+                return variant_family(&self.base, s);
+            }
+            style = s.fallback();
+        }
+        FontFamily::Proportional
+    }
+    /// The font id for a numeric weight (see [`FontStyle::from_weight`]) and size
+    pub fn font_id(&self, weight: u16, italic: bool, size: f32) -> egui::FontId {
+        egui::FontId::new(size, self.family(FontStyle::from_weight(weight, italic)))
+    }
+    /// Rich text using the font picked by [`Self::font_id`]
+    pub fn rich_text(
+        &self,
+        text: impl Into<String>,
+        weight: u16,
+        italic: bool,
+        size: f32,
+    ) -> egui::RichText {
+        egui::RichText::new(text).font(self.font_id(weight, italic, size))
+    }
+}
+
+/// Reason registering style variants failed, see [`register_style_variants`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleVariantError {
+    /// The file of a variant couldn't be loaded
+    Load(FontLoadError),
+    /// A variant's font or family is already taken, or its identifier isn't valid
+    Rejected(FontConfigError),
+}
+
+impl std::fmt::Display for StyleVariantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load(e) => e.fmt(f),
+            Self::Rejected(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StyleVariantError {}
+
+/// A variant whose file was loaded, and whose font and family can be added without replacing
+/// anything
+pub(crate) struct LoadedVariant {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) data: FontData,
+    /// The family to create for the variant, if any
+    pub(crate) family: Option<String>,
+}
+
+/// Load the style variants of `base` from `files`, checking that none of their fonts
+/// (named after `font_base`) and families (named after `base`) exist yet
+pub(crate) fn load_style_variants(
+    config: &FontConfigMut,
+    source: Option<&dyn FontSource>,
+    (base, font_base): (&str, &str),
+    files: &[(FontStyle, &str)],
+    create_families: bool,
+) -> Result<Vec<LoadedVariant>, StyleVariantError> {
+    let mut loaded = Vec::new();
+    for &(style, path) in files {
+        let name = variant_font_name(font_base, style);
+        if config.font_defs.font_data.contains_key(&name) {
+            return Err(StyleVariantError::Rejected(FontConfigError::FontExists(
+                name,
+            )));
+        }
+        let family = create_families.then(|| variant_family_name(base, style));
+        if let Some(family) = &family {
+            let family = FontFamily::Name(family.as_str().into());
+            if config.font_defs.families.contains_key(&family) {
+                return Err(StyleVariantError::Rejected(FontConfigError::FamilyExists(
+                    family,
+                )));
+            }
+        }
+        let data = crate::source::load_custom_font(source, path).map_err(|e| {
+            StyleVariantError::Load(FontLoadError::new(
+                path,
+                format!("{} style: {}", style.label(), e.message),
+            ))
+        })?;
+        loaded.push(LoadedVariant {
+            name,
+            path: path.to_owned(),
+            data,
+            family,
+        });
+    }
+    Ok(loaded)
+}
+
+/// Register the style variants of the typeface `base` from the given font files
+///
+/// Each file is added as a font named by [`variant_font_name`]. If `create_families` is true,
+/// a named family ([`variant_family_name`]) is created for each variant as well, falling back to
+/// the fonts of the proportional family.
+///
+/// The files are loaded from `source`, or from the file system if it's `None`.
+/// All files are read, and the fonts and families checked not to exist yet, before anything
+/// is inserted, so on error the configuration is left untouched.
+pub fn register_style_variants(
+    config: &mut FontConfigMut,
+    source: Option<&dyn FontSource>,
+    base: &str,
+    files: &[(FontStyle, &str)],
+    create_families: bool,
+) -> Result<(), StyleVariantError> {
+    let loaded = load_style_variants(config, source, (base, base), files, create_families)?;
+    for variant in loaded {
+        let name = config
+            .insert_font(&variant.name, variant.data, Some(variant.path))
+            .map_err(StyleVariantError::Rejected)?;
+        if let Some(family) = variant.family {
+            config
+                .add_family(&family, &FamilyTemplate::PrimaryWithFallback(name))
+                .map_err(StyleVariantError::Rejected)?;
+        }
+    }
+    Ok(())
+}
+
+/// Style variants to register, as entered into the [`VariantForm`]
+pub(crate) struct VariantRequest {
+    /// Name of the typeface, sanitized but not namespaced
+    pub(crate) base: String,
+    pub(crate) files: Vec<(FontStyle, String)>,
+    pub(crate) create_families: bool,
+}
+
+/// State of the style variant registration form
+pub(crate) struct VariantForm {
+    base: String,
+    paths: [String; 4],
+    create_families: bool,
+}
+
+impl Default for VariantForm {
+    fn default() -> Self {
+        Self {
+            base: String::new(),
+            paths: Default::default(),
+            create_families: true,
+        }
+    }
+}
+
+impl VariantForm {
+    /// Show the form. Returns the variants to register once the user submits it.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        normalize: Option<fn(&str) -> String>,
+    ) -> Option<VariantRequest> {
+        let re = ui.add(egui::TextEdit::singleline(&mut self.base).hint_text("Typeface name"));
+        a11y_text_label(&re, "Typeface name of style variants", &self.base);
+        for (style, path) in FontStyle::ALL.into_iter().zip(&mut self.paths) {
+            let label = format!("Path to {} file", style.label());
+            let re = ui.add(egui::TextEdit::singleline(path).hint_text(&label));
+            a11y_text_label(&re, &label, path);
+        }
+        ui.checkbox(&mut self.create_families, "Create a family for each style");
+        let base = sanitize_font_name(&self.base, normalize);
+        let any_path = self.paths.iter().any(|p| !p.trim().is_empty());
+        let problem = match &base {
+            Err(e) => Some(e.to_string()),
+            Ok(_) if !any_path => Some("Provide the file of at least one style".to_owned()),
+            Ok(_) => None,
+        };
+        let mut re = ui.add_enabled(problem.is_none(), egui::Button::new("Add style variants"));
+        if let Some(problem) = &problem {
+            re = re.on_disabled_hover_text(problem);
+        }
+        let (Ok(base), true) = (base, re.clicked()) else {
+            return None;
+        };
+        let files = FontStyle::ALL
+            .into_iter()
+            .zip(&self.paths)
+            .filter(|(_, path)| !path.trim().is_empty())
+            .map(|(style, path)| (style, path.trim().to_owned()))
+            .collect();
+        Some(VariantRequest {
+            base,
+            files,
+            create_families: self.create_families,
+        })
+    }
+    /// Empty the form, after its variants were registered
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{register_style_variants, FontStyle, StyleVariantError},
+        crate::{CustomFontPaths, FontConfig, FontConfigError, FontLoadError, FontSource},
+        egui::{FontDefinitions, FontFamily},
+    };
+
+    fn hack(path: &str) -> Result<Vec<u8>, FontLoadError> {
+        match path {
+            "hack.ttf" => Ok(FontDefinitions::default().font_data["Hack"].font.to_vec()),
+            _ => Err(FontLoadError::new(path, "Not found")),
+        }
+    }
+
+    const FILES: [(FontStyle, &str); 2] = [
+        (FontStyle::Regular, "hack.ttf"),
+        (FontStyle::Bold, "hack.ttf"),
+    ];
+
+    #[test]
+    fn registers_variants() {
+        let mut config = FontConfig::new(FontDefinitions::default(), CustomFontPaths::default());
+        let source: &dyn FontSource = &hack;
+        register_style_variants(&mut config.edit(), Some(source), "Mine", &FILES, true).unwrap();
+        assert_eq!(
+            config.custom.get("Mine-Bold").map(String::as_str),
+            Some("hack.ttf")
+        );
+        let bold = &config.font_defs.families[&FontFamily::Name("Mine Bold".into())];
+        assert_eq!(bold[0], "Mine-Bold");
+        assert!(
+            config.font_defs.families[&FontFamily::Name("Mine".into())][1..]
+                .iter()
+                .eq(&config.font_defs.families[&FontFamily::Proportional])
+        );
+    }
+
+    #[test]
+    fn reports_collisions() {
+        let mut config = FontConfig::new(FontDefinitions::default(), CustomFontPaths::default());
+        let source: &dyn FontSource = &hack;
+        let taken = FontFamily::Name("Mine Bold".into());
+        config.font_defs.families.insert(taken.clone(), Vec::new());
+        assert_eq!(
+            register_style_variants(&mut config.edit(), Some(source), "Mine", &FILES, true),
+            Err(StyleVariantError::Rejected(FontConfigError::FamilyExists(
+                taken.clone()
+            )))
+        );
+        assert!(config.font_defs.families[&taken].is_empty());
+        assert!(!config.font_defs.font_data.contains_key("Mine-Regular"));
+
+        register_style_variants(&mut config.edit(), Some(source), "Mine", &FILES, false).unwrap();
+        let before = config.font_defs.clone();
+        assert_eq!(
+            register_style_variants(&mut config.edit(), Some(source), "Mine", &FILES, false),
+            Err(StyleVariantError::Rejected(FontConfigError::FontExists(
+                "Mine-Regular".into()
+            )))
+        );
+        assert_eq!(config.font_defs.families, before.families);
+    }
+}
@@ -0,0 +1,182 @@
+//! Keeping the zoom factor and text style sizes from compounding
+
+use {
+    egui::{TextStyle, Theme},
+    std::collections::BTreeMap,
+};
+
+/// The text style sizes of a theme at a scale of 1, as last set by [`set_text_scale`]
+///
+/// Scaling starts over from these rather than from the current, rounded sizes, so zooming in
+/// and out repeatedly doesn't drift.
+#[derive(Clone, Default)]
+struct BaseSizes(BTreeMap<TextStyle, f32>);
+
+fn base_sizes_id(theme: Theme) -> egui::Id {
+    egui::Id::new(("egui_fontcfg_base_text_sizes", theme == Theme::Dark))
+}
+
+/// How much the text styles of `style` are scaled compared to egui's defaults, judged by the
+/// body text size
+fn style_scale(style: &egui::Style) -> f32 {
+    let default = TextStyle::Body.resolve(&egui::Style::default()).size;
+    style
+        .text_styles
+        .get(&TextStyle::Body)
+        .map_or(1.0, |font_id| font_id.size / default)
+}
+
+/// How much the text styles of the context are scaled compared to egui's defaults,
+/// judged by the body text size
+pub(crate) fn text_scale(ctx: &egui::Context) -> f32 {
+    style_scale(&ctx.style())
+}
+
+/// Multiply the sizes of all text styles of the context by `factor`, in the styles of both
+/// themes
+pub(crate) fn scale_text_styles(ctx: &egui::Context, factor: f32) {
+    for theme in [Theme::Dark, Theme::Light] {
+        let scale = style_scale(&ctx.style_of(theme));
+        set_theme_text_scale(ctx, theme, scale * factor);
+    }
+}
+
+/// Scale the text styles of the context so the body text is `scale` times egui's default
+/// size, in the styles of both themes
+///
+/// The styles keep their families, and their sizes relative to each other, including the
+/// ones the application set itself.
+pub(crate) fn set_text_scale(ctx: &egui::Context, scale: f32) {
+    for theme in [Theme::Dark, Theme::Light] {
+        set_theme_text_scale(ctx, theme, scale);
+    }
+}
+
+fn set_theme_text_scale(ctx: &egui::Context, theme: Theme, scale: f32) {
+    let id = base_sizes_id(theme);
+    let mut base = ctx
+        .data(|d| d.get_temp::<BaseSizes>(id))
+        .unwrap_or_default();
+    ctx.style_mut_of(theme, |style| {
+        let current = style_scale(style);
+        for (text_style, font_id) in &mut style.text_styles {
+            // Sizes that changed since they were last scaled, or that are new, were set by the
+            // application, so they are taken as they are
+            let size = match base.0.get(text_style) {
+                Some(&size) if (size * current - font_id.size).abs() < 0.01 => size,
+                _ => font_id.size / current,
+            };
+            base.0.insert(text_style.clone(), size);
+            font_id.size = size * scale;
+        }
+    });
+    ctx.data_mut(|d| d.insert_temp(id, base));
+}
+
+fn approx_one(v: f32) -> bool {
+    (v - 1.0).abs() < 0.005
+}
+
+/// Show the zoom factor and text style scale of the context, offering to fold one into
+/// the other
+pub(crate) fn zoom_ui(ui: &mut egui::Ui, high_contrast: bool) {
+    let ctx = ui.ctx().clone();
+    let zoom = ctx.zoom_factor();
+    let scale = text_scale(&ctx);
+    egui::Grid::new("zoom_and_text_size")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Zoom");
+            ui.label(format!("{:.0}%", zoom * 100.0))
+                .on_hover_text("Scales the whole ui, including text");
+            ui.end_row();
+            ui.label("Text sizes");
+            ui.label(format!("{:.0}%", scale * 100.0))
+                .on_hover_text("Body text size compared to egui's default");
+            ui.end_row();
+            ui.label("Text appears at");
+            ui.strong(format!("{:.0}%", zoom * scale * 100.0));
+            ui.end_row();
+        });
+    if !approx_one(zoom) && !approx_one(scale) {
+        crate::ui::status_label(
+            ui,
+            high_contrast,
+            crate::ui::Severity::Warning,
+            "Both the zoom and the text sizes are scaled, so their effects multiply",
+        );
+    }
+    ui.horizontal_wrapped(|ui| {
+        if ui
+            .add_enabled(
+                !approx_one(zoom),
+                egui::Button::new("Move zoom into text sizes"),
+            )
+            .on_hover_text("Only scale text, leaving the rest of the ui at its normal size")
+            .clicked()
+        {
+            scale_text_styles(&ctx, zoom);
+            ctx.set_zoom_factor(1.0);
+        }
+        if ui
+            .add_enabled(
+                !approx_one(scale),
+                egui::Button::new("Move text sizes into zoom"),
+            )
+            .on_hover_text("Restore the default text sizes, and zoom the whole ui instead")
+            .clicked()
+        {
+            set_text_scale(&ctx, 1.0);
+            ctx.set_zoom_factor(zoom * scale);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{scale_text_styles, set_text_scale, text_scale},
+        egui::{Style, Theme},
+    };
+
+    #[test]
+    fn scales_both_themes() {
+        let ctx = egui::Context::default();
+        scale_text_styles(&ctx, 1.5);
+        scale_text_styles(&ctx, 1.5);
+        assert!((text_scale(&ctx) - 2.25).abs() < 1e-4);
+        let body = |style: &Style| style.text_styles[&egui::TextStyle::Body].size;
+        for theme in [Theme::Dark, Theme::Light] {
+            let scale = body(&ctx.style_of(theme)) / body(&Style::default());
+            assert!((scale - 2.25).abs() < 1e-4);
+        }
+        set_text_scale(&ctx, 1.0);
+        for theme in [Theme::Dark, Theme::Light] {
+            assert_eq!(
+                ctx.style_of(theme).text_styles,
+                Style::default().text_styles
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_applications_text_styles() {
+        let ctx = egui::Context::default();
+        let heading = egui::TextStyle::Name("Heading".into());
+        let id = egui::FontId::new(30.0, egui::FontFamily::Name("Heading".into()));
+        ctx.all_styles_mut(|style| {
+            style.text_styles.insert(heading.clone(), id.clone());
+        });
+        let styles = ctx.style().text_styles.clone();
+        for percent in [50, 137, 200, 73, 161] {
+            set_text_scale(&ctx, percent as f32 / 100.0);
+        }
+        let scaled = &ctx.style().text_styles[&heading];
+        assert_eq!(scaled.family, id.family);
+        assert!((scaled.size - 30.0 * 1.61).abs() < 1e-3);
+        set_text_scale(&ctx, 1.0);
+        for theme in [Theme::Dark, Theme::Light] {
+            assert_eq!(ctx.style_of(theme).text_styles, styles);
+        }
+    }
+}
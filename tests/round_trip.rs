@@ -0,0 +1,174 @@
+//! The whole crate working together: a custom font source, loading by priority, presets,
+//! showing the dialog, and saving and restoring the configuration
+
+use {
+    egui::{FontDefinitions, FontFamily},
+    egui_fontcfg::{
+        dry_run_apply, load_fonts_by_priority, new_family, report_family_use, BundledFont,
+        CustomFontPaths, FamilyTemplate, FontCfgUi, FontCfgWindow, FontConfigSnapshot,
+        FontDefsUiMsg, FontLoadError, FontOrigin, FontPreset,
+    },
+    std::collections::HashMap,
+};
+
+/// Font data the application ships with
+const HACK: &[u8] = include_bytes!("fixtures/Hack-Regular.ttf");
+
+/// Prefix of the paths served from the application's own assets rather than the file system
+const ASSET_PREFIX: &str = "asset:";
+
+/// What the application keeps around between frames
+struct App {
+    font_defs: FontDefinitions,
+    custom: CustomFontPaths,
+    window: FontCfgWindow,
+    /// The persisted configuration, standing in for a file or local storage
+    saved: Option<String>,
+}
+
+#[test]
+fn round_trip() {
+    let ctx = egui::Context::default();
+    let assets = HashMap::from([(format!("{ASSET_PREFIX}Hack"), HACK)]);
+
+    // A font the user added from disk in an earlier session
+    let user_font = std::env::temp_dir().join("egui-fontcfg-round-trip-font.ttf");
+    std::fs::write(&user_font, HACK).unwrap();
+    let user_font = user_font.display().to_string();
+
+    let mut font_defs = FontDefinitions::default();
+    new_family(
+        &mut font_defs,
+        "Heading",
+        &FamilyTemplate::CopyOfProportional,
+    );
+    let mut custom = CustomFontPaths::default();
+    custom.insert("user-font".into(), user_font.clone());
+    custom.insert("asset-font".into(), format!("{ASSET_PREFIX}Hack"));
+    font_defs
+        .families
+        .entry(FontFamily::Proportional)
+        .or_default()
+        .push("user-font".into());
+
+    // Custom paths starting with `asset:` are served from memory, the rest from disk
+    let source = move |path: &str| -> Result<Vec<u8>, FontLoadError> {
+        match assets.get(path) {
+            Some(data) => Ok(data.to_vec()),
+            None => std::fs::read(path).map_err(|e| FontLoadError::new(path, e)),
+        }
+    };
+    let bundled = BundledFont::new("bundled-hack", HACK, "Hack, shipped with the app");
+    let resolutions = load_fonts_by_priority(
+        &FontOrigin::DEFAULT_PRIORITY,
+        Some(&source),
+        &custom,
+        std::slice::from_ref(&bundled),
+        &mut font_defs,
+    );
+    for name in ["user-font", "asset-font"] {
+        let resolution = resolutions.iter().find(|r| r.name == name).unwrap();
+        let (origin, path) = resolution.winner.as_ref().unwrap();
+        assert_eq!(*origin, FontOrigin::CustomPath);
+        assert_eq!(Some(path), custom.get(name));
+        assert_eq!(*font_defs.font_data[name].font, *HACK);
+    }
+
+    let mut ui = FontCfgUi::default()
+        .font_source(source.clone())
+        .collect_events(true)
+        .bundled_font(bundled)
+        .font_resolutions(resolutions)
+        .family_order(vec![FontFamily::Name("Heading".into())])
+        .max_size([400.0, 600.0]);
+    // Presets the user can switch between, e.g. with hotkeys or in `FontCfgUi::show_kiosk`
+    ui.presets_mut().insert(
+        "Default",
+        FontPreset {
+            font_defs: font_defs.clone(),
+            custom: custom.clone(),
+        },
+    );
+    ui.presets_mut().insert(
+        "egui defaults",
+        FontPreset {
+            font_defs: FontDefinitions::default(),
+            custom: CustomFontPaths::default(),
+        },
+    );
+    let mut window = FontCfgWindow::new(ui);
+    window.open = true;
+
+    let report = dry_run_apply(&font_defs);
+    assert!(report.font_bytes >= 2 * HACK.len());
+    egui_fontcfg::apply(&ctx, &font_defs).unwrap();
+
+    let mut app = App {
+        font_defs,
+        custom,
+        window,
+        saved: None,
+    };
+    for _ in 0..3 {
+        frame(&ctx, &mut app);
+    }
+    // What the save button would do
+    save(&mut app);
+
+    // Restore the saved configuration, like on the next start
+    let text = app.saved.as_deref().unwrap();
+    let snapshot = FontConfigSnapshot::parse(text).unwrap();
+    let mut restored = FontDefinitions::default();
+    let mut restored_custom = CustomFontPaths::default();
+    snapshot
+        .apply_to_defs(Some(&source), &mut restored, &mut restored_custom)
+        .unwrap();
+    assert_eq!(restored.families, app.font_defs.families);
+    assert_eq!(
+        snapshot.family_order()[0],
+        FontFamily::Name("Heading".into())
+    );
+    assert_eq!(restored_custom.get("user-font"), Some(&user_font));
+    assert_eq!(*restored.font_data["asset-font"].font, *HACK);
+
+    // Switch presets like a user would
+    let presets = app.window.ui_mut().presets_mut();
+    presets.apply_preset(&ctx, "egui defaults").unwrap();
+    assert_eq!(presets.active(), Some("egui defaults"));
+
+    let _ = std::fs::remove_file(&user_font);
+}
+
+/// One frame of the application
+fn frame(ctx: &egui::Context, app: &mut App) {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(1024.0, 768.0),
+        )),
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Reporting which named families are used lets presets keep their fonts
+            report_family_use(ctx, &FontFamily::Name("Heading".into()), 1);
+            ui.label(egui::RichText::new("Heading").family(FontFamily::Name("Heading".into())));
+        });
+        if let FontDefsUiMsg::SaveRequest =
+            app.window
+                .show(ctx, &mut app.font_defs, Some(&mut app.custom))
+        {
+            save(app);
+        }
+        // Nothing was edited, so there's nothing to sync
+        assert!(app.window.ui_mut().take_events().is_empty());
+    });
+}
+
+/// Persist the configuration, in the order the user arranged the families in
+fn save(app: &mut App) {
+    let mut snapshot = FontConfigSnapshot::from_defs(&app.font_defs, &app.custom);
+    let order = app.window.ui_mut().family_display_order(&app.font_defs);
+    snapshot.order_families(&order);
+    app.saved = Some(snapshot.to_text());
+}
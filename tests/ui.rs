@@ -0,0 +1,245 @@
+//! Driving the dialog like a user would, finding the controls by their accessible names
+//!
+//! Adding fonts by path needs the `fs` feature.
+#![cfg(feature = "fs")]
+
+use {
+    egui::{FontDefinitions, FontFamily},
+    egui_fontcfg::{
+        BundledFont, ConfirmationPolicy, CustomFontPaths, FontCfgUi, FontCfgWindow, FontDefsUiMsg,
+    },
+    egui_kittest::{kittest::Queryable, Harness},
+};
+
+/// What the application keeps around between frames
+struct State {
+    font_defs: FontDefinitions,
+    custom: CustomFontPaths,
+    ui: FontCfgUi,
+    /// The messages the dialog returned, other than [`FontDefsUiMsg::None`]
+    msgs: Vec<FontDefsUiMsg>,
+}
+
+fn fixture_path() -> String {
+    concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/Hack-Regular.ttf"
+    )
+    .to_owned()
+}
+
+/// egui's default fonts, without Hack, so adding the Hack fixture isn't a duplicate
+fn defs_without_hack() -> FontDefinitions {
+    let mut defs = FontDefinitions::default();
+    defs.font_data.remove("Hack");
+    for fonts in defs.families.values_mut() {
+        fonts.retain(|f| f != "Hack");
+    }
+    defs
+}
+
+fn harness<'a>(ui: FontCfgUi) -> Harness<'a, State> {
+    let state = State {
+        font_defs: defs_without_hack(),
+        custom: CustomFontPaths::default(),
+        ui,
+        msgs: Vec::new(),
+    };
+    let mut harness = Harness::new_ui_state(
+        |ui, state: &mut State| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let msg = state
+                    .ui
+                    .show(ui, &mut state.font_defs, Some(&mut state.custom));
+                if !matches!(msg, FontDefsUiMsg::None) {
+                    state.msgs.push(msg);
+                }
+            });
+        },
+        state,
+    );
+    harness.set_size(egui::vec2(800.0, 2000.0));
+    harness.run();
+    harness
+}
+
+/// Open the add new font form and fill it in with the fixture, to be added as `name`
+fn fill_add_form(harness: &mut Harness<State>, name: &str) {
+    harness.get_by_label("Open add new font form").click();
+    harness.run();
+    harness.get_by_label("Identifier for new font").focus();
+    harness.run();
+    harness
+        .get_by_label("Identifier for new font")
+        .type_text(name);
+    harness.run();
+    harness.get_by_label("Path to new font").focus();
+    harness.run();
+    harness
+        .get_by_label("Path to new font")
+        .type_text(fixture_path());
+    harness.run();
+}
+
+#[test]
+fn add_font_and_edit_families() {
+    let mut harness = harness(FontCfgUi::default());
+    fill_add_form(&mut harness, "Code");
+    harness
+        .get_by_label("Also add new fonts to Proportional family")
+        .click();
+    harness.run();
+    harness.get_by_label("Add new font").click();
+    harness.run();
+
+    let state = harness.state();
+    assert_eq!(state.custom.get("Code"), Some(&fixture_path()));
+    let fixture = std::fs::read(fixture_path()).unwrap();
+    assert_eq!(*state.font_defs.font_data["Code"].font, *fixture);
+    assert!(state.font_defs.families[&FontFamily::Proportional].contains(&"Code".to_owned()));
+    assert!(!state.font_defs.families[&FontFamily::Monospace].contains(&"Code".to_owned()));
+
+    harness
+        .get_by_label("Remove Ubuntu-Light from Proportional family")
+        .click();
+    harness.run();
+    harness.get_by_label("Apply").click();
+    harness.run();
+
+    let state = harness.state();
+    assert!(state
+        .msgs
+        .iter()
+        .any(|msg| matches!(msg, FontDefsUiMsg::Applied { .. })));
+    let mut expected = defs_without_hack();
+    expected.font_data.insert(
+        "Code".into(),
+        std::sync::Arc::new(egui::FontData::from_owned(fixture)),
+    );
+    let proportional = expected
+        .families
+        .get_mut(&FontFamily::Proportional)
+        .unwrap();
+    proportional.retain(|f| f != "Ubuntu-Light");
+    proportional.push("Code".into());
+    assert_eq!(state.font_defs.families, expected.families);
+    assert_eq!(
+        state.font_defs.font_data.keys().collect::<Vec<_>>(),
+        expected.font_data.keys().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn confirmed_overwrite_replaces_the_font() {
+    let ui = FontCfgUi::default().confirmation_policy(ConfirmationPolicy::ALL);
+    let mut harness = harness(ui);
+    fill_add_form(&mut harness, "Ubuntu-Light");
+    harness.get_by_label("Add new font").click();
+    harness.run();
+    // Nothing is replaced before the user confirms
+    let fixture = std::fs::read(fixture_path()).unwrap();
+    assert_ne!(
+        *harness.state().font_defs.font_data["Ubuntu-Light"].font,
+        *fixture
+    );
+
+    harness
+        .get_by_label("Confirm: A font called Ubuntu-Light already exists. Replace it?")
+        .click();
+    harness.run();
+
+    let state = harness.state();
+    assert_eq!(*state.font_defs.font_data["Ubuntu-Light"].font, *fixture);
+    assert_eq!(state.custom.get("Ubuntu-Light"), Some(&fixture_path()));
+    // The family entries of the replaced font stay
+    assert!(
+        state.font_defs.families[&FontFamily::Proportional].contains(&"Ubuntu-Light".to_owned())
+    );
+}
+
+#[test]
+fn bundled_fonts_go_into_the_user_namespace() {
+    const HACK: &[u8] = include_bytes!("fixtures/Hack-Regular.ttf");
+    let ui = FontCfgUi::default()
+        .user_namespace("user")
+        .bundled_font(BundledFont::new("Bundled", HACK, "Hack"));
+    let mut harness = harness(ui);
+    harness.get_by_label("Open add new font form").click();
+    harness.run();
+    harness.get_by_label("Bundled").click();
+    harness.run();
+    harness.get_by_label("Add").click();
+    harness.run();
+
+    let state = harness.state();
+    let name = egui_fontcfg::namespaced("user", "Bundled");
+    assert_eq!(*state.font_defs.font_data[&name].font, *HACK);
+    assert!(!state.font_defs.font_data.contains_key("Bundled"));
+    assert_eq!(
+        state.custom.get(&name).map(String::as_str),
+        Some("bundled:Bundled")
+    );
+}
+
+/// Press `key` with `modifiers` during the next frame
+fn press(
+    harness: &mut Harness<(FontDefinitions, FontCfgWindow)>,
+    key: egui::Key,
+    modifiers: egui::Modifiers,
+) {
+    harness.input_mut().events.push(egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers,
+    });
+    harness.run();
+}
+
+#[test]
+fn undo_and_redo_shortcuts() {
+    let mut window = FontCfgWindow::new(FontCfgUi::default());
+    window.open = true;
+    let mut harness = Harness::new_state(
+        |ctx, (font_defs, window): &mut (FontDefinitions, FontCfgWindow)| {
+            window.show(ctx, font_defs, None);
+        },
+        (defs_without_hack(), window),
+    );
+    harness.set_size(egui::vec2(800.0, 2000.0));
+    harness.run();
+    harness
+        .get_by_label("Remove Ubuntu-Light from Proportional family")
+        .click();
+    harness.run();
+    let proportional = |harness: &Harness<(FontDefinitions, FontCfgWindow)>| {
+        harness.state().0.families[&FontFamily::Proportional].contains(&"Ubuntu-Light".into())
+    };
+    assert!(!proportional(&harness));
+
+    press(&mut harness, egui::Key::Z, egui::Modifiers::COMMAND);
+    assert!(proportional(&harness));
+    press(
+        &mut harness,
+        egui::Key::Z,
+        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+    );
+    assert!(!proportional(&harness));
+    press(&mut harness, egui::Key::Z, egui::Modifiers::COMMAND);
+    press(&mut harness, egui::Key::Y, egui::Modifiers::COMMAND);
+    assert!(!proportional(&harness));
+}
+
+#[test]
+fn sub_form_fields_report_focus() {
+    let mut harness = harness(FontCfgUi::default());
+    harness.get_by_label("Add style variants").click();
+    harness.run();
+    assert!(!harness.state().ui.has_text_focus());
+    harness
+        .get_by_label("Typeface name of style variants")
+        .focus();
+    harness.run();
+    assert!(harness.state().ui.has_text_focus());
+}